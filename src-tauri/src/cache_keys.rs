@@ -0,0 +1,115 @@
+//! Keys of per-path cache entries: indexed thumbnail folders and index state
+//! files. Keys used to come from `DefaultHasher`, whose output may change
+//! between Rust releases, so caches created with those keys are re-keyed once.
+
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+use std::sync::Mutex;
+
+use tauri::{AppHandle, Manager};
+use xxhash_rust::xxh3::{xxh3_128, xxh3_64};
+
+use crate::db::LibraryDatabase;
+use crate::{normalize_path_string, to_forward_slash_path};
+
+const CACHE_KEY_VERSION: &str = "2";
+const CACHE_KEY_VERSION_FILE: &str = ".key-version";
+
+static MIGRATION_CHECKED: Mutex<bool> = Mutex::new(false);
+
+/// Stable key of a path: XXH3 of the normalized, lowercased path.
+pub fn hash_path(path: &str) -> String {
+    let normalized = normalize_path_string(path).to_ascii_lowercase();
+    format!("{:016x}", xxh3_64(normalized.as_bytes()))
+}
+
+/// Key of a page thumbnail in the content-addressed store, derived from the
+/// page fingerprint reported by its source.
+pub fn content_key(fingerprint: &str) -> String {
+    format!("{:032x}", xxh3_128(fingerprint.as_bytes()))
+}
+
+fn legacy_hash_path(path: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    normalize_path_string(path)
+        .to_ascii_lowercase()
+        .hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+/// Re-keys caches written with legacy keys, once per installation. Runs at
+/// most once per process; a failed migration is retried on the next start.
+pub fn ensure_cache_keys_migrated(app: &AppHandle) {
+    let mut checked = MIGRATION_CHECKED
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if *checked {
+        return;
+    }
+    *checked = true;
+    if let Err(message) = migrate_legacy_cache_keys(app) {
+        eprintln!("[Thumbnails][Rust] Failed to migrate cache keys: {}", message);
+    }
+}
+
+fn migrate_legacy_cache_keys(app: &AppHandle) -> Result<(), String> {
+    let app_data = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to resolve app data dir: {e}"))?;
+    let indexed_root = app_data.join("thumbnails").join("indexed");
+    let version_file = indexed_root.join(CACHE_KEY_VERSION_FILE);
+    if fs::read_to_string(&version_file).is_ok_and(|version| version.trim() == CACHE_KEY_VERSION) {
+        return Ok(());
+    }
+
+    let state_dir = app_data.join("index-state");
+    let has_legacy_entries = [&indexed_root, &state_dir]
+        .iter()
+        .any(|dir| fs::read_dir(dir).is_ok_and(|mut entries| entries.next().is_some()));
+    if has_legacy_entries {
+        let database = app.state::<LibraryDatabase>();
+        let comic_paths = database.comic_paths(app)?;
+        let index_paths = database.index_paths(app)?;
+
+        let mut renamed_dirs = Vec::new();
+        for comic_path in &comic_paths {
+            let (legacy_dir, dir) = (
+                indexed_root.join(legacy_hash_path(comic_path)),
+                indexed_root.join(hash_path(comic_path)),
+            );
+            if rename_entry(&legacy_dir, &dir)? {
+                renamed_dirs.push((to_forward_slash_path(&legacy_dir), to_forward_slash_path(&dir)));
+            }
+        }
+        for index_path in &index_paths {
+            rename_entry(
+                &state_dir.join(format!("{}.json", legacy_hash_path(index_path))),
+                &state_dir.join(format!("{}.json", hash_path(index_path))),
+            )?;
+        }
+        database.rekey_thumbnail_dirs(app, &renamed_dirs)?;
+        println!(
+            "[Thumbnails][Rust] Re-keyed {} thumbnail folders of {} known comics",
+            renamed_dirs.len(),
+            comic_paths.len()
+        );
+    }
+
+    fs::create_dir_all(&indexed_root)
+        .map_err(|e| format!("Failed to create indexed thumbnail dir {}: {e}", indexed_root.display()))?;
+    fs::write(&version_file, CACHE_KEY_VERSION)
+        .map_err(|e| format!("Failed to write {}: {e}", version_file.display()))
+}
+
+/// Moves a legacy entry to its new key. Returns whether anything was moved.
+fn rename_entry(legacy: &Path, target: &Path) -> Result<bool, String> {
+    if legacy == target || !legacy.exists() || target.exists() {
+        return Ok(false);
+    }
+    fs::rename(legacy, target)
+        .map(|_| true)
+        .map_err(|e| format!("Failed to move {} to {}: {e}", legacy.display(), target.display()))
+}
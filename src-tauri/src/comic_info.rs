@@ -0,0 +1,500 @@
+//! Parsing of `ComicInfo.xml` (the ComicRack metadata schema) embedded in
+//! archives and comic folders.
+
+use std::collections::HashSet;
+
+use quick_xml::events::{BytesDecl, BytesEnd, BytesStart, BytesText, Event};
+use quick_xml::{Reader, Writer};
+use serde::{Deserialize, Serialize};
+
+pub const COMIC_INFO_FILE_NAME: &str = "ComicInfo.xml";
+
+#[derive(Debug, Default, Clone)]
+pub struct ComicInfo {
+    pub series: Option<String>,
+    pub number: Option<String>,
+    pub volume: Option<i64>,
+    pub writer: Option<String>,
+    pub penciller: Option<String>,
+    pub publisher: Option<String>,
+    pub year: Option<i64>,
+    pub summary: Option<String>,
+    pub genre: Option<String>,
+    pub manga: Option<bool>,
+    /// `(page_number, type)` pairs from the `<Pages>` block, 1-based.
+    pub page_types: Vec<(i64, String)>,
+}
+
+/// Edited metadata to write back into a ComicInfo.xml. Fields that are `None`
+/// are removed from the document, everything the app does not manage is kept.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComicInfoUpdate {
+    pub series: Option<String>,
+    pub number: Option<String>,
+    pub volume: Option<i64>,
+    pub writer: Option<String>,
+    pub penciller: Option<String>,
+    pub publisher: Option<String>,
+    pub year: Option<i64>,
+    pub summary: Option<String>,
+    pub genre: Option<String>,
+    pub manga: Option<bool>,
+}
+
+/// Elements written from [`ComicInfoUpdate`], in ComicInfo schema order.
+const MANAGED_ELEMENTS: [&str; 10] = [
+    "Series",
+    "Number",
+    "Volume",
+    "Summary",
+    "Year",
+    "Writer",
+    "Penciller",
+    "Publisher",
+    "Genre",
+    "Manga",
+];
+
+impl ComicInfoUpdate {
+    /// Value to write for a managed element, given its current raw content.
+    fn value_of(&self, tag: &str, existing: &str) -> Option<String> {
+        match tag {
+            "Series" => self.series.clone(),
+            "Number" => self.number.clone(),
+            "Volume" => self.volume.map(|volume| volume.to_string()),
+            "Summary" => self.summary.clone(),
+            "Year" => self.year.map(|year| year.to_string()),
+            "Writer" => self.writer.clone(),
+            "Penciller" => self.penciller.clone(),
+            "Publisher" => self.publisher.clone(),
+            "Genre" => self.genre.clone(),
+            // Keep "YesAndRightToLeft" when the comic is still marked as manga.
+            "Manga" => self.manga.map(|manga| match manga {
+                true if existing.trim().starts_with("Yes") => existing.trim().to_string(),
+                true => "Yes".to_string(),
+                false => "No".to_string(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Which metadata wins when both ComicInfo.xml and the index path pattern
+/// provide a value.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MetadataPrecedence {
+    #[default]
+    ComicInfo,
+    Pattern,
+}
+
+/// Whether an archive entry or file is a ComicInfo.xml, in any directory.
+pub fn is_comic_info_name(path: &str) -> bool {
+    path.rsplit(['/', '\\'])
+        .next()
+        .map(|name| name.eq_ignore_ascii_case(COMIC_INFO_FILE_NAME))
+        .unwrap_or(false)
+}
+
+pub fn parse_comic_info(bytes: &[u8]) -> Result<ComicInfo, String> {
+    let mut reader = Reader::from_reader(bytes);
+
+    let mut info = ComicInfo::default();
+    let mut current_tag: Option<String> = None;
+    // Text and CDATA sections of the current element, which may be split
+    // across several events.
+    let mut text = String::new();
+    let mut buf = Vec::new();
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(element)) => {
+                let name = String::from_utf8_lossy(element.name().as_ref()).to_string();
+                if name == "Page" {
+                    push_page_type(&mut info, &element);
+                }
+                current_tag = Some(name);
+                text.clear();
+            }
+            Ok(Event::Empty(element)) => {
+                if element.name().as_ref() == b"Page" {
+                    push_page_type(&mut info, &element);
+                }
+            }
+            Ok(Event::Text(content)) if current_tag.is_some() => {
+                let content = content
+                    .unescape()
+                    .map_err(|e| format!("Failed to decode ComicInfo.xml text: {e}"))?;
+                text.push_str(&content);
+            }
+            Ok(Event::CData(content)) if current_tag.is_some() => {
+                text.push_str(&String::from_utf8_lossy(&content));
+            }
+            Ok(Event::End(_)) => {
+                let value = text.trim();
+                if let (Some(tag), false) = (current_tag.take(), value.is_empty()) {
+                    apply_field(&mut info, &tag, value.to_string());
+                }
+                text.clear();
+            }
+            Ok(Event::Eof) => break,
+            Ok(_) => {}
+            Err(e) => {
+                return Err(format!(
+                    "Failed to parse ComicInfo.xml at position {}: {e}",
+                    reader.error_position()
+                ))
+            }
+        }
+        buf.clear();
+    }
+
+    Ok(info)
+}
+
+fn apply_field(info: &mut ComicInfo, tag: &str, value: String) {
+    match tag {
+        "Series" => info.series = Some(value),
+        "Number" => info.number = Some(value),
+        "Volume" => info.volume = value.parse().ok(),
+        "Writer" => info.writer = Some(value),
+        "Penciller" => info.penciller = Some(value),
+        "Publisher" => info.publisher = Some(value),
+        "Year" => info.year = value.parse().ok().filter(|year| *year > 0),
+        "Summary" => info.summary = Some(value),
+        "Genre" => info.genre = Some(value),
+        "Manga" => {
+            info.manga = match value.as_str() {
+                "Yes" | "YesAndRightToLeft" => Some(true),
+                "No" => Some(false),
+                _ => None,
+            }
+        }
+        _ => {}
+    }
+}
+
+fn push_page_type(info: &mut ComicInfo, element: &BytesStart) {
+    let mut image_index = None;
+    let mut page_type = None;
+    for attribute in element.attributes().flatten() {
+        let Ok(value) = attribute.unescape_value() else {
+            continue;
+        };
+        match attribute.key.as_ref() {
+            b"Image" => image_index = value.trim().parse::<i64>().ok(),
+            b"Type" => page_type = Some(value.trim().to_string()),
+            _ => {}
+        }
+    }
+
+    if let (Some(image_index), Some(page_type)) = (image_index, page_type) {
+        if image_index >= 0 && !page_type.is_empty() {
+            info.page_types.push((image_index + 1, page_type));
+        }
+    }
+}
+
+/// Applies `update` to an existing ComicInfo.xml, or creates a new document
+/// when there is none. Managed elements are replaced in place and missing ones
+/// are added before `<Pages>`; all other content is passed through untouched.
+pub fn update_comic_info(existing: Option<&[u8]>, update: &ComicInfoUpdate) -> Result<Vec<u8>, String> {
+    match existing {
+        Some(bytes) => match rewrite_comic_info(bytes, update)? {
+            Some(xml) => Ok(xml),
+            None => new_comic_info(update),
+        },
+        None => new_comic_info(update),
+    }
+}
+
+fn new_comic_info(update: &ComicInfoUpdate) -> Result<Vec<u8>, String> {
+    let mut writer = Writer::new_with_indent(Vec::new(), b' ', 2);
+    let mut root = BytesStart::new("ComicInfo");
+    root.push_attribute(("xmlns:xsd", "http://www.w3.org/2001/XMLSchema"));
+    root.push_attribute(("xmlns:xsi", "http://www.w3.org/2001/XMLSchema-instance"));
+
+    writer
+        .write_event(Event::Decl(BytesDecl::new("1.0", Some("utf-8"), None)))
+        .and_then(|_| writer.write_event(Event::Start(root)))
+        .map_err(|e| format!("Failed to write ComicInfo.xml: {e}"))?;
+    for tag in MANAGED_ELEMENTS {
+        if let Some(value) = update.value_of(tag, "") {
+            write_element(&mut writer, tag, &value)?;
+        }
+    }
+    writer
+        .write_event(Event::End(BytesEnd::new("ComicInfo")))
+        .map_err(|e| format!("Failed to write ComicInfo.xml: {e}"))?;
+    Ok(writer.into_inner())
+}
+
+/// Streams the existing document through, swapping managed elements. Returns
+/// `None` if the document has no root element to update.
+fn rewrite_comic_info(bytes: &[u8], update: &ComicInfoUpdate) -> Result<Option<Vec<u8>>, String> {
+    let mut reader = Reader::from_reader(bytes);
+    let mut writer = Writer::new(Vec::new());
+    let mut written: HashSet<&str> = HashSet::new();
+    let mut depth = 0usize;
+    let mut has_root = false;
+    let mut appended = false;
+    // Whitespace between root children is held back so that removed elements
+    // don't leave empty lines behind.
+    let mut pending_whitespace: Option<Event> = None;
+    let mut child_indent: Option<Event> = None;
+
+    loop {
+        let event = reader.read_event().map_err(|e| {
+            format!(
+                "Failed to parse ComicInfo.xml at position {}: {e}",
+                reader.error_position()
+            )
+        })?;
+
+        match &event {
+            Event::Eof => break,
+            Event::Text(text) if depth == 1 && text.iter().all(u8::is_ascii_whitespace) => {
+                pending_whitespace = Some(event);
+                continue;
+            }
+            Event::Start(element) | Event::Empty(element) if depth == 1 => {
+                let name = String::from_utf8_lossy(element.name().as_ref()).to_string();
+                if pending_whitespace.is_some() {
+                    child_indent = pending_whitespace.clone();
+                }
+                if let Some(tag) = MANAGED_ELEMENTS.iter().copied().find(|tag| *tag == name) {
+                    let existing = match &event {
+                        Event::Start(element) => reader
+                            .read_text(element.to_end().name())
+                            .map_err(|e| format!("Failed to parse ComicInfo.xml element {}: {e}", tag))?
+                            .to_string(),
+                        _ => String::new(),
+                    };
+                    let whitespace = pending_whitespace.take();
+                    if written.insert(tag) {
+                        if let Some(value) = update.value_of(tag, &existing) {
+                            write_events(&mut writer, whitespace)?;
+                            write_element(&mut writer, tag, &value)?;
+                        }
+                    }
+                    continue;
+                }
+
+                if name == "Pages" && !appended {
+                    append_missing(&mut writer, update, &written, child_indent.clone())?;
+                    appended = true;
+                }
+                if matches!(event, Event::Start(_)) {
+                    depth += 1;
+                }
+            }
+            Event::Start(_) => {
+                has_root |= depth == 0;
+                depth += 1;
+            }
+            Event::End(_) => {
+                if depth == 1 && !appended {
+                    append_missing(&mut writer, update, &written, child_indent.clone())?;
+                    appended = true;
+                }
+                depth = depth.saturating_sub(1);
+            }
+            _ => {}
+        }
+
+        write_events(&mut writer, pending_whitespace.take())?;
+        write_events(&mut writer, Some(event))?;
+    }
+
+    Ok(has_root.then(|| writer.into_inner()))
+}
+
+fn append_missing(
+    writer: &mut Writer<Vec<u8>>,
+    update: &ComicInfoUpdate,
+    written: &HashSet<&str>,
+    indent: Option<Event>,
+) -> Result<(), String> {
+    for tag in MANAGED_ELEMENTS {
+        if written.contains(tag) {
+            continue;
+        }
+        if let Some(value) = update.value_of(tag, "") {
+            write_events(writer, indent.clone())?;
+            write_element(writer, tag, &value)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_events(writer: &mut Writer<Vec<u8>>, event: Option<Event>) -> Result<(), String> {
+    match event {
+        Some(event) => writer
+            .write_event(event)
+            .map_err(|e| format!("Failed to write ComicInfo.xml: {e}")),
+        None => Ok(()),
+    }
+}
+
+fn write_element(writer: &mut Writer<Vec<u8>>, tag: &str, value: &str) -> Result<(), String> {
+    writer
+        .create_element(tag)
+        .write_text_content(BytesText::new(value))
+        .map(|_| ())
+        .map_err(|e| format!("Failed to write ComicInfo.xml element {}: {e}", tag))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_managed_fields_and_page_types() {
+        let xml = br#"<?xml version="1.0" encoding="utf-8"?>
+<ComicInfo xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+  <Series>Tom &amp; Jerry</Series>
+  <Number>12</Number>
+  <Volume>3</Volume>
+  <Summary><![CDATA[A <b>chase</b>]]></Summary>
+  <Year>1999</Year>
+  <Writer>Hanna</Writer>
+  <Penciller>Barbera</Penciller>
+  <Publisher>MGM</Publisher>
+  <Genre>Comedy</Genre>
+  <Manga>YesAndRightToLeft</Manga>
+  <Pages>
+    <Page Image="0" Type="FrontCover" />
+    <Page Image="5" Type="Story"></Page>
+  </Pages>
+</ComicInfo>"#;
+
+        let info = parse_comic_info(xml).unwrap();
+
+        assert_eq!(info.series.as_deref(), Some("Tom & Jerry"));
+        assert_eq!(info.number.as_deref(), Some("12"));
+        assert_eq!(info.volume, Some(3));
+        assert_eq!(info.summary.as_deref(), Some("A <b>chase</b>"));
+        assert_eq!(info.year, Some(1999));
+        assert_eq!(info.writer.as_deref(), Some("Hanna"));
+        assert_eq!(info.penciller.as_deref(), Some("Barbera"));
+        assert_eq!(info.publisher.as_deref(), Some("MGM"));
+        assert_eq!(info.genre.as_deref(), Some("Comedy"));
+        assert_eq!(info.manga, Some(true));
+        assert_eq!(
+            info.page_types,
+            vec![(1, "FrontCover".to_string()), (6, "Story".to_string())]
+        );
+    }
+
+    #[test]
+    fn leaves_missing_and_invalid_fields_empty() {
+        let xml = b"<ComicInfo>
+  <Series>  Partial  </Series>
+  <Volume>three</Volume>
+  <Year>0</Year>
+  <Writer></Writer>
+  <Manga>Unknown</Manga>
+  <Pages><Page Image=\"x\" Type=\"Story\" /><Page Image=\"1\" /></Pages>
+</ComicInfo>";
+
+        let info = parse_comic_info(xml).unwrap();
+
+        assert_eq!(info.series.as_deref(), Some("Partial"));
+        assert_eq!(info.number, None);
+        assert_eq!(info.volume, None);
+        assert_eq!(info.year, None);
+        assert_eq!(info.writer, None);
+        assert_eq!(info.manga, None);
+        assert!(info.page_types.is_empty());
+    }
+
+    #[test]
+    fn documents_without_metadata_give_empty_info() {
+        for xml in [&b""[..], b"<ComicInfo/>", b"<Other><Title>Not used</Title></Other>"] {
+            let info = parse_comic_info(xml).unwrap();
+            assert_eq!(info.series, None);
+            assert_eq!(info.writer, None);
+            assert!(info.page_types.is_empty());
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_documents() {
+        assert!(parse_comic_info(b"<ComicInfo><Series>Broken</Writer></ComicInfo>").is_err());
+        assert!(parse_comic_info(b"<ComicInfo><Series>Unescaped &bogus;</Series></ComicInfo>").is_err());
+    }
+
+    #[test]
+    fn rewrite_keeps_unmanaged_content_untouched() {
+        let xml = br#"<?xml version="1.0" encoding="ISO-8859-1" standalone="yes"?>
+<!-- Tagged by hand -->
+<ComicInfo xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:noNamespaceSchemaLocation="ComicInfo.xsd">
+  <Title lang='en'>Pilot &amp; more</Title>
+  <Series>Old series</Series>
+  <Writer>Someone</Writer>
+  <Notes><![CDATA[<kept> as is]]></Notes>
+  <Custom a="1"><Nested b="2"/></Custom>
+  <Pages>
+    <Page Image="0" Type="FrontCover" ImageWidth="800"/>
+  </Pages>
+</ComicInfo>
+"#;
+        let update = ComicInfoUpdate {
+            series: Some("New & improved".to_string()),
+            ..ComicInfoUpdate::default()
+        };
+
+        let rewritten = update_comic_info(Some(xml), &update).unwrap();
+
+        let expected = br#"<?xml version="1.0" encoding="ISO-8859-1" standalone="yes"?>
+<!-- Tagged by hand -->
+<ComicInfo xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:noNamespaceSchemaLocation="ComicInfo.xsd">
+  <Title lang='en'>Pilot &amp; more</Title>
+  <Series>New &amp; improved</Series>
+  <Notes><![CDATA[<kept> as is]]></Notes>
+  <Custom a="1"><Nested b="2"/></Custom>
+  <Pages>
+    <Page Image="0" Type="FrontCover" ImageWidth="800"/>
+  </Pages>
+</ComicInfo>
+"#;
+        assert_eq!(String::from_utf8_lossy(&rewritten), String::from_utf8_lossy(expected));
+    }
+
+    #[test]
+    fn rewrite_adds_missing_elements_before_pages() {
+        let xml = b"<ComicInfo>\n  <Title>Pilot</Title>\n  <Manga>YesAndRightToLeft</Manga>\n  <Pages/>\n</ComicInfo>";
+        let update = ComicInfoUpdate {
+            series: Some("Series".to_string()),
+            year: Some(2001),
+            manga: Some(true),
+            ..ComicInfoUpdate::default()
+        };
+
+        let rewritten = update_comic_info(Some(xml), &update).unwrap();
+
+        assert_eq!(
+            String::from_utf8_lossy(&rewritten),
+            "<ComicInfo>\n  <Title>Pilot</Title>\n  <Manga>YesAndRightToLeft</Manga>\n  \
+             <Series>Series</Series>\n  <Year>2001</Year>\n  <Pages/>\n</ComicInfo>"
+        );
+    }
+
+    #[test]
+    fn update_creates_a_document_when_there_is_none() {
+        let update = ComicInfoUpdate {
+            series: Some("Series".to_string()),
+            manga: Some(false),
+            ..ComicInfoUpdate::default()
+        };
+
+        for existing in [None, Some(&b""[..])] {
+            let created = update_comic_info(existing, &update).unwrap();
+            let info = parse_comic_info(&created).unwrap();
+            assert_eq!(info.series.as_deref(), Some("Series"));
+            assert_eq!(info.manga, Some(false));
+        }
+    }
+}
@@ -0,0 +1,518 @@
+//! Backend access to `comic-shelf.db`, the database the frontend opens through
+//! the SQL plugin. Indexing results are written here directly, one
+//! transaction per comic, so indexing does not depend on the webview.
+
+use std::borrow::Cow;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use sqlx::error::BoxDynError;
+use sqlx::migrate::{Migration as SqlxMigration, MigrationSource, MigrationType, Migrator};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_sql::{Migration, MigrationKind};
+
+use crate::{get_migrations, IndexedComicPayload, IndexedPagePayload};
+
+pub const DATABASE_URL: &str = "sqlite:comic-shelf.db";
+const DATABASE_FILE_NAME: &str = "comic-shelf.db";
+/// The frontend may be writing at the same time, so wait for its locks rather
+/// than failing the comic.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(30);
+/// Evicted thumbnails cleared per statement, below SQLite's default limit of
+/// 999 bound parameters.
+const CLEAR_BATCH_SIZE: usize = 500;
+
+/// Feeds the app's migration list to the sqlx migrator the same way the SQL
+/// plugin does, so both sides agree on applied versions and checksums.
+#[derive(Debug)]
+struct AppMigrations(Vec<Migration>);
+
+impl MigrationSource<'static> for AppMigrations {
+    fn resolve(
+        self,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<SqlxMigration>, BoxDynError>> + Send + 'static>> {
+        Box::pin(async move {
+            Ok(self
+                .0
+                .into_iter()
+                .filter(|migration| matches!(migration.kind, MigrationKind::Up))
+                .map(|migration| {
+                    SqlxMigration::new(
+                        migration.version,
+                        Cow::Borrowed(migration.description),
+                        MigrationType::ReversibleUp,
+                        Cow::Borrowed(migration.sql),
+                        false,
+                    )
+                })
+                .collect())
+        })
+    }
+}
+
+/// Lazily opened connection pool.
+#[derive(Default)]
+pub struct LibraryDatabase {
+    pool: Mutex<Option<SqlitePool>>,
+}
+
+impl LibraryDatabase {
+    fn pool(&self, app: &AppHandle) -> Result<SqlitePool, String> {
+        let mut pool = self.pool.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(pool) = pool.as_ref() {
+            return Ok(pool.clone());
+        }
+        let opened = tauri::async_runtime::block_on(open_pool(app))?;
+        *pool = Some(opened.clone());
+        Ok(opened)
+    }
+
+    /// Inserts or updates a comic and all of its pages in one transaction and
+    /// returns the comic ID. Pages beyond the new page count are removed.
+    pub fn upsert_comic(&self, app: &AppHandle, comic: &IndexedComicPayload) -> Result<i64, String> {
+        let pool = self.pool(app)?;
+        tauri::async_runtime::block_on(upsert_comic(&pool, comic))
+            .map_err(|e| format!("Failed to save comic {} to the database: {e}", comic.path))
+    }
+
+    pub fn delete_comics(&self, app: &AppHandle, paths: &[String]) -> Result<(), String> {
+        if paths.is_empty() {
+            return Ok(());
+        }
+        let pool = self.pool(app)?;
+        tauri::async_runtime::block_on(async {
+            let mut tx = pool.begin().await?;
+            for path in paths {
+                sqlx::query("DELETE FROM comics WHERE path = $1")
+                    .bind(path)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+            tx.commit().await
+        })
+        .map_err(|e| format!("Failed to delete removed comics from the database: {e}"))
+    }
+
+    pub fn comic_paths(&self, app: &AppHandle) -> Result<Vec<String>, String> {
+        let pool = self.pool(app)?;
+        tauri::async_runtime::block_on(sqlx::query_scalar("SELECT path FROM comics").fetch_all(&pool))
+            .map_err(|e| format!("Failed to read comic paths: {e}"))
+    }
+
+    pub fn thumbnail_paths(&self, app: &AppHandle) -> Result<Vec<String>, String> {
+        let pool = self.pool(app)?;
+        tauri::async_runtime::block_on(
+            sqlx::query_scalar("SELECT thumbnail_path FROM comic_pages WHERE thumbnail_path IS NOT NULL")
+                .fetch_all(&pool),
+        )
+        .map_err(|e| format!("Failed to read thumbnail paths: {e}"))
+    }
+
+    /// Thumbnails of the first page of every comic.
+    pub fn cover_thumbnail_paths(&self, app: &AppHandle) -> Result<Vec<String>, String> {
+        let pool = self.pool(app)?;
+        tauri::async_runtime::block_on(
+            sqlx::query_scalar(
+                "SELECT thumbnail_path FROM comic_pages WHERE page_number = 1 AND thumbnail_path IS NOT NULL",
+            )
+            .fetch_all(&pool),
+        )
+        .map_err(|e| format!("Failed to read cover thumbnail paths: {e}"))
+    }
+
+    /// Marks pages whose thumbnail was evicted as having none and returns the
+    /// paths of their comics. `paths` use forward slashes, like the stored
+    /// thumbnail paths.
+    pub fn clear_thumbnail_paths(&self, app: &AppHandle, paths: &[String]) -> Result<Vec<String>, String> {
+        if paths.is_empty() {
+            return Ok(Vec::new());
+        }
+        let pool = self.pool(app)?;
+        tauri::async_runtime::block_on(clear_thumbnail_paths(&pool, paths))
+            .map_err(|e| format!("Failed to clear evicted thumbnail paths: {e}"))
+    }
+
+    /// Path and source type of a comic.
+    pub fn comic_source(&self, app: &AppHandle, comic_id: i64) -> Result<(String, String), String> {
+        let pool = self.pool(app)?;
+        tauri::async_runtime::block_on(
+            sqlx::query_as("SELECT path, source_type FROM comics WHERE id = $1")
+                .bind(comic_id)
+                .fetch_optional(&pool),
+        )
+        .map_err(|e| format!("Failed to read comic {comic_id}: {e}"))?
+        .ok_or_else(|| format!("Comic {comic_id} does not exist"))
+    }
+
+    /// Path and source type of every comic with pages that have no thumbnail.
+    pub fn comics_missing_page_thumbnails(&self, app: &AppHandle) -> Result<Vec<(String, String)>, String> {
+        let pool = self.pool(app)?;
+        tauri::async_runtime::block_on(
+            sqlx::query_as(
+                "SELECT path, source_type FROM comics
+                 WHERE EXISTS (SELECT 1 FROM comic_pages WHERE comic_pages.comic_id = comics.id AND comic_pages.thumbnail_path IS NULL)
+                 ORDER BY path",
+            )
+            .fetch_all(&pool),
+        )
+        .map_err(|e| format!("Failed to read comics without page thumbnails: {e}"))
+    }
+
+    /// Stores the thumbnails of pages that have one, leaving the others as
+    /// they are.
+    pub fn update_page_thumbnails(
+        &self,
+        app: &AppHandle,
+        comic_path: &str,
+        pages: &[IndexedPagePayload],
+    ) -> Result<(), String> {
+        let pool = self.pool(app)?;
+        tauri::async_runtime::block_on(async {
+            let mut tx = pool.begin().await?;
+            for page in pages {
+                let Some(thumbnail_path) = page.thumbnail_path.as_ref() else {
+                    continue;
+                };
+                sqlx::query(
+                    "UPDATE comic_pages SET thumbnail_path = $1, thumbnail_exists = 1
+                     WHERE comic_id = (SELECT id FROM comics WHERE path = $2) AND page_number = $3",
+                )
+                .bind(thumbnail_path)
+                .bind(comic_path)
+                .bind(page.page_number)
+                .execute(&mut *tx)
+                .await?;
+            }
+            tx.commit().await
+        })
+        .map_err(|e| format!("Failed to save page thumbnails of {comic_path}: {e}"))
+    }
+
+    pub fn index_paths(&self, app: &AppHandle) -> Result<Vec<String>, String> {
+        let pool = self.pool(app)?;
+        tauri::async_runtime::block_on(sqlx::query_scalar("SELECT path FROM index_paths").fetch_all(&pool))
+            .map_err(|e| format!("Failed to read index paths: {e}"))
+    }
+
+    /// Points stored page thumbnails at their re-keyed folders. Each pair is
+    /// the old and the new folder path.
+    pub fn rekey_thumbnail_dirs(&self, app: &AppHandle, renamed_dirs: &[(String, String)]) -> Result<(), String> {
+        if renamed_dirs.is_empty() {
+            return Ok(());
+        }
+        let pool = self.pool(app)?;
+        tauri::async_runtime::block_on(async {
+            let mut tx = pool.begin().await?;
+            for (old_dir, new_dir) in renamed_dirs {
+                let old_prefix = format!("{}/", old_dir);
+                sqlx::query(
+                    "UPDATE comic_pages SET thumbnail_path = $1 || substr(thumbnail_path, length($2) + 1)
+                     WHERE substr(thumbnail_path, 1, length($2)) = $2",
+                )
+                .bind(format!("{}/", new_dir))
+                .bind(&old_prefix)
+                .execute(&mut *tx)
+                .await?;
+            }
+            tx.commit().await
+        })
+        .map_err(|e| format!("Failed to update thumbnail paths: {e}"))
+    }
+}
+
+async fn open_pool(app: &AppHandle) -> Result<SqlitePool, String> {
+    let config_dir = app
+        .path()
+        .app_config_dir()
+        .map_err(|e| format!("Failed to resolve app config dir: {e}"))?;
+    std::fs::create_dir_all(&config_dir)
+        .map_err(|e| format!("Failed to create app config dir {}: {e}", config_dir.display()))?;
+
+    let options = SqliteConnectOptions::new()
+        .filename(config_dir.join(DATABASE_FILE_NAME))
+        .create_if_missing(true)
+        .foreign_keys(true)
+        .busy_timeout(BUSY_TIMEOUT);
+    let pool = SqlitePoolOptions::new()
+        .max_connections(2)
+        .connect_with(options)
+        .await
+        .map_err(|e| format!("Failed to open database: {e}"))?;
+
+    Migrator::new(AppMigrations(get_migrations()))
+        .await
+        .map_err(|e| format!("Failed to load database migrations: {e}"))?
+        .run(&pool)
+        .await
+        .map_err(|e| format!("Failed to migrate database: {e}"))?;
+    Ok(pool)
+}
+
+async fn upsert_comic(pool: &SqlitePool, comic: &IndexedComicPayload) -> Result<i64, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let comic_id: i64 = sqlx::query_scalar(
+        "INSERT INTO comics (path, source_type, title, artist, series, issue, volume, writer, penciller, publisher, year, summary, genre, manga, cover_image_path, page_count, indexing_status, indexing_error, updated_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, 'completed', NULL, datetime('now'))
+         ON CONFLICT(path) DO UPDATE SET
+           source_type = excluded.source_type,
+           title = excluded.title,
+           artist = excluded.artist,
+           series = excluded.series,
+           issue = excluded.issue,
+           volume = excluded.volume,
+           writer = excluded.writer,
+           penciller = excluded.penciller,
+           publisher = excluded.publisher,
+           year = excluded.year,
+           summary = excluded.summary,
+           genre = excluded.genre,
+           manga = excluded.manga,
+           cover_image_path = excluded.cover_image_path,
+           page_count = excluded.page_count,
+           indexing_status = excluded.indexing_status,
+           indexing_error = excluded.indexing_error,
+           updated_at = datetime('now')
+         RETURNING id",
+    )
+    .bind(&comic.path)
+    .bind(&comic.source_type)
+    .bind(&comic.title)
+    .bind(&comic.artist)
+    .bind(&comic.series)
+    .bind(&comic.issue)
+    .bind(comic.volume)
+    .bind(&comic.writer)
+    .bind(&comic.penciller)
+    .bind(&comic.publisher)
+    .bind(comic.year)
+    .bind(&comic.summary)
+    .bind(&comic.genre)
+    .bind(comic.manga)
+    .bind(&comic.cover_image_path)
+    .bind(comic.page_count)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    for page in &comic.pages {
+        sqlx::query(
+            "INSERT INTO comic_pages (comic_id, page_number, file_path, file_name, source_type, source_path, archive_entry_path, pdf_page_number, thumbnail_path, thumbnail_exists, page_type)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+             ON CONFLICT(comic_id, page_number) DO UPDATE SET
+               file_path = excluded.file_path,
+               file_name = excluded.file_name,
+               source_type = excluded.source_type,
+               source_path = excluded.source_path,
+               archive_entry_path = excluded.archive_entry_path,
+               pdf_page_number = excluded.pdf_page_number,
+               thumbnail_path = excluded.thumbnail_path,
+               thumbnail_exists = excluded.thumbnail_exists,
+               page_type = excluded.page_type",
+        )
+        .bind(comic_id)
+        .bind(page.page_number)
+        .bind(&page.file_path)
+        .bind(&page.file_name)
+        .bind(&page.source_type)
+        .bind(&page.source_path)
+        .bind(&page.archive_entry_path)
+        .bind(page.pdf_page_number)
+        .bind(&page.thumbnail_path)
+        .bind(page.thumbnail_exists)
+        .bind(&page.page_type)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    sqlx::query("DELETE FROM comic_pages WHERE comic_id = $1 AND page_number > $2")
+        .bind(comic_id)
+        .bind(comic.pages.len() as i64)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(comic_id)
+}
+
+/// Clears `paths` in batches that stay below SQLite's parameter limit, using
+/// the index on `comic_pages.thumbnail_path`.
+async fn clear_thumbnail_paths(pool: &SqlitePool, paths: &[String]) -> Result<Vec<String>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let mut comic_paths = Vec::new();
+    for batch in paths.chunks(CLEAR_BATCH_SIZE) {
+        let placeholders = (1..=batch.len())
+            .map(|index| format!("${index}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let select = format!(
+            "SELECT DISTINCT comics.path FROM comics
+             JOIN comic_pages ON comic_pages.comic_id = comics.id
+             WHERE comic_pages.thumbnail_path IN ({placeholders})"
+        );
+        let mut affected = sqlx::query_scalar::<_, String>(&select);
+        for path in batch {
+            affected = affected.bind(path);
+        }
+        comic_paths.extend(affected.fetch_all(&mut *tx).await?);
+
+        let update = format!(
+            "UPDATE comic_pages SET thumbnail_path = NULL, thumbnail_exists = 0
+             WHERE thumbnail_path IN ({placeholders})"
+        );
+        let mut cleared = sqlx::query(&update);
+        for path in batch {
+            cleared = cleared.bind(path);
+        }
+        cleared.execute(&mut *tx).await?;
+    }
+    tx.commit().await?;
+    comic_paths.sort();
+    comic_paths.dedup();
+    Ok(comic_paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn memory_pool() -> SqlitePool {
+        let options = SqliteConnectOptions::new()
+            .in_memory(true)
+            .foreign_keys(true);
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(options)
+            .await
+            .expect("open in-memory database");
+        Migrator::new(AppMigrations(get_migrations()))
+            .await
+            .expect("load migrations")
+            .run(&pool)
+            .await
+            .expect("migrate database");
+        pool
+    }
+
+    fn page(page_number: i64, file_name: &str, thumbnail_path: Option<&str>) -> IndexedPagePayload {
+        IndexedPagePayload {
+            page_number,
+            file_path: format!("/comics/a.cbz/{file_name}"),
+            file_name: file_name.to_string(),
+            source_type: "archive".to_string(),
+            source_path: "/comics/a.cbz".to_string(),
+            archive_entry_path: Some(file_name.to_string()),
+            pdf_page_number: None,
+            thumbnail_path: thumbnail_path.map(str::to_string),
+            thumbnail_exists: thumbnail_path.is_some(),
+            page_type: None,
+        }
+    }
+
+    fn comic(pages: Vec<IndexedPagePayload>) -> IndexedComicPayload {
+        IndexedComicPayload {
+            path: "/comics/a.cbz".to_string(),
+            title: "a".to_string(),
+            source_type: "archive".to_string(),
+            artist: None,
+            series: None,
+            issue: None,
+            volume: None,
+            writer: None,
+            penciller: None,
+            publisher: None,
+            year: None,
+            summary: None,
+            genre: None,
+            manga: None,
+            cover_image_path: None,
+            page_count: pages.len() as i64,
+            pages,
+        }
+    }
+
+    #[test]
+    fn upsert_replaces_the_thumbnail_of_a_changed_page() {
+        tauri::async_runtime::block_on(async {
+            let pool = memory_pool().await;
+            upsert_comic(
+                &pool,
+                &comic(vec![
+                    page(1, "001.jpg", Some("pages/aa/old-1.webp")),
+                    page(2, "002.jpg", Some("pages/bb/old-2.webp")),
+                ]),
+            )
+            .await
+            .expect("insert comic");
+
+            // Page 2 was replaced and its thumbnail is deferred to the fill job.
+            let comic_id = upsert_comic(
+                &pool,
+                &comic(vec![
+                    page(1, "001.jpg", Some("pages/aa/old-1.webp")),
+                    page(2, "002-new.jpg", None),
+                ]),
+            )
+            .await
+            .expect("update comic");
+
+            let thumbnails: Vec<(i64, Option<String>, bool)> = sqlx::query_as(
+                "SELECT page_number, thumbnail_path, thumbnail_exists FROM comic_pages
+                 WHERE comic_id = $1 ORDER BY page_number",
+            )
+            .bind(comic_id)
+            .fetch_all(&pool)
+            .await
+            .expect("read pages");
+            assert_eq!(
+                thumbnails,
+                vec![
+                    (1, Some("pages/aa/old-1.webp".to_string()), true),
+                    (2, None, false),
+                ]
+            );
+        });
+    }
+
+    #[test]
+    fn clearing_thumbnail_paths_reports_affected_comics() {
+        tauri::async_runtime::block_on(async {
+            let pool = memory_pool().await;
+            upsert_comic(
+                &pool,
+                &comic(vec![
+                    page(1, "001.jpg", Some("pages/aa/one.jpg")),
+                    page(2, "002.jpg", Some("pages/bb/two.jpg")),
+                    page(3, "003.jpg", Some("pages/aa/one.jpg")),
+                ]),
+            )
+            .await
+            .expect("insert comic");
+
+            // Spans two batches.
+            let mut evicted: Vec<String> = (0..CLEAR_BATCH_SIZE)
+                .map(|index| format!("pages/cc/{index}.jpg"))
+                .collect();
+            evicted.push("pages/aa/one.jpg".to_string());
+            let comics = clear_thumbnail_paths(&pool, &evicted).await.expect("clear paths");
+
+            assert_eq!(comics, vec!["/comics/a.cbz".to_string()]);
+            let thumbnails: Vec<(i64, Option<String>, bool)> = sqlx::query_as(
+                "SELECT page_number, thumbnail_path, thumbnail_exists FROM comic_pages ORDER BY page_number",
+            )
+            .fetch_all(&pool)
+            .await
+            .expect("read pages");
+            assert_eq!(
+                thumbnails,
+                vec![
+                    (1, None, false),
+                    (2, Some("pages/bb/two.jpg".to_string()), true),
+                    (3, None, false),
+                ]
+            );
+        });
+    }
+}
@@ -0,0 +1,288 @@
+//! Per index path record of the sources seen by the last indexing run, used to
+//! skip comics whose files did not change since then.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::cache_keys::{ensure_cache_keys_migrated, hash_path};
+use crate::comic_info::MetadataPrecedence;
+use crate::jobs::{IndexingJob, JOB_CANCELLED_MESSAGE};
+use crate::normalize_path_string;
+use crate::sources::SourceMetadata;
+use crate::thumbnail_settings::ThumbnailSettings;
+
+/// Paths that indexing work is running on, with the time it started. Work on
+/// a path waits for work on the same path, its ancestors and descendants, so
+/// runs of one index path cannot overwrite each other's state while runs of
+/// other index paths go on.
+static LOCKED_PATHS: Mutex<Vec<(String, SystemTime)>> = Mutex::new(Vec::new());
+static PATH_UNLOCKED: Condvar = Condvar::new();
+/// How often work waiting for a path checks whether its job was cancelled.
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(250);
+/// Comics to index again whose index path was locked when they were marked.
+/// They are applied to the state whenever it is loaded or saved next.
+static DEFERRED_RETRIES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+pub struct IndexStateGuard {
+    path: String,
+}
+
+impl Drop for IndexStateGuard {
+    fn drop(&mut self) {
+        let mut locked = lock_paths();
+        if let Some(position) = locked.iter().position(|(path, _)| *path == self.path) {
+            locked.swap_remove(position);
+        }
+        PATH_UNLOCKED.notify_all();
+    }
+}
+
+/// Waits until no other work runs on `path`, then holds it until the guard is
+/// dropped. Fails once `job` is cancelled while waiting, so a cancelled job
+/// queued behind a long run does not wait for it.
+pub fn lock_index_state(path: &str, job: Option<&IndexingJob>) -> Result<IndexStateGuard, String> {
+    let path = normalize_path_string(path).trim_end_matches('/').to_string();
+    let mut locked = lock_paths();
+    while locked.iter().any(|(locked_path, _)| paths_overlap(locked_path, &path)) {
+        if job.is_some_and(|job| job.is_cancelled()) {
+            return Err(JOB_CANCELLED_MESSAGE.to_string());
+        }
+        locked = PATH_UNLOCKED
+            .wait_timeout(locked, CANCEL_POLL_INTERVAL)
+            .map(|(guard, _)| guard)
+            .unwrap_or_else(|poisoned| poisoned.into_inner().0);
+    }
+    locked.push((path.clone(), SystemTime::now()));
+    Ok(IndexStateGuard { path })
+}
+
+/// Holds `path` like [`lock_index_state`] if no other work runs on it, without
+/// waiting.
+pub fn try_lock_index_state(path: &str) -> Option<IndexStateGuard> {
+    let path = normalize_path_string(path).trim_end_matches('/').to_string();
+    let mut locked = lock_paths();
+    if locked.iter().any(|(locked_path, _)| paths_overlap(locked_path, &path)) {
+        return None;
+    }
+    locked.push((path.clone(), SystemTime::now()));
+    Some(IndexStateGuard { path })
+}
+
+/// Has the comics indexed again by the next run once the work holding their
+/// index path saved its state.
+pub fn defer_retries(comic_paths: impl IntoIterator<Item = String>) {
+    lock_deferred_retries().extend(comic_paths);
+}
+
+fn lock_deferred_retries() -> MutexGuard<'static, Vec<String>> {
+    DEFERRED_RETRIES
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Start of the oldest indexing work that is still running, paused or not.
+/// Thumbnails it wrote or reused since then may not be in the database yet.
+pub fn oldest_running_since() -> Option<SystemTime> {
+    lock_paths().iter().map(|(_, started)| *started).min()
+}
+
+fn lock_paths() -> MutexGuard<'static, Vec<(String, SystemTime)>> {
+    LOCKED_PATHS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn paths_overlap(a: &str, b: &str) -> bool {
+    let is_within = |inner: &str, outer: &str| {
+        inner
+            .strip_prefix(outer)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    };
+    is_within(a, b) || is_within(b, a)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceFingerprint {
+    pub source_type: String,
+    pub file_size: u64,
+    pub modified_ms: Option<i64>,
+    pub entry_count: usize,
+}
+
+impl SourceFingerprint {
+    pub fn new(source_type: &str, metadata: &SourceMetadata, entry_count: usize) -> Self {
+        Self {
+            source_type: source_type.to_string(),
+            file_size: metadata.file_size,
+            modified_ms: metadata.modified_ms,
+            entry_count,
+        }
+    }
+
+    /// Whether the source still looks like it did when it was fingerprinted.
+    /// The entry count is only compared when the source can report it cheaply.
+    pub fn matches(&self, source_type: &str, metadata: &SourceMetadata) -> bool {
+        self.source_type == source_type
+            && self.file_size == metadata.file_size
+            && self.modified_ms == metadata.modified_ms
+            && metadata
+                .entry_count
+                .is_none_or(|entry_count| entry_count == self.entry_count)
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexState {
+    /// Settings the stored comics were indexed with. Metadata and thumbnails
+    /// depend on them, so any change invalidates every fingerprint.
+    pub pattern: String,
+    pub metadata_precedence: MetadataPrecedence,
+    /// States written before thumbnails were configurable used the defaults.
+    #[serde(default)]
+    pub thumbnail_settings: ThumbnailSettings,
+    pub comics: HashMap<String, SourceFingerprint>,
+    /// Comics that may be in the database but have no fingerprint, because
+    /// their last run could not complete. They are indexed again by the next
+    /// run and only removed once their sources are gone.
+    #[serde(default)]
+    pub retry_comics: HashSet<String>,
+}
+
+impl IndexState {
+    pub fn new(
+        pattern: &str,
+        metadata_precedence: MetadataPrecedence,
+        thumbnail_settings: ThumbnailSettings,
+    ) -> Self {
+        Self {
+            pattern: pattern.to_string(),
+            metadata_precedence,
+            thumbnail_settings,
+            comics: HashMap::new(),
+            retry_comics: HashSet::new(),
+        }
+    }
+
+    /// Paths of every comic the state accounts for, with or without a
+    /// fingerprint.
+    pub fn comic_paths(&self) -> impl Iterator<Item = &String> {
+        self.comics.keys().chain(&self.retry_comics)
+    }
+
+    pub fn contains(&self, comic_path: &str) -> bool {
+        self.comics.contains_key(comic_path) || self.retry_comics.contains(comic_path)
+    }
+
+    pub fn set_fingerprint(&mut self, comic_path: String, fingerprint: SourceFingerprint) {
+        self.retry_comics.remove(&comic_path);
+        self.comics.insert(comic_path, fingerprint);
+    }
+
+    /// Drops the fingerprint of a comic so the next run indexes it again,
+    /// without reporting it as removed.
+    pub fn retry_next_run(&mut self, comic_path: String) {
+        self.comics.remove(&comic_path);
+        self.retry_comics.insert(comic_path);
+    }
+
+    pub fn remove(&mut self, comic_path: &str) {
+        self.comics.remove(comic_path);
+        self.retry_comics.remove(comic_path);
+    }
+
+    /// Marks the deferred retries of comics in this state, and takes them
+    /// from the deferred list when `take` is set.
+    fn apply_deferred_retries(&mut self, take: bool) -> Vec<String> {
+        let mut deferred = lock_deferred_retries();
+        let applied: Vec<String> = deferred
+            .iter()
+            .filter(|comic_path| self.contains(comic_path))
+            .cloned()
+            .collect();
+        if take {
+            deferred.retain(|comic_path| !self.contains(comic_path));
+        }
+        drop(deferred);
+        for comic_path in &applied {
+            self.retry_next_run(comic_path.clone());
+        }
+        applied
+    }
+
+    /// Loads the state of the last run. A missing or unreadable state file
+    /// simply means every comic is indexed again.
+    pub fn load(app: &AppHandle, base_path: &str) -> Self {
+        let mut state = Self::load_file(app, base_path);
+        state.apply_deferred_retries(false);
+        state
+    }
+
+    fn load_file(app: &AppHandle, base_path: &str) -> Self {
+        let Ok(path) = state_file_path(app, base_path) else {
+            return Self::default();
+        };
+        match fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes).unwrap_or_else(|e| {
+                eprintln!(
+                    "[Indexing][Rust] Ignoring unreadable index state {}: {e}",
+                    path.display()
+                );
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn is_compatible(
+        &self,
+        pattern: &str,
+        metadata_precedence: MetadataPrecedence,
+        thumbnail_settings: &ThumbnailSettings,
+    ) -> bool {
+        self.pattern == pattern
+            && self.metadata_precedence == metadata_precedence
+            && self.thumbnail_settings.produces_same_thumbnails(thumbnail_settings)
+    }
+
+    pub fn save(&mut self, app: &AppHandle, base_path: &str) -> Result<(), String> {
+        let applied = self.apply_deferred_retries(true);
+        let result = self.write(app, base_path);
+        if result.is_err() {
+            defer_retries(applied);
+        }
+        result
+    }
+
+    fn write(&self, app: &AppHandle, base_path: &str) -> Result<(), String> {
+        let path = state_file_path(app, base_path)?;
+        let bytes = serde_json::to_vec(self)
+            .map_err(|e| format!("Failed to serialize index state: {e}"))?;
+        let temp_path = path.with_extension("json.tmp");
+        fs::write(&temp_path, bytes)
+            .map_err(|e| format!("Failed to write index state {}: {e}", temp_path.display()))?;
+        fs::rename(&temp_path, &path)
+            .map_err(|e| format!("Failed to replace index state {}: {e}", path.display()))
+    }
+}
+
+fn state_file_path(app: &AppHandle, base_path: &str) -> Result<PathBuf, String> {
+    ensure_cache_keys_migrated(app);
+    let state_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to resolve app data dir: {e}"))?
+        .join("index-state");
+    if !state_dir.exists() {
+        fs::create_dir_all(&state_dir)
+            .map_err(|e| format!("Failed to create index state dir {}: {e}", state_dir.display()))?;
+    }
+    Ok(state_dir.join(format!("{}.json", hash_path(base_path))))
+}
@@ -0,0 +1,121 @@
+//! Control handles for long running indexing jobs. Workers call
+//! [`IndexingJob::checkpoint`] between units of work, which blocks while the
+//! job is paused and fails once it is cancelled.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+
+use serde::Serialize;
+
+pub const INDEXING_JOB_FINISHED_EVENT: &str = "indexing-job-finished";
+pub const JOB_CANCELLED_MESSAGE: &str = "Indexing job was cancelled";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum IndexingJobStatus {
+    Completed,
+    Cancelled,
+    Failed,
+}
+
+pub struct IndexingJob {
+    id: u64,
+    base_path: String,
+    cancelled: AtomicBool,
+    paused: AtomicBool,
+    pause_lock: Mutex<()>,
+    resumed: Condvar,
+}
+
+impl IndexingJob {
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn base_path(&self) -> &str {
+        &self.base_path
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Acquire)
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Release);
+        self.wake();
+    }
+
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::Release);
+    }
+
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::Release);
+        self.wake();
+    }
+
+    /// Waits while the job is paused. Returns an error once the job has been
+    /// cancelled so that callers can bail out with `?`.
+    pub fn checkpoint(&self) -> Result<(), String> {
+        if self.paused.load(Ordering::Acquire) {
+            let mut guard = self.pause_lock.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            while self.paused.load(Ordering::Acquire) && !self.is_cancelled() {
+                guard = self
+                    .resumed
+                    .wait(guard)
+                    .unwrap_or_else(|poisoned| poisoned.into_inner());
+            }
+        }
+
+        if self.is_cancelled() {
+            Err(JOB_CANCELLED_MESSAGE.to_string())
+        } else {
+            Ok(())
+        }
+    }
+
+    fn wake(&self) {
+        // Taking the lock orders the flag change before any waiter re-checks it.
+        let _guard = self.pause_lock.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        self.resumed.notify_all();
+    }
+}
+
+/// Registry of running jobs.
+#[derive(Default)]
+pub struct IndexingJobs {
+    next_id: AtomicU64,
+    jobs: Mutex<HashMap<u64, Arc<IndexingJob>>>,
+}
+
+impl IndexingJobs {
+    pub fn start(&self, base_path: &str) -> Arc<IndexingJob> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let job = Arc::new(IndexingJob {
+            id,
+            base_path: base_path.to_string(),
+            cancelled: AtomicBool::new(false),
+            paused: AtomicBool::new(false),
+            pause_lock: Mutex::new(()),
+            resumed: Condvar::new(),
+        });
+        self.lock().insert(id, job.clone());
+        job
+    }
+
+    pub fn get(&self, id: u64) -> Result<Arc<IndexingJob>, String> {
+        self.lock()
+            .get(&id)
+            .cloned()
+            .ok_or_else(|| format!("Indexing job {} is not running", id))
+    }
+
+    pub fn finish(&self, id: u64) {
+        self.lock().remove(&id);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<u64, Arc<IndexingJob>>> {
+        self.jobs.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
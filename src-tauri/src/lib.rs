@@ -1,36 +1,62 @@
 use std::cmp::Ordering;
 use std::cmp;
 use regex::Regex;
-use std::collections::hash_map::DefaultHasher;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
-use std::hash::{Hash, Hasher};
-use std::io::Cursor;
-use std::io::Read;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering as AtomicOrdering};
+use std::sync::{mpsc, Mutex};
+use std::thread;
 
-use image::codecs::jpeg::JpegEncoder;
-use image::imageops::FilterType;
-use image::GenericImageView;
-use lopdf::Document;
 use num_cpus;
 use rayon::prelude::*;
 use serde::Serialize;
 use tauri::AppHandle;
+use tauri::ipc::Channel;
 use tauri::Emitter;
 use tauri::Manager;
+use tauri::State;
 use tauri_plugin_sql::{Builder as SqlBuilder, Migration, MigrationKind};
-use unrar::Archive;
-use zip::ZipArchive;
+
+mod cache_keys;
+mod comic_info;
+mod db;
+mod index_state;
+mod jobs;
+mod lru;
+mod page_cache;
+mod page_images;
+mod progress;
+mod protocol;
+mod sources;
+mod storage;
+mod thumbnail_cache;
+mod thumbnail_settings;
+mod watcher;
+
+use cache_keys::{content_key, ensure_cache_keys_migrated, hash_path};
+use comic_info::{is_comic_info_name, parse_comic_info, ComicInfo, ComicInfoUpdate, MetadataPrecedence};
+use db::{LibraryDatabase, DATABASE_URL};
+use index_state::{lock_index_state, IndexState, IndexStateGuard, SourceFingerprint};
+use jobs::{IndexingJob, IndexingJobStatus, IndexingJobs, INDEXING_JOB_FINISHED_EVENT};
+use page_cache::{PageCache, PageVariant, ReaderViewMode};
+use page_images::BorderCrop;
+use progress::{ComicProgress, IndexingPhase, IndexingProgress};
+use protocol::COMIC_PROTOCOL;
+use sources::{
+    is_archive_extension, open_archive_source, open_source, ComicSource, ImageFolderSource,
+    PageLocator, PdfSource, SourcePage,
+};
+use storage::{detect_storage_kind, IoConcurrency, IoPermits};
+use thumbnail_cache::{in_flight_since, is_in_flight, ThumbnailCache, ThumbnailCacheStats};
+use thumbnail_settings::{ThumbnailConfig, ThumbnailSettings, ThumbnailTier};
+use watcher::{LibraryWatchers, WatchStatusPayload, WatchedIndexPath};
 
 const IMAGE_EXTENSIONS: [&str; 6] = ["jpg", "jpeg", "png", "gif", "webp", "bmp"];
-const ARCHIVE_EXTENSIONS: [&str; 2] = ["cbz", "cbr"];
 const PDF_EXTENSION: &str = "pdf";
-const THUMBNAIL_MAX_SIZE: u32 = 300;
-const THUMBNAIL_JPEG_QUALITY: u8 = 80;
-const INDEXING_PROGRESS_EVENT: &str = "indexing-progress";
+const INDEXED_PAGES_DIR: &str = "pages";
 
-#[derive(Serialize)]
+#[derive(Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 struct ComicCandidate {
     path: String,
@@ -46,14 +72,14 @@ struct ImagePageEntry {
     page_number: i64,
 }
 
-#[derive(Serialize)]
+#[derive(Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 struct IndexingErrorPayload {
     path: String,
     message: String,
 }
 
-#[derive(Serialize)]
+#[derive(Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 struct IndexedPagePayload {
     page_number: i64,
@@ -65,9 +91,10 @@ struct IndexedPagePayload {
     pdf_page_number: Option<i64>,
     thumbnail_path: Option<String>,
     thumbnail_exists: bool,
+    page_type: Option<String>,
 }
 
-#[derive(Serialize)]
+#[derive(Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 struct IndexedComicPayload {
     path: String,
@@ -76,15 +103,44 @@ struct IndexedComicPayload {
     artist: Option<String>,
     series: Option<String>,
     issue: Option<String>,
+    volume: Option<i64>,
+    writer: Option<String>,
+    penciller: Option<String>,
+    publisher: Option<String>,
+    year: Option<i64>,
+    summary: Option<String>,
+    genre: Option<String>,
+    manga: Option<bool>,
     cover_image_path: Option<String>,
     page_count: i64,
     pages: Vec<IndexedPagePayload>,
 }
 
-#[derive(Serialize)]
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+enum ComicChange {
+    Added,
+    Changed,
+}
+
+/// A single indexed comic, streamed to the frontend as soon as it is done.
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct IndexedComicEvent {
+    change: ComicChange,
+    comic: IndexedComicPayload,
+}
+
+#[derive(Clone, Default, Serialize)]
 #[serde(rename_all = "camelCase")]
 struct BuildIndexPayloadResult {
-    comics: Vec<IndexedComicPayload>,
+    /// Comics that were not part of the previous run. Empty when comics are
+    /// streamed through a channel instead.
+    added: Vec<IndexedComicPayload>,
+    /// Previously indexed comics whose source changed. Empty when streamed.
+    changed: Vec<IndexedComicPayload>,
+    removed_comic_paths: Vec<String>,
+    unchanged_comic_paths: Vec<String>,
     active_comic_paths: Vec<String>,
     errors: Vec<IndexingErrorPayload>,
 }
@@ -98,13 +154,12 @@ struct ScanResult {
 
 #[derive(Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
-struct IndexingProgressEventPayload {
+struct IndexingJobFinishedPayload {
+    job_id: u64,
     base_path: String,
-    total_comics: i64,
-    current_comic: i64,
-    current_path: String,
-    percentage: f64,
-    current_task: String,
+    status: IndexingJobStatus,
+    error: Option<String>,
+    result: Option<BuildIndexPayloadResult>,
 }
 
 #[derive(Default)]
@@ -132,7 +187,7 @@ fn is_image_file(path: &Path) -> bool {
 
 fn is_archive_file(path: &Path) -> bool {
     extension_of(path)
-        .map(|ext| ARCHIVE_EXTENSIONS.contains(&ext.as_str()))
+        .map(|ext| is_archive_extension(&ext))
         .unwrap_or(false)
 }
 
@@ -229,46 +284,63 @@ fn walk_for_candidates(dir: &Path, out: &mut Vec<ComicCandidate>, errors: &mut V
         if path.is_file() {
             if is_image_file(&path) {
                 contains_images = true;
-            } else if is_pdf_file(&path) {
-                let title = path
-                    .file_stem()
-                    .and_then(|s| s.to_str())
-                    .unwrap_or("PDF")
-                    .to_string();
-                out.push(ComicCandidate {
-                    path: to_forward_slash_path(&path),
-                    title,
-                    source_type: "pdf".to_string(),
-                });
-            } else if is_archive_file(&path) {
-                let title = path
-                    .file_stem()
-                    .and_then(|s| s.to_str())
-                    .unwrap_or("Archive")
-                    .to_string();
-                out.push(ComicCandidate {
-                    path: to_forward_slash_path(&path),
-                    title,
-                    source_type: "archive".to_string(),
-                });
+            } else if let Some(candidate) = file_candidate(&path) {
+                out.push(candidate);
             }
         }
     }
 
     if contains_images {
-        let title = dir
-            .file_name()
-            .and_then(|s| s.to_str())
-            .unwrap_or("Comic")
-            .to_string();
-        out.push(ComicCandidate {
-            path: to_forward_slash_path(dir),
-            title,
-            source_type: "image".to_string(),
-        });
+        out.push(image_folder_candidate(dir));
     }
 }
 
+/// Candidate for a PDF or archive file.
+fn file_candidate(path: &Path) -> Option<ComicCandidate> {
+    let (source_type, fallback_title) = if is_pdf_file(path) {
+        ("pdf", "PDF")
+    } else if is_archive_file(path) {
+        ("archive", "Archive")
+    } else {
+        return None;
+    };
+    let title = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or(fallback_title)
+        .to_string();
+    Some(ComicCandidate {
+        path: to_forward_slash_path(path),
+        title,
+        source_type: source_type.to_string(),
+    })
+}
+
+fn image_folder_candidate(dir: &Path) -> ComicCandidate {
+    let title = dir
+        .file_name()
+        .and_then(|s| s.to_str())
+        .unwrap_or("Comic")
+        .to_string();
+    ComicCandidate {
+        path: to_forward_slash_path(dir),
+        title,
+        source_type: "image".to_string(),
+    }
+}
+
+/// Candidate for a single path, without descending into subdirectories.
+fn candidate_at(path: &Path) -> Option<ComicCandidate> {
+    if path.is_file() {
+        return file_candidate(path);
+    }
+    let contains_images = fs::read_dir(path).ok()?.filter_map(Result::ok).any(|entry| {
+        let entry_path = entry.path();
+        entry_path.is_file() && is_image_file(&entry_path)
+    });
+    contains_images.then(|| image_folder_candidate(path))
+}
+
 fn ensure_thumb_dir(app: &AppHandle) -> Result<PathBuf, String> {
     let app_data = app
         .path()
@@ -286,15 +358,8 @@ fn normalize_path_string(path: &str) -> String {
     path.replace('\\', "/")
 }
 
-fn hash_path(path: &str) -> String {
-    let mut hasher = DefaultHasher::new();
-    normalize_path_string(path)
-        .to_ascii_lowercase()
-        .hash(&mut hasher);
-    format!("{:016x}", hasher.finish())
-}
-
 fn ensure_indexed_thumb_root(app: &AppHandle) -> Result<PathBuf, String> {
+    ensure_cache_keys_migrated(app);
     let thumb_root = ensure_thumb_dir(app)?;
     let indexed_root = thumb_root.join("indexed");
     if !indexed_root.exists() {
@@ -308,87 +373,31 @@ fn ensure_indexed_thumb_root(app: &AppHandle) -> Result<PathBuf, String> {
     Ok(indexed_root)
 }
 
-fn get_indexed_thumbnail_path(
-    app: &AppHandle,
-    comic_path: &str,
-    page_number: i64,
-) -> Result<PathBuf, String> {
-    let indexed_root = ensure_indexed_thumb_root(app)?;
-    let comic_hash = hash_path(comic_path);
-    Ok(indexed_root.join(comic_hash).join(format!("{}.jpg", page_number)))
-}
-
-fn ensure_indexed_thumb_comic_dir(app: &AppHandle, comic_path: &str) -> Result<PathBuf, String> {
-    let indexed_root = ensure_indexed_thumb_root(app)?;
-    let comic_hash = hash_path(comic_path);
-    let comic_dir = indexed_root.join(comic_hash);
-    if !comic_dir.exists() {
-        fs::create_dir_all(&comic_dir).map_err(|e| {
-            format!(
-                "Failed to create indexed comic thumbnail dir {}: {e}",
-                comic_dir.display()
-            )
-        })?;
-    }
-    Ok(comic_dir)
-}
-
-fn emit_indexing_progress(
-    app: &AppHandle,
-    base_path: &str,
-    total_comics: usize,
-    current_comic: usize,
-    current_path: &str,
-    current_task: &str,
-) {
-    let percentage = if total_comics == 0 {
-        0.0
-    } else {
-        ((current_comic as f64) / (total_comics as f64) * 100.0).clamp(0.0, 100.0)
-    };
-
-    let payload = IndexingProgressEventPayload {
-        base_path: base_path.to_string(),
-        total_comics: total_comics as i64,
-        current_comic: current_comic as i64,
-        current_path: current_path.to_string(),
-        percentage,
-        current_task: current_task.to_string(),
-    };
-
-    if let Err(error) = app.emit(INDEXING_PROGRESS_EVENT, payload) {
-        eprintln!("[Indexing][Rust] Failed to emit progress event: {error}");
-    }
+/// Page thumbnails are stored by content, so identical pages share one file
+/// and a changed page gets a new one: `pages/{key[..2]}/{key}.{ext}`. The
+/// thumbnail settings are part of the key, so changing them only regenerates
+/// thumbnails that were made with other settings.
+fn get_indexed_thumbnail_path(indexed_root: &Path, fingerprint: &str, settings: &ThumbnailSettings) -> PathBuf {
+    let key = content_key(&format!("{}|{}", fingerprint, settings.cache_tag()));
+    indexed_root
+        .join(INDEXED_PAGES_DIR)
+        .join(&key[..2])
+        .join(format!("{}.{}", key, settings.format.extension()))
 }
 
 fn generate_indexed_thumbnail_from_bytes(
-    app: &AppHandle,
     image_bytes: &[u8],
-    comic_path: &str,
-    page_number: i64,
+    thumb_path: &Path,
+    settings: &ThumbnailSettings,
 ) -> Result<String, String> {
-    let comic_dir = ensure_indexed_thumb_comic_dir(app, comic_path)?;
-    let thumb_path = comic_dir.join(format!("{}.jpg", page_number));
-    if thumb_path.exists() {
-        println!(
-            "[Indexing][Rust][Thumbnail] Reusing cached thumbnail for '{}' page {}",
-            comic_path, page_number
-        );
-        return Ok(to_forward_slash_path(&thumb_path));
+    if let Some(parent) = thumb_path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create indexed thumbnail dir {}: {e}", parent.display()))?;
     }
-
-    println!(
-        "[Indexing][Rust][Thumbnail] Generating thumbnail for '{}' page {}",
-        comic_path, page_number
-    );
-    write_resized_thumbnail_bytes(image_bytes, &thumb_path)?;
-    println!(
-        "[Indexing][Rust][Thumbnail] Generated thumbnail for '{}' page {} -> {}",
-        comic_path,
-        page_number,
-        to_forward_slash_path(&thumb_path)
-    );
-    Ok(to_forward_slash_path(&thumb_path))
+    if !thumb_path.exists() {
+        write_resized_thumbnail_bytes(image_bytes, thumb_path, settings)?;
+    }
+    Ok(to_forward_slash_path(thumb_path))
 }
 
 fn get_relative_path(base_path: &Path, target_path: &Path) -> String {
@@ -465,565 +474,1003 @@ fn extract_metadata(relative_path: &str, pattern: &str) -> Option<PatternMetadat
     None
 }
 
-fn list_image_pages_internal(comic_dir: &Path) -> Result<Vec<ImagePageEntry>, String> {
-    if !comic_dir.exists() || !comic_dir.is_dir() {
-        return Err(format!("Comic path is not a directory: {}", comic_dir.display()));
+/// Reads and parses the ComicInfo.xml of a comic. A missing or malformed file
+/// only loses the embedded metadata, so errors are logged instead of failing
+/// the comic.
+fn read_comic_info(comic_path: &str, source_type: &str) -> Option<ComicInfo> {
+    let result = open_source(comic_path, source_type)
+        .and_then(|source| source.comic_info_xml())
+        .and_then(|xml| xml.map(|bytes| parse_comic_info(&bytes)).transpose());
+    match result {
+        Ok(comic_info) => comic_info,
+        Err(message) => {
+            eprintln!(
+                "[Indexing][Rust] Ignoring ComicInfo.xml of '{}': {}",
+                comic_path, message
+            );
+            None
+        }
     }
+}
 
-    let entries = fs::read_dir(comic_dir)
-        .map_err(|e| format!("Failed to read comic dir {}: {e}", comic_dir.display()))?;
-    let mut files: Vec<PathBuf> = entries
-        .filter_map(Result::ok)
-        .map(|e| e.path())
-        .filter(|p| p.is_file() && is_image_file(p))
-        .collect();
+/// Combines path pattern and ComicInfo.xml metadata, filling each field from
+/// the preferred source first. The "Unknown" artist and title series fallbacks
+/// only apply when the pattern did not match and ComicInfo.xml has no value.
+fn resolve_metadata(
+    pattern_metadata: Option<PatternMetadata>,
+    comic_info: Option<&ComicInfo>,
+    precedence: MetadataPrecedence,
+    title: &str,
+) -> PatternMetadata {
+    let pattern_matched = pattern_metadata.is_some();
+    let from_pattern = pattern_metadata.unwrap_or_default();
+    let from_comic_info = comic_info
+        .map(|info| PatternMetadata {
+            artist: info.writer.clone().or_else(|| info.penciller.clone()),
+            series: info.series.clone(),
+            issue: info.number.clone(),
+        })
+        .unwrap_or_default();
 
-    files.sort_by(|a, b| {
-        let aa = a.file_name().and_then(|s| s.to_str()).unwrap_or_default();
-        let bb = b.file_name().and_then(|s| s.to_str()).unwrap_or_default();
-        natural_cmp(aa, bb)
-    });
+    let (primary, secondary) = match precedence {
+        MetadataPrecedence::ComicInfo => (from_comic_info, from_pattern),
+        MetadataPrecedence::Pattern => (from_pattern, from_comic_info),
+    };
+    let mut metadata = PatternMetadata {
+        artist: primary.artist.or(secondary.artist),
+        series: primary.series.or(secondary.series),
+        issue: primary.issue.or(secondary.issue),
+    };
+    if !pattern_matched {
+        metadata.artist.get_or_insert_with(|| "Unknown".to_string());
+        metadata.series.get_or_insert_with(|| title.to_string());
+    }
+    metadata
+}
 
-    Ok(files
-        .iter()
-        .enumerate()
-        .map(|(idx, path)| ImagePageEntry {
-            file_path: to_forward_slash_path(path),
-            file_name: path
-                .file_name()
-                .and_then(|s| s.to_str())
-                .unwrap_or_default()
-                .to_string(),
-            page_number: (idx + 1) as i64,
-        })
-        .collect())
+fn page_payload(
+    source: &dyn ComicSource,
+    page: SourcePage,
+    thumbnail_path: Option<String>,
+) -> IndexedPagePayload {
+    let comic_path = to_forward_slash_path(source.path());
+    let (file_path, archive_entry_path, pdf_page_number) = match page.locator {
+        PageLocator::File(file_path) => (file_path, None, None),
+        PageLocator::ArchiveEntry(entry_path) => (comic_path, Some(entry_path), None),
+        PageLocator::PdfPage(pdf_page) => (comic_path, None, Some(pdf_page)),
+    };
+
+    IndexedPagePayload {
+        page_number: page.page_number,
+        source_path: file_path.clone(),
+        file_path,
+        file_name: page.file_name,
+        source_type: source.source_type().to_string(),
+        archive_entry_path,
+        pdf_page_number,
+        thumbnail_exists: thumbnail_path.is_some(),
+        thumbnail_path: thumbnail_path.map(|path| normalize_path_string(&path)),
+        page_type: None,
+    }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn build_pages_for_candidate(
     app: &AppHandle,
-    base_path: &str,
-    total_comics: usize,
-    current_comic: usize,
     comic_path: &str,
     source_type: &str,
     full_reindex: bool,
+    thumbnail_settings: &ThumbnailSettings,
+    cover_only: bool,
+    job: Option<&IndexingJob>,
+    progress: &ComicProgress,
 ) -> Result<Vec<IndexedPagePayload>, String> {
-    if source_type == "image" {
-        let entries = list_image_pages_internal(Path::new(comic_path))?;
-        // Pre-create comic thumbnail directory once to avoid contention in parallel loop
-        ensure_indexed_thumb_comic_dir(app, comic_path)?;
+    let source = open_source(comic_path, source_type)?;
+    let pages = source.list_pages()?;
+    if pages.is_empty() {
+        return Ok(Vec::new());
+    }
+    progress.pages_discovered(pages.len());
+    let indexed_root = ensure_indexed_thumb_root(app)?;
+    let thumbnail_cache = app.state::<ThumbnailCache>();
+
+    // With `cover_only`, pages after the first keep only thumbnails that
+    // already exist; the others are deferred.
+    let mut thumbnails: HashMap<i64, String> = HashMap::new();
+    let mut pending: Vec<&SourcePage> = Vec::new();
+    for (position, page) in pages.iter().enumerate() {
+        let thumb_path = get_indexed_thumbnail_path(&indexed_root, &page.fingerprint, thumbnail_settings);
+        if thumb_path.exists() && !full_reindex {
+            thumbnail_cache.record_hit(&thumb_path);
+            thumbnails.insert(page.page_number, to_forward_slash_path(&thumb_path));
+            progress.page_done(0);
+        } else if cover_only && position > 0 {
+            progress.page_done(0);
+        } else {
+            pending.push(page);
+        }
+    }
 
-        return entries
-            .into_par_iter()
-            .map(|entry| {
-                let thumb_path = get_indexed_thumbnail_path(app, comic_path, entry.page_number)?;
-                let thumb_exists = thumb_path.exists();
+    let generate = |page: &SourcePage, bytes: &[u8]| -> Result<(i64, String), String> {
+        if let Some(job) = job {
+            job.checkpoint()?;
+        }
+        thumbnail_cache.record_miss();
+        let thumb_path = generate_indexed_thumbnail_from_bytes(
+            bytes,
+            &get_indexed_thumbnail_path(&indexed_root, &page.fingerprint, thumbnail_settings),
+            thumbnail_settings,
+        )?;
+        progress.page_done(bytes.len());
+        Ok((page.page_number, thumb_path))
+    };
 
-                let (final_thumb_path, exists) = if thumb_exists && !full_reindex {
-                    (thumb_path.to_str().unwrap().to_string(), true)
-                } else {
-                    let task = format!(
-                        "Generating image thumbnail for page {} ({})",
-                        entry.page_number, entry.file_name
-                    );
-                    emit_indexing_progress(app, base_path, total_comics, current_comic, comic_path, &task);
-                    println!(
-                        "[Indexing][Rust][Thumbnail] Processing image source '{}' page {}",
-                        entry.file_path, entry.page_number
-                    );
-                    let bytes = fs::read(PathBuf::from(entry.file_path.clone())).map_err(|e| {
-                        format!("Failed to read source image for thumbnail generation: {e}")
-                    })?;
-                    let thumbnail_path =
-                        generate_indexed_thumbnail_from_bytes(app, &bytes, comic_path, entry.page_number)?;
-                    (thumbnail_path, true)
-                };
-
-                Ok(IndexedPagePayload {
-                    page_number: entry.page_number,
-                    file_path: entry.file_path.clone(),
-                    file_name: entry.file_name,
-                    source_type: "image".to_string(),
-                    source_path: entry.file_path,
-                    archive_entry_path: None,
-                    pdf_page_number: None,
-                    thumbnail_path: Some(normalize_path_string(&final_thumb_path)),
-                    thumbnail_exists: exists,
-                })
-            })
-            .collect();
+    if source.random_access() {
+        let generated: Vec<(i64, String)> = pending
+            .into_par_iter()
+            .map(|page| generate(page, &source.read_page_preview(&page.locator)?))
+            .collect::<Result<_, String>>()?;
+        thumbnails.extend(generated);
+    } else if !pending.is_empty() {
+        // Sequential formats are decoded in one pass. Pages are thumbnailed in
+        // batches along the way, so only one batch is held in memory.
+        if let Some(job) = job {
+            job.checkpoint()?;
+        }
+        let by_locator: HashMap<&PageLocator, &SourcePage> =
+            pending.iter().map(|page| (&page.locator, *page)).collect();
+        let locators: Vec<PageLocator> = pending.iter().map(|page| page.locator.clone()).collect();
+        let batch_size = rayon::current_num_threads() * 2;
+        let mut batch: Vec<(&SourcePage, Vec<u8>)> = Vec::with_capacity(batch_size);
+        let mut flush = |batch: &mut Vec<(&SourcePage, Vec<u8>)>| -> Result<(), String> {
+            let generated: Vec<(i64, String)> = batch
+                .par_drain(..)
+                .map(|(page, bytes)| generate(page, &bytes))
+                .collect::<Result<_, String>>()?;
+            thumbnails.extend(generated);
+            Ok(())
+        };
+        source.visit_pages(&locators, &mut |locator, bytes| {
+            let page = by_locator
+                .get(locator)
+                .ok_or_else(|| format!("{} returned an unrequested page: {:?}", source.format(), locator))?;
+            batch.push((page, bytes));
+            if batch.len() >= batch_size {
+                flush(&mut batch)?;
+            }
+            Ok(())
+        })?;
+        flush(&mut batch)?;
     }
 
-    if source_type == "pdf" {
-        let page_count = count_pdf_pages(comic_path.to_string())?;
-        let mut pages = Vec::new();
-        for page_number in 1..=page_count {
-            let thumb_path = get_indexed_thumbnail_path(app, comic_path, page_number)?;
-            let thumb_exists = thumb_path.exists();
-
-            let task = format!(
-                "Preparing PDF page {} metadata (thumbnail {})",
-                page_number,
-                if thumb_exists { "exists" } else { "generated in frontend" }
-            );
-            emit_indexing_progress(
-                app,
-                base_path,
-                total_comics,
-                current_comic,
-                comic_path,
-                &task,
-            );
-            
-            pages.push(IndexedPagePayload {
-                page_number,
-                file_path: normalize_path_string(comic_path),
-                file_name: format!("page-{}.pdf", page_number),
-                source_type: "pdf".to_string(),
-                source_path: normalize_path_string(comic_path),
-                archive_entry_path: None,
-                pdf_page_number: Some(page_number),
-                thumbnail_path: if thumb_exists { Some(normalize_path_string(thumb_path.to_str().unwrap())) } else { None },
-                thumbnail_exists: thumb_exists,
-            });
+    Ok(pages
+        .into_iter()
+        .map(|page| {
+            let thumbnail_path = thumbnails.remove(&page.page_number);
+            page_payload(source.as_ref(), page, thumbnail_path)
+        })
+        .collect())
+}
+
+/// Result of reading one candidate, applied to the run in candidate order.
+enum CandidateOutcome {
+    Unchanged(SourceFingerprint),
+    Empty,
+    Indexed {
+        change: ComicChange,
+        comic: Box<IndexedComicPayload>,
+        fingerprint: Option<SourceFingerprint>,
+    },
+    Failed(String),
+}
+
+struct ProcessedCandidate {
+    comic_path: String,
+    previous_fingerprint: Option<SourceFingerprint>,
+    outcome: CandidateOutcome,
+}
+
+/// Read-only settings of an indexing run, shared by its workers.
+#[derive(Clone, Copy)]
+struct CandidateIndexer<'a> {
+    app: &'a AppHandle,
+    base_path: &'a str,
+    pattern: &'a str,
+    precedence: MetadataPrecedence,
+    full_reindex: bool,
+    thumbnail_settings: ThumbnailSettings,
+    /// Generate only cover thumbnails; see [`build_pages_for_candidate`].
+    cover_only: bool,
+    reuse_previous: bool,
+    job: Option<&'a IndexingJob>,
+}
+
+impl CandidateIndexer<'_> {
+    /// Reads the candidate's source and builds its payload. Fails only when
+    /// the job is cancelled; any other problem is reported as an outcome.
+    fn process(
+        &self,
+        candidate: ComicCandidate,
+        previous_fingerprint: Option<SourceFingerprint>,
+        position: usize,
+        total_candidates: usize,
+        progress: &IndexingProgress,
+        permits: &IoPermits,
+    ) -> Result<ProcessedCandidate, String> {
+        if let Some(job) = self.job {
+            job.checkpoint()?;
         }
-        return Ok(pages);
+        let _permit = permits.acquire();
+        let comic_path = candidate.path.clone();
+        let comic_progress = progress.start_comic(&comic_path);
+        println!(
+            "[Indexing][Rust] [{}/{}] Processing '{}' (type: {})",
+            position,
+            total_candidates,
+            comic_path,
+            candidate.source_type
+        );
+
+        let outcome = self.build_outcome(candidate, previous_fingerprint.as_ref(), &comic_progress)?;
+        Ok(ProcessedCandidate {
+            comic_path,
+            previous_fingerprint,
+            outcome,
+        })
     }
 
-    let entry_names = list_archive_image_entries(comic_path.to_string())?;
-    ensure_indexed_thumb_comic_dir(app, comic_path)?;
-
-    let archive_path = PathBuf::from(comic_path);
-    let ext = extension_of(&archive_path).unwrap_or_default();
-
-    if ext == "cbz" {
-        let file = fs::File::open(&archive_path)
-            .map_err(|e| format!("Failed to open CBZ archive {}: {e}", archive_path.display()))?;
-        let mut archive = ZipArchive::new(file)
-            .map_err(|e| format!("Failed to read CBZ archive {}: {e}", archive_path.display()))?;
-
-        enum CbzTask {
-            AlreadyExists {
-                page_number: i64,
-                entry_path: String,
-                thumb_path: String,
-            },
-            Generate {
-                page_number: i64,
-                entry_path: String,
-                bytes: Vec<u8>,
-            },
+    fn build_outcome(
+        &self,
+        candidate: ComicCandidate,
+        previous_fingerprint: Option<&SourceFingerprint>,
+        progress: &ComicProgress,
+    ) -> Result<CandidateOutcome, String> {
+        let comic_path = candidate.path.as_str();
+        let source_metadata = open_source(comic_path, &candidate.source_type)
+            .and_then(|source| source.metadata())
+            .ok();
+        if let (true, Some(fingerprint), Some(source_metadata)) =
+            (self.reuse_previous, previous_fingerprint, source_metadata.as_ref())
+        {
+            if fingerprint.matches(&candidate.source_type, source_metadata) {
+                return Ok(CandidateOutcome::Unchanged(fingerprint.clone()));
+            }
         }
 
-        let mut tasks = Vec::with_capacity(entry_names.len());
-        
-        for (idx, entry_path) in entry_names.into_iter().enumerate() {
-            let page_number = (idx + 1) as i64;
-            let thumb_path = get_indexed_thumbnail_path(app, comic_path, page_number)?;
-            let thumb_exists = thumb_path.exists();
-
-            if thumb_exists && !full_reindex {
-                tasks.push(CbzTask::AlreadyExists {
-                    page_number,
-                    entry_path,
-                    thumb_path: thumb_path.to_str().unwrap().to_string(),
-                });
-            } else {
-                let mut entry_file = archive.by_name(&entry_path)
-                    .map_err(|e| format!("Failed to find entry {} in archive: {e}", entry_path))?;
-                
-                let mut bytes = Vec::new();
-                entry_file.read_to_end(&mut bytes)
-                    .map_err(|e| format!("Failed to read CBZ image entry {}: {e}", entry_path))?;
-                
-                tasks.push(CbzTask::Generate {
-                    page_number,
-                    entry_path,
-                    bytes,
-                });
+        let relative_path = get_relative_path(Path::new(self.base_path), Path::new(comic_path));
+        let comic_info = read_comic_info(comic_path, &candidate.source_type);
+        let metadata = resolve_metadata(
+            extract_metadata(&relative_path, self.pattern),
+            comic_info.as_ref(),
+            self.precedence,
+            &candidate.title,
+        );
+        let comic_info = comic_info.unwrap_or_default();
+
+        let mut pages = match build_pages_for_candidate(
+            self.app,
+            comic_path,
+            &candidate.source_type,
+            self.full_reindex,
+            &self.thumbnail_settings,
+            self.cover_only,
+            self.job,
+            progress,
+        ) {
+            Ok(pages) => pages,
+            Err(message) if self.job.is_some_and(|job| job.is_cancelled()) => return Err(message),
+            Err(message) => {
+                eprintln!("[Indexing][Rust] Failed '{}': {}", comic_path, message);
+                return Ok(CandidateOutcome::Failed(message));
             }
+        };
+        if pages.is_empty() {
+            println!(
+                "[Indexing][Rust] Skipping '{}' (no pages discovered)",
+                comic_path
+            );
+            return Ok(CandidateOutcome::Empty);
         }
 
-        return tasks.into_par_iter().map(|task| {
-            let comic_path_str = comic_path.to_string();
-            match task {
-                CbzTask::AlreadyExists { page_number, entry_path, thumb_path } => {
-                    let task_desc = format!("Preparing CBZ page {} metadata (exists)", page_number);
-                    emit_indexing_progress(app, base_path, total_comics, current_comic, &comic_path_str, &task_desc);
-                    
-                    let file_name = Path::new(&entry_path)
-                        .file_name()
-                        .and_then(|s| s.to_str())
-                        .unwrap_or(&entry_path)
-                        .to_string();
-
-                    Ok(IndexedPagePayload {
-                        page_number,
-                        file_path: normalize_path_string(&comic_path_str),
-                        file_name,
-                        source_type: "archive".to_string(),
-                        source_path: normalize_path_string(&comic_path_str),
-                        archive_entry_path: Some(entry_path),
-                        pdf_page_number: None,
-                        thumbnail_path: Some(normalize_path_string(&thumb_path)),
-                        thumbnail_exists: true,
-                    })
-                },
-                CbzTask::Generate { page_number, entry_path, bytes } => {
-                    let task_desc = format!("Generating CBZ thumbnail for page {} ({})", page_number, entry_path);
-                    emit_indexing_progress(app, base_path, total_comics, current_comic, &comic_path_str, &task_desc);
-                    
-                    let thumbnail_path = generate_indexed_thumbnail_from_bytes(app, &bytes, &comic_path_str, page_number)?;
-                    
-                    let file_name = Path::new(&entry_path)
-                        .file_name()
-                        .and_then(|s| s.to_str())
-                        .unwrap_or(&entry_path)
-                        .to_string();
-
-                    Ok(IndexedPagePayload {
-                        page_number,
-                        file_path: normalize_path_string(&comic_path_str),
-                        file_name,
-                        source_type: "archive".to_string(),
-                        source_path: normalize_path_string(&comic_path_str),
-                        archive_entry_path: Some(entry_path),
-                        pdf_page_number: None,
-                        thumbnail_path: Some(normalize_path_string(&thumbnail_path)),
-                        thumbnail_exists: true,
-                    })
-                }
+        for (page_number, page_type) in comic_info.page_types {
+            if let Some(page) = pages.iter_mut().find(|page| page.page_number == page_number) {
+                page.page_type = Some(page_type);
             }
-        }).collect();
+        }
+
+        let cover_image_path = pages.first().map(|page| page.file_path.clone());
+        let page_count = pages.len();
+        let fingerprint = source_metadata.map(|source_metadata| {
+            SourceFingerprint::new(&candidate.source_type, &source_metadata, page_count)
+        });
+        let change = if previous_fingerprint.is_some() {
+            ComicChange::Changed
+        } else {
+            ComicChange::Added
+        };
+        println!(
+            "[Indexing][Rust] Indexed '{}' with {} pages",
+            comic_path, page_count
+        );
+        Ok(CandidateOutcome::Indexed {
+            change,
+            comic: Box::new(IndexedComicPayload {
+                path: candidate.path,
+                title: candidate.title,
+                source_type: candidate.source_type,
+                artist: metadata.artist,
+                series: metadata.series,
+                issue: metadata.issue,
+                volume: comic_info.volume,
+                writer: comic_info.writer,
+                penciller: comic_info.penciller,
+                publisher: comic_info.publisher,
+                year: comic_info.year,
+                summary: comic_info.summary,
+                genre: comic_info.genre,
+                manga: comic_info.manga,
+                cover_image_path,
+                page_count: page_count as i64,
+                pages,
+            }),
+            fingerprint,
+        })
     }
+}
 
-    if ext == "cbr" {
-        let mut archive = Archive::new(comic_path)
-            .open_for_processing()
-            .map_err(|e| format!("Failed to open CBR for processing {}: {e}", archive_path.display()))?;
-
-        enum CbrTask {
-            AlreadyExists {
-                page_number: i64,
-                entry_path: String,
-                thumb_path: String,
-            },
-            Generate {
-                page_number: i64,
-                entry_path: String,
-                bytes: Vec<u8>,
-            },
+/// State of one indexing run over (a subset of) the candidates of an index
+/// path. Fingerprints of the previous run decide which comics can be skipped
+/// and whether an indexed comic is reported as added or changed.
+struct IndexingRun<'a> {
+    app: &'a AppHandle,
+    base_path: &'a str,
+    pattern: &'a str,
+    precedence: MetadataPrecedence,
+    full_reindex: bool,
+    thumbnail_settings: ThumbnailSettings,
+    cover_only: bool,
+    job: Option<&'a IndexingJob>,
+    on_comic: Option<&'a Channel<IndexedComicEvent>>,
+    progress: &'a IndexingProgress<'a>,
+    reuse_previous: bool,
+    previous_state: IndexState,
+    state: IndexState,
+    result: BuildIndexPayloadResult,
+    state_guard: IndexStateGuard,
+}
+
+impl<'a> IndexingRun<'a> {
+    fn new(
+        app: &'a AppHandle,
+        base_path: &'a str,
+        pattern: &'a str,
+        precedence: MetadataPrecedence,
+        full_reindex: bool,
+        job: Option<&'a IndexingJob>,
+        progress: &'a IndexingProgress<'a>,
+    ) -> Result<Self, String> {
+        let state_guard = lock_index_state(base_path, job)?;
+        let thumbnail_settings = app.state::<ThumbnailConfig>().get(app);
+        let previous_state = IndexState::load(app, base_path);
+        let reuse_previous =
+            !full_reindex && previous_state.is_compatible(pattern, precedence, &thumbnail_settings);
+        if !reuse_previous && !previous_state.comics.is_empty() {
+            println!(
+                "[Indexing][Rust] Ignoring previous index state for '{}' (full reindex or changed settings)",
+                base_path
+            );
         }
 
-        let mut name_to_page = HashMap::new();
-        for (idx, name) in entry_names.iter().enumerate() {
-            name_to_page.insert(name.clone(), (idx + 1) as i64);
+        Ok(Self {
+            app,
+            base_path,
+            pattern,
+            precedence,
+            full_reindex,
+            thumbnail_settings,
+            cover_only: false,
+            job,
+            on_comic: None,
+            progress,
+            reuse_previous,
+            previous_state,
+            state: IndexState::new(pattern, precedence, thumbnail_settings),
+            result: BuildIndexPayloadResult::default(),
+            state_guard,
+        })
+    }
+
+    /// Starts from the previous state so that comics outside of the indexed
+    /// candidates stay untouched.
+    fn keep_previous_comics(&mut self) {
+        if self.reuse_previous {
+            self.state.comics = self.previous_state.comics.clone();
+            self.state.retry_comics = self.previous_state.retry_comics.clone();
         }
+    }
 
-        let mut tasks = Vec::new();
-
-        loop {
-            let Some(before_file) = archive
-                .read_header()
-                .map_err(|e| format!("Failed reading CBR header {}: {e}", archive_path.display()))?
-            else {
-                break;
-            };
-
-            let entry_path = before_file.entry().filename.to_string_lossy().replace('\\', "/");
-            if is_image_entry_name(&entry_path) {
-                let page_number = *name_to_page.get(&entry_path).ok_or_else(|| format!("Entry {} not found in pre-scanned list", entry_path))?;
-                let thumb_path = get_indexed_thumbnail_path(app, comic_path, page_number)?;
-                let thumb_exists = thumb_path.exists();
-
-                if thumb_exists && !full_reindex {
-                    tasks.push(CbrTask::AlreadyExists {
-                        page_number,
-                        entry_path,
-                        thumb_path: thumb_path.to_str().unwrap().to_string(),
-                    });
-                    archive = before_file.skip().map_err(|e| format!("Failed skipping CBR entry: {e}"))?;
-                } else {
-                    let (data, next_archive) = before_file
-                        .read()
-                        .map_err(|e| format!("Failed reading CBR image entry {}: {e}", entry_path))?;
-                    
-                    tasks.push(CbrTask::Generate {
-                        page_number,
-                        entry_path,
-                        bytes: data,
-                    });
-                    archive = next_archive;
+    fn indexer(&self) -> CandidateIndexer<'a> {
+        CandidateIndexer {
+            app: self.app,
+            base_path: self.base_path,
+            pattern: self.pattern,
+            precedence: self.precedence,
+            full_reindex: self.full_reindex,
+            thumbnail_settings: self.thumbnail_settings,
+            cover_only: self.cover_only,
+            reuse_previous: self.reuse_previous,
+            job: self.job,
+        }
+    }
+
+    /// Reads candidates on a bounded number of workers, sized for the storage
+    /// of the index path, and applies their outcomes in candidate order so the
+    /// result does not depend on which comic finishes first.
+    ///
+    /// Fails only when the run's job is cancelled. The state is not saved in
+    /// that case, so the next run starts from the previous fingerprints.
+    fn index_candidates(&mut self, candidates: Vec<ComicCandidate>) -> Result<(), String> {
+        let total_candidates = candidates.len();
+        self.progress.set_comics(0, total_candidates);
+        self.progress.set_phase(IndexingPhase::Indexing);
+        if total_candidates == 0 {
+            return Ok(());
+        }
+
+        let storage = detect_storage_kind(Path::new(self.base_path));
+        let concurrency = IoConcurrency::for_storage(storage);
+        let file_permits = IoPermits::new(concurrency.files);
+        let folder_permits = IoPermits::new(concurrency.folders);
+        let worker_count = concurrency.max().min(total_candidates);
+        self.progress.set_concurrency(storage, worker_count);
+        println!(
+            "[Indexing][Rust] Indexing {} comics in '{}' with {} workers ({:?} storage, {} archive readers)",
+            total_candidates, self.base_path, worker_count, storage, concurrency.files
+        );
+
+        let queue: Vec<(usize, ComicCandidate, Option<SourceFingerprint>)> = candidates
+            .into_iter()
+            .enumerate()
+            .map(|(index, candidate)| {
+                let previous_fingerprint = self.previous_state.comics.get(&candidate.path).cloned();
+                (index, candidate, previous_fingerprint)
+            })
+            .collect();
+        let queue = Mutex::new(queue.into_iter());
+        let stopped = AtomicBool::new(false);
+        let indexer = self.indexer();
+        let (sender, receiver) = mpsc::channel();
+
+        thread::scope(|scope| {
+            for _ in 0..worker_count {
+                let sender = sender.clone();
+                let (queue, progress, stopped) = (&queue, self.progress, &stopped);
+                let (file_permits, folder_permits) = (&file_permits, &folder_permits);
+                scope.spawn(move || {
+                    while !stopped.load(AtomicOrdering::Relaxed) {
+                        let next = queue
+                            .lock()
+                            .unwrap_or_else(|poisoned| poisoned.into_inner())
+                            .next();
+                        let Some((index, candidate, previous_fingerprint)) = next else {
+                            break;
+                        };
+                        let permits = if candidate.source_type == "image" {
+                            folder_permits
+                        } else {
+                            file_permits
+                        };
+                        let processed = indexer.process(
+                            candidate,
+                            previous_fingerprint,
+                            index + 1,
+                            total_candidates,
+                            progress,
+                            permits,
+                        );
+                        if sender.send((index, processed)).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+            drop(sender);
+
+            let mut outcome = Ok(());
+            let mut next_index = 0;
+            let mut buffered = BTreeMap::new();
+            for (index, processed) in receiver {
+                buffered.insert(index, processed);
+                while let Some(processed) = buffered.remove(&next_index) {
+                    next_index += 1;
+                    match processed {
+                        Ok(processed) if outcome.is_ok() => self.apply(processed),
+                        Ok(_) => {}
+                        Err(message) => {
+                            stopped.store(true, AtomicOrdering::Relaxed);
+                            outcome = outcome.and(Err(message));
+                        }
+                    }
                 }
-            } else {
-                archive = before_file
-                    .skip()
-                    .map_err(|e| format!("Failed skipping CBR entry: {e}"))?;
             }
-        }
+            outcome
+        })
+    }
 
-        let mut results: Vec<IndexedPagePayload> = tasks.into_par_iter().map(|task| {
-            let comic_path_str = comic_path.to_string();
-            match task {
-                CbrTask::AlreadyExists { page_number, entry_path, thumb_path } => {
-                    let task_desc = format!("Preparing CBR page {} metadata (exists)", page_number);
-                    emit_indexing_progress(app, base_path, total_comics, current_comic, &comic_path_str, &task_desc);
-
-                    let file_name = Path::new(&entry_path)
-                        .file_name()
-                        .and_then(|s| s.to_str())
-                        .unwrap_or(&entry_path)
-                        .to_string();
-
-                    Ok(IndexedPagePayload {
-                        page_number,
-                        file_path: normalize_path_string(&comic_path_str),
-                        file_name,
-                        source_type: "archive".to_string(),
-                        source_path: normalize_path_string(&comic_path_str),
-                        archive_entry_path: Some(entry_path),
-                        pdf_page_number: None,
-                        thumbnail_path: Some(normalize_path_string(&thumb_path)),
-                        thumbnail_exists: true,
-                    })
-                },
-                CbrTask::Generate { page_number, entry_path, bytes } => {
-                    let task_desc = format!("Generating CBR thumbnail for page {} ({})", page_number, entry_path);
-                    emit_indexing_progress(app, base_path, total_comics, current_comic, &comic_path_str, &task_desc);
-
-                    let thumbnail_path = generate_indexed_thumbnail_from_bytes(app, &bytes, &comic_path_str, page_number)?;
-                    
-                    let file_name = Path::new(&entry_path)
-                        .file_name()
-                        .and_then(|s| s.to_str())
-                        .unwrap_or(&entry_path)
-                        .to_string();
-
-                    Ok(IndexedPagePayload {
-                        page_number,
-                        file_path: normalize_path_string(&comic_path_str),
-                        file_name,
-                        source_type: "archive".to_string(),
-                        source_path: normalize_path_string(&comic_path_str),
-                        archive_entry_path: Some(entry_path),
-                        pdf_page_number: None,
-                        thumbnail_path: Some(normalize_path_string(&thumbnail_path)),
-                        thumbnail_exists: true,
-                    })
+    fn apply(&mut self, processed: ProcessedCandidate) {
+        let ProcessedCandidate {
+            comic_path,
+            previous_fingerprint,
+            outcome,
+        } = processed;
+        match outcome {
+            CandidateOutcome::Unchanged(fingerprint) => {
+                self.state.set_fingerprint(comic_path.clone(), fingerprint);
+                self.result.active_comic_paths.push(comic_path.clone());
+                self.result.unchanged_comic_paths.push(comic_path);
+            }
+            CandidateOutcome::Empty => {
+                self.state.remove(&comic_path);
+            }
+            CandidateOutcome::Indexed {
+                change,
+                comic,
+                fingerprint,
+            } => {
+                match fingerprint {
+                    Some(fingerprint) => self.state.set_fingerprint(comic_path.clone(), fingerprint),
+                    None => self.state.retry_next_run(comic_path.clone()),
+                }
+                self.result.active_comic_paths.push(comic_path);
+                self.deliver(change, *comic);
+            }
+            CandidateOutcome::Failed(message) => {
+                // Keep the last good index of the comic; a changed source no
+                // longer matches the fingerprint and is retried next run.
+                match previous_fingerprint {
+                    Some(fingerprint) => {
+                        self.state.set_fingerprint(comic_path.clone(), fingerprint);
+                        self.result.active_comic_paths.push(comic_path.clone());
+                    }
+                    None if self.previous_state.retry_comics.contains(&comic_path) => {
+                        self.state.retry_next_run(comic_path.clone());
+                        self.result.active_comic_paths.push(comic_path.clone());
+                    }
+                    None => {
+                        self.state.remove(&comic_path);
+                    }
                 }
+                self.result.errors.push(IndexingErrorPayload {
+                    path: comic_path,
+                    message,
+                });
+            }
+        }
+    }
+
+    /// Saves the comic to the database, then streams it when a channel is
+    /// attached or collects it into the result otherwise.
+    fn deliver(&mut self, change: ComicChange, comic: IndexedComicPayload) {
+        if let Err(message) = self.app.state::<LibraryDatabase>().upsert_comic(self.app, &comic) {
+            eprintln!("[Indexing][Rust] {}", message);
+            self.state.retry_next_run(comic.path.clone());
+            self.result.errors.push(IndexingErrorPayload {
+                path: comic.path,
+                message,
+            });
+            return;
+        }
+
+        let Some(channel) = self.on_comic else {
+            match change {
+                ComicChange::Added => self.result.added.push(comic),
+                ComicChange::Changed => self.result.changed.push(comic),
             }
-        }).collect::<Result<Vec<IndexedPagePayload>, String>>()?;
+            return;
+        };
 
-        results.sort_by(|a, b| a.page_number.cmp(&b.page_number));
-        return Ok(results);
+        let comic_path = comic.path.clone();
+        if let Err(e) = channel.send(IndexedComicEvent { change, comic }) {
+            // The comic is saved, but the frontend never saw it: stream it
+            // again next run rather than skipping it as unchanged.
+            eprintln!("[Indexing][Rust] Failed to stream '{}': {e}", comic_path);
+            self.state.retry_next_run(comic_path);
+        }
     }
 
-    Err(format!("Unsupported archive extension for file: {}", archive_path.display()))
+    /// Removes comics of the previous run that are gone from the database and
+    /// persists the new fingerprints. Comics marked for a retry are kept.
+    fn finish(mut self) -> BuildIndexPayloadResult {
+        self.progress.set_phase(IndexingPhase::Finalizing);
+        let mut removed_comic_paths: Vec<String> = self
+            .previous_state
+            .comic_paths()
+            .filter(|path| !self.state.contains(path))
+            .cloned()
+            .collect();
+        removed_comic_paths.sort_by(|a, b| natural_cmp(a, b));
+        if let Err(message) = self
+            .app
+            .state::<LibraryDatabase>()
+            .delete_comics(self.app, &removed_comic_paths)
+        {
+            eprintln!("[Indexing][Rust] {}", message);
+        }
+        self.result.removed_comic_paths = removed_comic_paths;
+
+        if let Err(message) = self.state.save(self.app, self.base_path) {
+            eprintln!(
+                "[Indexing][Rust] Failed to save index state for '{}': {}",
+                self.base_path, message
+            );
+        }
+        // The run's thumbnails are in the database now; eviction only has to
+        // spare those of other running work, and may update this run's state.
+        drop(self.state_guard);
+        if let Err(message) = self
+            .app
+            .state::<ThumbnailCache>()
+            .enforce_budget(self.app, &self.thumbnail_settings)
+        {
+            eprintln!("[Thumbnails][Rust] {}", message);
+        }
+
+        println!(
+            "[Indexing][Rust] Completed payload build for '{}': added={}, changed={}, removed={}, unchanged={}, errors={}",
+            self.base_path,
+            self.result.added.len(),
+            self.result.changed.len(),
+            self.result.removed_comic_paths.len(),
+            self.result.unchanged_comic_paths.len(),
+            self.result.errors.len()
+        );
+        self.progress.set_phase(IndexingPhase::Completed);
+        self.result
+    }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn build_index_payload_for_path_impl(
     app: &AppHandle,
     base_path: &str,
     pattern: &str,
+    precedence: MetadataPrecedence,
+    full_reindex: bool,
+    cover_only: bool,
+    job: Option<&IndexingJob>,
+    on_comic: Option<&Channel<IndexedComicEvent>>,
 ) -> Result<BuildIndexPayloadResult, String> {
     println!(
         "[Indexing][Rust] Starting payload build for base path '{}' with pattern '{}'",
         base_path, pattern
     );
+    let progress = IndexingProgress::new(app, base_path);
+    progress.set_phase(IndexingPhase::Scanning);
     let scan_result = scan_comic_candidates(base_path.to_string())?;
     let candidates = scan_result.candidates;
-    let mut errors = scan_result.errors;
     let total_candidates = candidates.len();
     println!(
         "[Indexing][Rust] Found {} candidate comics in '{}'",
         total_candidates,
         base_path
     );
-    let base_path_buf = PathBuf::from(base_path);
-    let mut comics = Vec::new();
-    let mut active_comic_paths = Vec::new();
 
-    for (index, candidate) in candidates.into_iter().enumerate() {
-        let comic_path = candidate.path.clone();
-        let current_comic = index + 1;
-        emit_indexing_progress(
-            app,
-            base_path,
-            total_candidates,
-            current_comic,
-            &comic_path,
-            "Scanning comic source",
-        );
-        println!(
-            "[Indexing][Rust] [{}/{}] Processing '{}' (type: {})",
-            current_comic,
-            total_candidates,
-            comic_path,
-            candidate.source_type
-        );
-        let relative_path = get_relative_path(&base_path_buf, Path::new(&comic_path));
-        let metadata = match extract_metadata(&relative_path, pattern) {
-            Some(m) => m,
-            None => PatternMetadata {
-                artist: Some("Unknown".to_string()),
-                series: Some(candidate.title.clone()),
-                issue: None,
-            },
-        };
+    let mut run = IndexingRun::new(app, base_path, pattern, precedence, full_reindex, job, &progress)?;
+    run.cover_only = cover_only;
+    run.on_comic = on_comic;
+    run.result.errors = scan_result.errors;
+    run.index_candidates(candidates)?;
+    Ok(run.finish())
+}
 
-        match build_pages_for_candidate(
-            app,
-            base_path,
-            total_candidates,
-            current_comic,
-            &comic_path,
-            &candidate.source_type,
-            false,
-        ) {
-            Ok(pages) => {
-                if pages.is_empty() {
-                    emit_indexing_progress(
-                        app,
-                        base_path,
-                        total_candidates,
-                        current_comic,
-                        &comic_path,
-                        "Skipping comic with no pages",
-                    );
-                    println!(
-                        "[Indexing][Rust] Skipping '{}' (no pages discovered)",
-                        comic_path
-                    );
-                    continue;
-                }
+/// Re-indexes only the comics affected by changes to `changed_paths`: comics
+/// at or below a changed path, and folder comics containing a changed image.
+/// Falls back to a full run when the index path has no usable previous state.
+fn reindex_changed_paths_impl(
+    app: &AppHandle,
+    base_path: &str,
+    pattern: &str,
+    precedence: MetadataPrecedence,
+    changed_paths: &[PathBuf],
+) -> Result<BuildIndexPayloadResult, String> {
+    let progress = IndexingProgress::new(app, base_path);
+    let mut run = IndexingRun::new(app, base_path, pattern, precedence, false, None, &progress)?;
+    if !run.reuse_previous {
+        drop(run);
+        return build_index_payload_for_path_impl(app, base_path, pattern, precedence, false, false, None, None);
+    }
+    run.keep_previous_comics();
 
-                let cover_image_path = pages.first().map(|page| page.file_path.clone());
-                active_comic_paths.push(comic_path.clone());
-                comics.push(IndexedComicPayload {
-                    path: comic_path.clone(),
-                    title: candidate.title,
-                    source_type: candidate.source_type,
-                    artist: metadata.artist,
-                    series: metadata.series,
-                    issue: metadata.issue,
-                    cover_image_path,
-                    page_count: pages.len() as i64,
-                    pages,
-                });
-                emit_indexing_progress(
-                    app,
-                    base_path,
-                    total_candidates,
-                    current_comic,
-                    &comic_path,
-                    "Finished comic payload generation",
-                );
-                println!(
-                    "[Indexing][Rust] Indexed '{}' with {} pages",
-                    comic_path,
-                    comics.last().map(|comic| comic.page_count).unwrap_or(0)
-                );
-            }
-            Err(message) => {
-                emit_indexing_progress(
-                    app,
-                    base_path,
-                    total_candidates,
-                    current_comic,
-                    &comic_path,
-                    "Failed while generating comic payload",
-                );
-                eprintln!(
-                    "[Indexing][Rust] Failed '{}': {}",
-                    comic_path,
-                    message
-                );
-                errors.push(IndexingErrorPayload {
-                    path: comic_path,
-                    message,
-                });
+    let base = PathBuf::from(base_path);
+    let mut candidates = Vec::new();
+    let mut affected_comics = HashSet::new();
+    for changed_path in changed_paths.iter().filter(|path| path.starts_with(&base)) {
+        if changed_path.is_dir() {
+            walk_for_candidates(changed_path, &mut candidates, &mut run.result.errors);
+        } else {
+            candidates.extend(candidate_at(changed_path));
+        }
+
+        let changed = to_forward_slash_path(changed_path);
+        let changed_prefix = format!("{}/", changed);
+        let parent = (is_image_file(changed_path) || is_comic_info_name(&changed))
+            .then(|| changed_path.parent().map(to_forward_slash_path))
+            .flatten();
+        for comic_path in run.previous_state.comic_paths() {
+            if *comic_path == changed
+                || comic_path.starts_with(&changed_prefix)
+                || parent.as_deref() == Some(comic_path.as_str())
+            {
+                affected_comics.insert(comic_path.clone());
             }
         }
+        if let Some(parent) = parent {
+            candidates.extend(candidate_at(Path::new(&parent)));
+        }
     }
 
-    println!(
-        "[Indexing][Rust] Completed payload build for '{}': indexed={}, errors={}",
-        base_path,
-        comics.len(),
-        errors.len()
-    );
-    emit_indexing_progress(
-        app,
-        base_path,
-        total_candidates,
-        total_candidates,
-        base_path,
-        "Completed Rust indexing payload",
+    candidates.extend(
+        affected_comics
+            .iter()
+            .filter_map(|comic_path| candidate_at(Path::new(comic_path))),
     );
+    candidates.sort_by(|a, b| natural_cmp(&a.path, &b.path));
+    candidates.dedup_by(|a, b| a.path == b.path);
+    for comic_path in &affected_comics {
+        if !candidates.iter().any(|candidate| candidate.path == *comic_path) {
+            run.state.remove(comic_path);
+        }
+    }
 
-    Ok(BuildIndexPayloadResult {
-        comics,
-        active_comic_paths,
-        errors,
-    })
+    println!(
+        "[Indexing][Rust] Re-indexing {} comics affected by {} changed paths in '{}'",
+        candidates.len(),
+        changed_paths.len(),
+        base_path
+    );
+    run.index_candidates(candidates)?;
+    Ok(run.finish())
 }
 
+/// Starts indexing an index path as a background job and returns its ID.
+/// Indexed comics are streamed through `on_comic` one at a time; the summary
+/// is reported through the `indexing-job-finished` event. With `cover_only`,
+/// only cover thumbnails are generated; the other pages get theirs when the
+/// comic is opened or by a thumbnail fill job.
 #[tauri::command]
-async fn build_index_payload_for_path(
+#[allow(clippy::too_many_arguments)]
+fn start_indexing_job(
     app: AppHandle,
+    jobs: State<'_, IndexingJobs>,
     base_path: String,
     pattern: String,
-) -> Result<BuildIndexPayloadResult, String> {
+    metadata_precedence: Option<MetadataPrecedence>,
+    full_reindex: Option<bool>,
+    cover_only: Option<bool>,
+    on_comic: Channel<IndexedComicEvent>,
+) -> u64 {
+    let job = jobs.start(&base_path);
+    let job_id = job.id();
+    println!("[Indexing][Rust] Starting indexing job {} for '{}'", job_id, base_path);
+
     tauri::async_runtime::spawn_blocking(move || {
-        build_index_payload_for_path_impl(&app, &base_path, &pattern)
-    })
-    .await
-    .map_err(|error| format!("Failed to join indexing task: {error}"))?
+        let outcome = build_index_payload_for_path_impl(
+            &app,
+            &base_path,
+            &pattern,
+            metadata_precedence.unwrap_or_default(),
+            full_reindex.unwrap_or(false),
+            cover_only.unwrap_or(false),
+            Some(&job),
+            Some(&on_comic),
+        );
+        app.state::<IndexingJobs>().finish(job.id());
+
+        let (status, error, result) = match outcome {
+            Ok(result) => (IndexingJobStatus::Completed, None, Some(result)),
+            Err(_) if job.is_cancelled() => (IndexingJobStatus::Cancelled, None, None),
+            Err(message) => (IndexingJobStatus::Failed, Some(message), None),
+        };
+        println!(
+            "[Indexing][Rust] Indexing job {} for '{}' finished: {:?}",
+            job.id(),
+            job.base_path(),
+            status
+        );
+        let payload = IndexingJobFinishedPayload {
+            job_id: job.id(),
+            base_path: job.base_path().to_string(),
+            status,
+            error,
+            result,
+        };
+        if let Err(e) = app.emit(INDEXING_JOB_FINISHED_EVENT, payload) {
+            eprintln!("[Indexing][Rust] Failed to emit job finished event: {e}");
+        }
+    });
+
+    job_id
+}
+
+#[tauri::command]
+fn cancel_indexing_job(jobs: State<'_, IndexingJobs>, job_id: u64) -> Result<(), String> {
+    jobs.get(job_id)?.cancel();
+    Ok(())
+}
+
+#[tauri::command]
+fn pause_indexing_job(jobs: State<'_, IndexingJobs>, job_id: u64) -> Result<(), String> {
+    jobs.get(job_id)?.pause();
+    Ok(())
+}
+
+#[tauri::command]
+fn resume_indexing_job(jobs: State<'_, IndexingJobs>, job_id: u64) -> Result<(), String> {
+    jobs.get(job_id)?.resume();
+    Ok(())
 }
 
+/// Starts watching the given index paths for changes, replacing the previously
+/// watched set. Roots on network filesystems are polled instead.
 #[tauri::command]
-async fn get_comic_pages(
+async fn watch_index_paths(
     app: AppHandle,
-    base_path: String,
-    total_comics: usize,
-    current_comic: usize,
-    comic_path: String,
-    source_type: String,
-    full_reindex: bool,
-) -> Result<Vec<IndexedPagePayload>, String> {
+    index_paths: Vec<WatchedIndexPath>,
+) -> Result<Vec<WatchStatusPayload>, String> {
+    tauri::async_runtime::spawn_blocking(move || app.state::<LibraryWatchers>().watch(&app, index_paths))
+        .await
+        .map_err(|error| format!("Failed to join watcher task: {error}"))
+}
+
+#[tauri::command]
+async fn unwatch_index_paths(app: AppHandle) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || app.state::<LibraryWatchers>().unwatch_all())
+        .await
+        .map_err(|error| format!("Failed to join watcher task: {error}"))
+}
+
+/// Generates the missing page thumbnails of an indexed comic and stores them
+/// in the database. Returns the number of pages with a thumbnail.
+fn fill_page_thumbnails(
+    app: &AppHandle,
+    comic_path: &str,
+    source_type: &str,
+    job: Option<&IndexingJob>,
+    progress: &ComicProgress,
+) -> Result<usize, String> {
+    let thumbnail_settings = app.state::<ThumbnailConfig>().get(app);
+    let pages = build_pages_for_candidate(
+        app,
+        comic_path,
+        source_type,
+        false,
+        &thumbnail_settings,
+        false,
+        job,
+        progress,
+    )?;
+    app.state::<LibraryDatabase>()
+        .update_page_thumbnails(app, comic_path, &pages)?;
+    Ok(pages.iter().filter(|page| page.thumbnail_path.is_some()).count())
+}
+
+/// Generates the page thumbnails that were deferred by cover-only indexing,
+/// for example when the overview of the comic is opened. Waits for indexing
+/// work on the comic's index path, which could otherwise overwrite the pages.
+#[tauri::command]
+async fn ensure_page_thumbnails(app: AppHandle, comic_id: i64) -> Result<usize, String> {
     tauri::async_runtime::spawn_blocking(move || {
-        build_pages_for_candidate(
-            &app,
-            &base_path,
-            total_comics,
-            current_comic,
-            &comic_path,
-            &source_type,
-            full_reindex,
-        )
+        let (comic_path, source_type) = app.state::<LibraryDatabase>().comic_source(&app, comic_id)?;
+        let _state_guard = lock_index_state(&comic_path, None)?;
+        let progress = IndexingProgress::silent(&app, &comic_path);
+        let comic_progress = progress.start_comic(&comic_path);
+        fill_page_thumbnails(&app, &comic_path, &source_type, None, &comic_progress)
     })
     .await
-    .map_err(|error| format!("Failed to join indexing task: {error}"))?
+    .map_err(|error| format!("Failed to join thumbnail task: {error}"))?
 }
 
-fn write_resized_thumbnail_bytes(bytes: &[u8], target_path: &Path) -> Result<(), String> {
-    let image = image::load_from_memory(bytes).map_err(|e| format!("Failed to decode image bytes: {e}"))?;
-    let (width, height) = image.dimensions();
-    let scale = (THUMBNAIL_MAX_SIZE as f32 / width as f32)
-        .min(THUMBNAIL_MAX_SIZE as f32 / height as f32)
-        .min(1.0);
-    let target_w = ((width as f32) * scale).round() as u32;
-    let target_h = ((height as f32) * scale).round() as u32;
+/// Starts a job generating all deferred page thumbnails of the comics below
+/// `base_path`. It is controlled like an indexing job and reports through the
+/// same events, without a result.
+#[tauri::command]
+fn start_thumbnail_fill_job(app: AppHandle, jobs: State<'_, IndexingJobs>, base_path: String) -> u64 {
+    let job = jobs.start(&base_path);
+    let job_id = job.id();
+    println!("[Thumbnails][Rust] Starting thumbnail fill job {} for '{}'", job_id, base_path);
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let outcome = fill_missing_page_thumbnails(&app, &base_path, &job);
+        app.state::<IndexingJobs>().finish(job.id());
 
-    let resized = image.resize(target_w.max(1), target_h.max(1), FilterType::Lanczos3);
-    let mut encoded = Vec::new();
-    let mut encoder = JpegEncoder::new_with_quality(&mut encoded, THUMBNAIL_JPEG_QUALITY);
-    encoder
-        .encode_image(&resized)
-        .map_err(|e| format!("Failed to encode thumbnail jpeg: {e}"))?;
+        let (status, error) = match outcome {
+            Ok(()) => (IndexingJobStatus::Completed, None),
+            Err(_) if job.is_cancelled() => (IndexingJobStatus::Cancelled, None),
+            Err(message) => (IndexingJobStatus::Failed, Some(message)),
+        };
+        println!(
+            "[Thumbnails][Rust] Thumbnail fill job {} for '{}' finished: {:?}",
+            job.id(),
+            job.base_path(),
+            status
+        );
+        let payload = IndexingJobFinishedPayload {
+            job_id: job.id(),
+            base_path: job.base_path().to_string(),
+            status,
+            error,
+            result: None,
+        };
+        if let Err(e) = app.emit(INDEXING_JOB_FINISHED_EVENT, payload) {
+            eprintln!("[Thumbnails][Rust] Failed to emit job finished event: {e}");
+        }
+    });
 
-    fs::write(target_path, encoded)
-        .map_err(|e| format!("Failed to write thumbnail {}: {e}", target_path.display()))
+    job_id
+}
+
+fn fill_missing_page_thumbnails(app: &AppHandle, base_path: &str, job: &IndexingJob) -> Result<(), String> {
+    let progress = IndexingProgress::new(app, base_path);
+    let base = Path::new(base_path);
+    let comics: Vec<(String, String)> = app
+        .state::<LibraryDatabase>()
+        .comics_missing_page_thumbnails(app)?
+        .into_iter()
+        .filter(|(comic_path, _)| Path::new(comic_path).starts_with(base))
+        .collect();
+    progress.set_comics(0, comics.len());
+    progress.set_phase(IndexingPhase::Indexing);
+
+    for (comic_path, source_type) in &comics {
+        job.checkpoint()?;
+        let _state_guard = lock_index_state(comic_path, Some(job))?;
+        let comic_progress = progress.start_comic(comic_path);
+        match fill_page_thumbnails(app, comic_path, source_type, Some(job), &comic_progress) {
+            Ok(_) => {}
+            Err(message) if job.is_cancelled() => return Err(message),
+            Err(message) => eprintln!("[Thumbnails][Rust] Failed to fill thumbnails of '{}': {}", comic_path, message),
+        }
+    }
+
+    progress.set_phase(IndexingPhase::Finalizing);
+    let thumbnail_settings = app.state::<ThumbnailConfig>().get(app);
+    app.state::<ThumbnailCache>().enforce_budget(app, &thumbnail_settings)?;
+    progress.set_phase(IndexingPhase::Completed);
+    Ok(())
+}
+
+/// Writes every thumbnail tier of the image, decoding it only once.
+/// `target_path` receives the medium tier, which is written last so that its
+/// existence implies that the other tiers exist as well.
+fn write_resized_thumbnail_bytes(
+    bytes: &[u8],
+    target_path: &Path,
+    settings: &ThumbnailSettings,
+) -> Result<(), String> {
+    let image = image::load_from_memory(bytes).map_err(|e| format!("Failed to decode image bytes: {e}"))?;
+    let mut tiers = settings.encode_tiers(&image)?;
+    tiers.sort_by_key(|(tier, _)| *tier == ThumbnailTier::Medium);
+    for (tier, encoded) in tiers {
+        write_file_atomically(&tier.path(target_path), &encoded)?;
+    }
+    Ok(())
+}
+
+/// Writes through a temporary file, so concurrent writers of the same
+/// thumbnail never leave a partially written file behind.
+fn write_file_atomically(target_path: &Path, bytes: &[u8]) -> Result<(), String> {
+    static TEMP_FILE_COUNTER: AtomicU64 = AtomicU64::new(0);
+    let temp_path = target_path.with_extension(format!(
+        "{}-{}.tmp",
+        std::process::id(),
+        TEMP_FILE_COUNTER.fetch_add(1, AtomicOrdering::Relaxed)
+    ));
+    fs::write(&temp_path, bytes)
+        .map_err(|e| format!("Failed to write thumbnail {}: {e}", temp_path.display()))?;
+    fs::rename(&temp_path, target_path).map_err(|e| {
+        let _ = fs::remove_file(&temp_path);
+        format!("Failed to write thumbnail {}: {e}", target_path.display())
+    })
 }
 
 #[tauri::command]
@@ -1042,7 +1489,18 @@ fn scan_comic_candidates(base_path: String) -> Result<ScanResult, String> {
 
 #[tauri::command]
 fn list_image_pages(comic_dir_path: String) -> Result<Vec<ImagePageEntry>, String> {
-    list_image_pages_internal(Path::new(&comic_dir_path))
+    let pages = ImageFolderSource::new(PathBuf::from(comic_dir_path)).list_pages()?;
+    Ok(pages
+        .into_iter()
+        .filter_map(|page| match page.locator {
+            PageLocator::File(file_path) => Some(ImagePageEntry {
+                file_path,
+                file_name: page.file_name,
+                page_number: page.page_number,
+            }),
+            _ => None,
+        })
+        .collect())
 }
 
 #[tauri::command]
@@ -1052,109 +1510,51 @@ fn read_binary_file(path: String) -> Result<Vec<u8>, String> {
 
 #[tauri::command]
 fn count_pdf_pages(path: String) -> Result<i64, String> {
-    let mut cursor = Cursor::new(fs::read(PathBuf::from(path)).map_err(|e| format!("Failed to read pdf: {e}"))?);
-    let doc = Document::load_from(&mut cursor).map_err(|e| format!("Failed to parse pdf document: {e}"))?;
-    Ok(doc.get_pages().len() as i64)
+    PdfSource::new(PathBuf::from(path)).page_count()
 }
 
+/// Reads a PDF page for display: the embedded image of scanned pages as-is,
+/// anything else rasterized at the default reader scale.
 #[tauri::command]
-fn list_archive_image_entries(path: String) -> Result<Vec<String>, String> {
-    let archive_path = PathBuf::from(path.clone());
-    let ext = extension_of(&archive_path).unwrap_or_default();
-
-    if ext == "cbz" {
-        let file = fs::File::open(&archive_path)
-            .map_err(|e| format!("Failed to open CBZ archive {}: {e}", archive_path.display()))?;
-        let mut archive = ZipArchive::new(file)
-            .map_err(|e| format!("Failed to read CBZ archive {}: {e}", archive_path.display()))?;
-
-        let mut names = Vec::new();
-        for idx in 0..archive.len() {
-            let file = archive
-                .by_index(idx)
-                .map_err(|e| format!("Failed to read CBZ entry at index {}: {e}", idx))?;
-            if !file.is_file() {
-                continue;
-            }
-            let name = file.name().replace('\\', "/");
-            if is_image_entry_name(&name) {
-                names.push(name);
-            }
-        }
-        names.sort_by(|a, b| natural_cmp(a, b));
-        return Ok(names);
-    }
-
-    if ext == "cbr" {
-        let mut names = Vec::new();
-        let listed = Archive::new(&path)
-            .open_for_listing()
-            .map_err(|e| format!("Failed to open CBR for listing {}: {e}", archive_path.display()))?;
-
-        for header in listed {
-            let header = header.map_err(|e| format!("Failed reading CBR header {}: {e}", archive_path.display()))?;
-            let name = header.filename.to_string_lossy().replace('\\', "/");
-            if is_image_entry_name(&name) {
-                names.push(name);
-            }
-        }
+async fn read_pdf_page(path: String, page_number: i64) -> Result<Vec<u8>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        PdfSource::new(PathBuf::from(path)).read_page(&PageLocator::PdfPage(page_number))
+    })
+    .await
+    .map_err(|error| format!("Failed to join pdf read task: {error}"))?
+}
 
-        names.sort_by(|a, b| natural_cmp(a, b));
-        return Ok(names);
-    }
+/// Writes edited metadata into the comic's ComicInfo.xml. Only CBZ archives
+/// are writable; other formats are refused without touching the file.
+#[tauri::command]
+async fn write_comic_info(
+    path: String,
+    source_type: String,
+    metadata: ComicInfoUpdate,
+) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        println!("[ComicInfo][Rust] Writing ComicInfo.xml to '{}'", path);
+        open_source(&path, &source_type)?.write_comic_info(&metadata)
+    })
+    .await
+    .map_err(|error| format!("Failed to join ComicInfo.xml write task: {error}"))?
+}
 
-    Err(format!("Unsupported archive extension for file: {}", archive_path.display()))
+#[tauri::command]
+fn list_archive_image_entries(path: String) -> Result<Vec<String>, String> {
+    let pages = open_archive_source(&path)?.list_pages()?;
+    Ok(pages
+        .into_iter()
+        .filter_map(|page| match page.locator {
+            PageLocator::ArchiveEntry(entry_path) => Some(entry_path),
+            _ => None,
+        })
+        .collect())
 }
 
 #[tauri::command]
 fn read_archive_image_entry(path: String, entry_path: String) -> Result<Vec<u8>, String> {
-    let archive_path = PathBuf::from(path.clone());
-    let ext = extension_of(&archive_path).unwrap_or_default();
-
-    if ext == "cbz" {
-        let file = fs::File::open(&archive_path)
-            .map_err(|e| format!("Failed to open CBZ archive {}: {e}", archive_path.display()))?;
-        let mut archive = ZipArchive::new(file)
-            .map_err(|e| format!("Failed to read CBZ archive {}: {e}", archive_path.display()))?;
-        let mut file = archive
-            .by_name(&entry_path)
-            .map_err(|e| format!("Failed to find CBZ entry {}: {e}", entry_path))?;
-        let mut bytes = Vec::new();
-        file.read_to_end(&mut bytes)
-            .map_err(|e| format!("Failed to read CBZ entry {}: {e}", entry_path))?;
-        return Ok(bytes);
-    }
-
-    if ext == "cbr" {
-        let mut archive = Archive::new(&path)
-            .open_for_processing()
-            .map_err(|e| format!("Failed to open CBR for processing {}: {e}", archive_path.display()))?;
-
-        loop {
-            let Some(before_file) = archive
-                .read_header()
-                .map_err(|e| format!("Failed reading CBR header {}: {e}", archive_path.display()))?
-            else {
-                break;
-            };
-
-            let current_name = before_file.entry().filename.to_string_lossy().replace('\\', "/");
-            if current_name == entry_path {
-                let (data, _after_read) = before_file
-                    .read()
-                    .map_err(|e| format!("Failed reading CBR entry {}: {e}", entry_path))?;
-                return Ok(data);
-            }
-
-            archive = before_file
-                .skip()
-                .map_err(|e| format!("Failed skipping CBR entry while searching {}: {e}", entry_path))?;
-        }
-
-        return Err(format!("CBR entry not found: {}", entry_path));
-    }
-
-    Err(format!("Unsupported archive extension for file: {}", archive_path.display()))
+    open_archive_source(&path)?.read_page(&PageLocator::ArchiveEntry(entry_path))
 }
 
 #[tauri::command]
@@ -1163,69 +1563,42 @@ fn read_archive_image_entries_batch(path: String, entry_paths: Vec<String>) -> R
         return Ok(Vec::new());
     }
 
-    let archive_path = PathBuf::from(path.clone());
-    let ext = extension_of(&archive_path).unwrap_or_default();
-
-    if ext == "cbz" {
-        let file = fs::File::open(&archive_path)
-            .map_err(|e| format!("Failed to open CBZ archive {}: {e}", archive_path.display()))?;
-        let mut archive = ZipArchive::new(file)
-            .map_err(|e| format!("Failed to read CBZ archive {}: {e}", archive_path.display()))?;
-
-        let mut output = Vec::with_capacity(entry_paths.len());
-        for entry_path in &entry_paths {
-            let mut file = archive
-                .by_name(entry_path)
-                .map_err(|e| format!("Failed to find CBZ entry {}: {e}", entry_path))?;
-            let mut bytes = Vec::new();
-            file.read_to_end(&mut bytes)
-                .map_err(|e| format!("Failed to read CBZ entry {}: {e}", entry_path))?;
-            output.push(bytes);
-        }
-        return Ok(output);
-    }
-
-    if ext == "cbr" {
-        let requested: HashSet<String> = entry_paths.iter().cloned().collect();
-        let mut found: HashMap<String, Vec<u8>> = HashMap::new();
-
-        let mut archive = Archive::new(&path)
-            .open_for_processing()
-            .map_err(|e| format!("Failed to open CBR for processing {}: {e}", archive_path.display()))?;
-
-        loop {
-            let Some(before_file) = archive
-                .read_header()
-                .map_err(|e| format!("Failed reading CBR header {}: {e}", archive_path.display()))?
-            else {
-                break;
-            };
-
-            let current_name = before_file.entry().filename.to_string_lossy().replace('\\', "/");
-            if requested.contains(&current_name) {
-                let (data, next_archive) = before_file
-                    .read()
-                    .map_err(|e| format!("Failed reading CBR entry {}: {e}", current_name))?;
-                found.insert(current_name, data);
-                archive = next_archive;
-            } else {
-                archive = before_file
-                    .skip()
-                    .map_err(|e| format!("Failed skipping CBR entry while batch reading: {e}"))?;
-            }
-        }
-
-        let mut output = Vec::with_capacity(entry_paths.len());
-        for entry_path in &entry_paths {
-            let data = found
-                .remove(entry_path)
-                .ok_or_else(|| format!("CBR entry not found: {}", entry_path))?;
-            output.push(data);
-        }
-        return Ok(output);
-    }
+    let locators: Vec<PageLocator> = entry_paths.into_iter().map(PageLocator::ArchiveEntry).collect();
+    open_archive_source(&path)?.read_pages(&locators)
+}
 
-    Err(format!("Unsupported archive extension for file: {}", archive_path.display()))
+/// Reads the pages around `page_number` into the page cache in the
+/// background, so the `comic://` protocol serves them from memory. The
+/// window defaults to what `view_mode` usually shows next; `width`, `height`
+/// and `crop` prefetch pages processed as they will be requested.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+fn prefetch_pages(
+    app: AppHandle,
+    cache: State<'_, PageCache>,
+    comic_path: String,
+    page_number: i64,
+    view_mode: ReaderViewMode,
+    ahead: Option<usize>,
+    behind: Option<usize>,
+    width: Option<u32>,
+    height: Option<u32>,
+    crop: Option<BorderCrop>,
+) {
+    let (default_ahead, default_behind) = view_mode.default_window();
+    let variant = PageVariant {
+        max_width: width.filter(|width| *width > 0),
+        max_height: height.filter(|height| *height > 0),
+        crop,
+    };
+    cache.prefetch(
+        &app,
+        comic_path,
+        page_number,
+        ahead.unwrap_or(default_ahead),
+        behind.unwrap_or(default_behind),
+        variant,
+    );
 }
 
 #[tauri::command]
@@ -1254,15 +1627,52 @@ fn generate_thumbnail_from_bytes(
             .map_err(|e| format!("Failed to create comic thumbnail dir {}: {e}", comic_dir.display()))?;
     }
 
-    let thumb_path = comic_dir.join(format!("{}.jpg", page_number));
+    let settings = app.state::<ThumbnailConfig>().get(&app);
+    let thumb_path = comic_dir.join(format!(
+        "{}-{}.{}",
+        page_number,
+        settings.cache_tag(),
+        settings.format.extension()
+    ));
+    let thumbnail_cache = app.state::<ThumbnailCache>();
     if thumb_path.exists() {
+        thumbnail_cache.record_hit(&thumb_path);
         return Ok(to_forward_slash_path(&thumb_path));
     }
 
-    write_resized_thumbnail_bytes(&image_bytes, &thumb_path)?;
+    thumbnail_cache.record_miss();
+    write_resized_thumbnail_bytes(&image_bytes, &thumb_path, &settings)?;
     Ok(to_forward_slash_path(&thumb_path))
 }
 
+#[tauri::command]
+fn get_thumbnail_settings(app: AppHandle, config: State<'_, ThumbnailConfig>) -> ThumbnailSettings {
+    config.get(&app)
+}
+
+/// Thumbnails made with the previous settings stay valid until the comics
+/// using them are indexed again, which then only regenerates those.
+#[tauri::command]
+async fn set_thumbnail_settings(app: AppHandle, settings: ThumbnailSettings) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        app.state::<ThumbnailConfig>().set(&app, settings)?;
+        app.state::<ThumbnailCache>().enforce_budget(&app, &settings)
+    })
+    .await
+    .map_err(|error| format!("Failed to join thumbnail task: {error}"))?
+}
+
+/// Walks the thumbnail cache, so it runs off the main thread.
+#[tauri::command]
+async fn get_thumbnail_cache_stats(app: AppHandle) -> Result<ThumbnailCacheStats, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let settings = app.state::<ThumbnailConfig>().get(&app);
+        app.state::<ThumbnailCache>().stats(&app, &settings)
+    })
+    .await
+    .map_err(|error| format!("Failed to join thumbnail stats task: {error}"))?
+}
+
 #[tauri::command]
 fn delete_thumbnails_for_comic(app: AppHandle, comic_id: i64) -> Result<(), String> {
     let thumb_root = ensure_thumb_dir(&app)?;
@@ -1296,12 +1706,22 @@ fn cleanup_orphan_thumbnails(app: AppHandle, active_comic_ids: Vec<i64>) -> Resu
 }
 
 #[tauri::command]
-fn cleanup_indexed_thumbnails(app: AppHandle, active_comic_paths: Vec<String>) -> Result<(), String> {
-    let indexed_root = ensure_indexed_thumb_root(&app)?;
+async fn cleanup_indexed_thumbnails(app: AppHandle, active_comic_paths: Vec<String>) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || cleanup_indexed_thumbnails_impl(&app, &active_comic_paths))
+        .await
+        .map_err(|error| format!("Failed to join thumbnail task: {error}"))?
+}
+
+fn cleanup_indexed_thumbnails_impl(app: &AppHandle, active_comic_paths: &[String]) -> Result<(), String> {
+    let indexed_root = ensure_indexed_thumb_root(app)?;
     if !indexed_root.exists() {
         return Ok(());
     }
 
+    cleanup_unreferenced_page_thumbnails(app, &indexed_root.join(INDEXED_PAGES_DIR))?;
+
+    // Folders of the per-comic layout used before page thumbnails were
+    // stored by content.
     let active_hashes: HashSet<String> = active_comic_paths.iter().map(|path| hash_path(path)).collect();
     let entries = fs::read_dir(&indexed_root)
         .map_err(|e| format!("Failed to read indexed thumbnail root {}: {e}", indexed_root.display()))?;
@@ -1318,7 +1738,7 @@ fn cleanup_indexed_thumbnails(app: AppHandle, active_comic_paths: Vec<String>) -
             .unwrap_or_default()
             .to_string();
 
-        if !active_hashes.contains(&folder) {
+        if folder != INDEXED_PAGES_DIR && !active_hashes.contains(&folder) {
             fs::remove_dir_all(&path).map_err(|e| {
                 format!(
                     "Failed to remove orphan indexed thumbnail dir {}: {e}",
@@ -1331,6 +1751,41 @@ fn cleanup_indexed_thumbnails(app: AppHandle, active_comic_paths: Vec<String>) -
     Ok(())
 }
 
+/// Page thumbnails can be shared between comics, so they are removed once no
+/// page in the database refers to them anymore.
+fn cleanup_unreferenced_page_thumbnails(app: &AppHandle, pages_dir: &Path) -> Result<(), String> {
+    let Ok(shards) = fs::read_dir(pages_dir) else {
+        return Ok(());
+    };
+    let referenced: HashSet<String> = app
+        .state::<LibraryDatabase>()
+        .thumbnail_paths(app)?
+        .into_iter()
+        .map(|path| normalize_path_string(&path))
+        .collect();
+    let in_flight_since = in_flight_since();
+
+    let mut removed = 0;
+    for shard in shards.flatten() {
+        let Ok(thumbnails) = fs::read_dir(shard.path()) else {
+            continue;
+        };
+        for thumbnail in thumbnails.flatten() {
+            let path = thumbnail.path();
+            let modified = thumbnail.metadata().and_then(|metadata| metadata.modified()).ok();
+            if !referenced.contains(&to_forward_slash_path(&ThumbnailTier::medium_path(&path)))
+                && !is_in_flight(modified, in_flight_since)
+            {
+                fs::remove_file(&path)
+                    .map_err(|e| format!("Failed to remove unused thumbnail {}: {e}", path.display()))?;
+                removed += 1;
+            }
+        }
+    }
+    println!("[Indexing][Rust][Thumbnail] Removed {} unused page thumbnails", removed);
+    Ok(())
+}
+
 fn get_migrations() -> Vec<Migration> {
     vec![
         Migration {
@@ -1480,6 +1935,33 @@ fn get_migrations() -> Vec<Migration> {
             ",
             kind: MigrationKind::Up,
         },
+        Migration {
+            version: 11,
+            description: "add_comic_info_metadata",
+            sql: "
+                ALTER TABLE comics ADD COLUMN volume INTEGER;
+                ALTER TABLE comics ADD COLUMN writer TEXT;
+                ALTER TABLE comics ADD COLUMN penciller TEXT;
+                ALTER TABLE comics ADD COLUMN publisher TEXT;
+                ALTER TABLE comics ADD COLUMN year INTEGER;
+                ALTER TABLE comics ADD COLUMN summary TEXT;
+                ALTER TABLE comics ADD COLUMN genre TEXT;
+                ALTER TABLE comics ADD COLUMN manga INTEGER;
+                ALTER TABLE comic_pages ADD COLUMN page_type TEXT;
+                ALTER TABLE index_paths ADD COLUMN metadata_precedence TEXT NOT NULL DEFAULT 'comicInfo';
+            ",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 12,
+            description: "index_page_thumbnail_paths",
+            sql: "
+                UPDATE comic_pages SET thumbnail_path = replace(thumbnail_path, '\\', '/')
+                WHERE instr(thumbnail_path, '\\') > 0;
+                CREATE INDEX IF NOT EXISTS idx_comic_pages_thumbnail_path ON comic_pages(thumbnail_path);
+            ",
+            kind: MigrationKind::Up,
+        },
     ]
 }
 
@@ -1496,25 +1978,48 @@ pub fn run() {
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_store::Builder::default().build())
+        .manage(LibraryWatchers::default())
+        .manage(IndexingJobs::default())
+        .manage(LibraryDatabase::default())
+        .manage(ThumbnailConfig::default())
+        .manage(ThumbnailCache::default())
+        .manage(PageCache::default())
+        // Pages are read and decoded off the main thread.
+        .register_asynchronous_uri_scheme_protocol(COMIC_PROTOCOL, |ctx, request, responder| {
+            let app = ctx.app_handle().clone();
+            tauri::async_runtime::spawn_blocking(move || responder.respond(protocol::handle_request(&app, &request)));
+        })
         .invoke_handler(tauri::generate_handler![
             scan_comic_candidates,
-            build_index_payload_for_path,
-            get_comic_pages,
+            start_indexing_job,
+            cancel_indexing_job,
+            pause_indexing_job,
+            resume_indexing_job,
+            watch_index_paths,
+            unwatch_index_paths,
+            ensure_page_thumbnails,
+            start_thumbnail_fill_job,
             list_image_pages,
             read_binary_file,
             count_pdf_pages,
+            read_pdf_page,
+            write_comic_info,
             list_archive_image_entries,
             read_archive_image_entry,
             read_archive_image_entries_batch,
+            prefetch_pages,
             generate_thumbnail_from_path,
             generate_thumbnail_from_bytes,
+            get_thumbnail_settings,
+            set_thumbnail_settings,
+            get_thumbnail_cache_stats,
             delete_thumbnails_for_comic,
             cleanup_orphan_thumbnails,
             cleanup_indexed_thumbnails,
         ])
         .plugin(
             SqlBuilder::default()
-                .add_migrations("sqlite:comic-shelf.db", get_migrations())
+                .add_migrations(DATABASE_URL, get_migrations())
                 .build(),
         )
         .run(tauri::generate_context!())
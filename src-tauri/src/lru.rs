@@ -0,0 +1,206 @@
+//! Least recently used cache with a weight budget, shared by the in-memory
+//! page caches. Lookups, inserts and evictions are O(1): entries live in a
+//! slab and are linked from the most to the least recently used one.
+
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+struct Node<K, V> {
+    key: K,
+    value: V,
+    weight: usize,
+    /// Next more recently used entry.
+    newer: Option<usize>,
+    /// Next less recently used entry.
+    older: Option<usize>,
+}
+
+pub struct LruCache<K, V> {
+    budget: usize,
+    weight: usize,
+    index: HashMap<K, usize>,
+    nodes: Vec<Option<Node<K, V>>>,
+    free: Vec<usize>,
+    newest: Option<usize>,
+    oldest: Option<usize>,
+}
+
+impl<K: Hash + Eq + Clone, V> LruCache<K, V> {
+    /// Cache that evicts entries once their total weight exceeds `budget`.
+    pub fn new(budget: usize) -> Self {
+        Self {
+            budget,
+            weight: 0,
+            index: HashMap::new(),
+            nodes: Vec::new(),
+            free: Vec::new(),
+            newest: None,
+            oldest: None,
+        }
+    }
+
+    pub fn budget(&self) -> usize {
+        self.budget
+    }
+
+    /// Marks the entry as most recently used and returns it.
+    pub fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let slot = *self.index.get(key)?;
+        self.unlink(slot);
+        self.push_newest(slot);
+        self.node(slot).map(|node| &node.value)
+    }
+
+    /// Inserts or replaces the entry for `key` as the most recently used one,
+    /// then evicts the least recently used entries to stay within the budget.
+    /// Entries heavier than the whole budget are not cached.
+    pub fn insert(&mut self, key: K, value: V, weight: usize) {
+        self.remove(&key);
+        if weight > self.budget {
+            return;
+        }
+        let node = Node {
+            key: key.clone(),
+            value,
+            weight,
+            newer: None,
+            older: None,
+        };
+        let slot = match self.free.pop() {
+            Some(slot) => {
+                self.nodes[slot] = Some(node);
+                slot
+            }
+            None => {
+                self.nodes.push(Some(node));
+                self.nodes.len() - 1
+            }
+        };
+        self.index.insert(key, slot);
+        self.weight += weight;
+        self.push_newest(slot);
+
+        while self.weight > self.budget {
+            let Some(oldest) = self.oldest else {
+                break;
+            };
+            self.remove_slot(oldest);
+        }
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let slot = *self.index.get(key)?;
+        self.remove_slot(slot)
+    }
+
+    fn remove_slot(&mut self, slot: usize) -> Option<V> {
+        self.unlink(slot);
+        let node = self.nodes[slot].take()?;
+        self.free.push(slot);
+        self.index.remove(&node.key);
+        self.weight -= node.weight;
+        Some(node.value)
+    }
+
+    fn node(&self, slot: usize) -> Option<&Node<K, V>> {
+        self.nodes.get(slot).and_then(Option::as_ref)
+    }
+
+    fn node_mut(&mut self, slot: usize) -> Option<&mut Node<K, V>> {
+        self.nodes.get_mut(slot).and_then(Option::as_mut)
+    }
+
+    fn unlink(&mut self, slot: usize) {
+        let Some((newer, older)) = self.node(slot).map(|node| (node.newer, node.older)) else {
+            return;
+        };
+        match newer {
+            Some(newer) => self.node_mut(newer).into_iter().for_each(|node| node.older = older),
+            None => self.newest = older,
+        }
+        match older {
+            Some(older) => self.node_mut(older).into_iter().for_each(|node| node.newer = newer),
+            None => self.oldest = newer,
+        }
+        if let Some(node) = self.node_mut(slot) {
+            node.newer = None;
+            node.older = None;
+        }
+    }
+
+    fn push_newest(&mut self, slot: usize) {
+        let previous = self.newest;
+        if let Some(node) = self.node_mut(slot) {
+            node.older = previous;
+        }
+        match previous {
+            Some(previous) => self.node_mut(previous).into_iter().for_each(|node| node.newer = Some(slot)),
+            None => self.oldest = Some(slot),
+        }
+        self.newest = Some(slot);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evicts_least_recently_used_entries_over_budget() {
+        let mut cache = LruCache::new(10);
+        cache.insert("a", 1, 4);
+        cache.insert("b", 2, 4);
+        assert_eq!(cache.get("a"), Some(&1));
+
+        cache.insert("c", 3, 4);
+
+        assert_eq!(cache.get("b"), None);
+        assert_eq!(cache.get("a"), Some(&1));
+        assert_eq!(cache.get("c"), Some(&3));
+    }
+
+    #[test]
+    fn replacing_an_entry_updates_its_weight() {
+        let mut cache = LruCache::new(10);
+        cache.insert("a", 1, 8);
+        cache.insert("a", 2, 2);
+        cache.insert("b", 3, 8);
+
+        assert_eq!(cache.get("a"), Some(&2));
+        assert_eq!(cache.get("b"), Some(&3));
+        assert_eq!(cache.remove("a"), Some(2));
+        assert_eq!(cache.get("a"), None);
+    }
+
+    #[test]
+    fn entries_heavier_than_the_budget_are_not_cached() {
+        let mut cache = LruCache::new(10);
+        cache.insert("a", 1, 4);
+        cache.insert("b", 2, 11);
+
+        assert_eq!(cache.get("b"), None);
+        assert_eq!(cache.get("a"), Some(&1));
+    }
+
+    #[test]
+    fn freed_slots_are_reused() {
+        let mut cache = LruCache::new(2);
+        for value in 0..100 {
+            cache.insert(value, value, 1);
+        }
+
+        assert_eq!(cache.nodes.len(), 3);
+        assert_eq!(cache.get(&98), Some(&98));
+        assert_eq!(cache.get(&99), Some(&99));
+        assert_eq!(cache.get(&97), None);
+    }
+}
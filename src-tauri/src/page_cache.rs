@@ -0,0 +1,329 @@
+//! Memory cache of the page images served by the `comic://` protocol. While a
+//! comic is read, the pages around the current one are read ahead on
+//! background threads, so turning a page is answered from memory even on slow
+//! network drives and for archives that can only be decompressed in order.
+//!
+//! Pages can be requested downscaled to the size they are displayed at and
+//! with their borders cropped; each variant of a page is cached separately.
+//! The content bounds of a page are detected once and shared by all of its
+//! cropped variants.
+//!
+//! Cached pages and listings are tied to the size and modification time of
+//! the comic; once it changes they are no longer found and age out.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use serde::Deserialize;
+use tauri::{AppHandle, Manager};
+
+use crate::lru::LruCache;
+use crate::page_images::{decode_page, detect_content_bounds, process_page, BorderCrop, ContentBounds};
+use crate::sources::{open_source_for_path, ComicSource, FileStamp, SourcePage, DECOMPRESSED_PAGES_BUDGET};
+
+/// Memory for page images, shared with the pages that RAR archives keep
+/// decompressed while they are read.
+const PAGE_MEMORY_BUDGET: usize = 384 * 1024 * 1024;
+const PAGE_CACHE_BUDGET: usize = PAGE_MEMORY_BUDGET - DECOMPRESSED_PAGES_BUDGET;
+/// One prefetch fills at most this fraction of the budget, so reading ahead
+/// never evicts the page that is being displayed.
+const PREFETCH_BUDGET_DIVISOR: usize = 2;
+const MAX_LISTINGS: usize = 32;
+const MAX_CONTENT_BOUNDS: usize = 4096;
+
+/// Reader view mode, as stored on the tab.
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ReaderViewMode {
+    Overview,
+    Single,
+    Scroll,
+}
+
+impl ReaderViewMode {
+    /// Pages read ahead of and behind the current page when not specified.
+    pub fn default_window(self) -> (usize, usize) {
+        match self {
+            // The overview only shows thumbnails.
+            Self::Overview => (0, 0),
+            Self::Single => (3, 1),
+            Self::Scroll => (5, 2),
+        }
+    }
+}
+
+/// Processing applied to a page before it is served. The default variant is
+/// the page as stored in the comic.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct PageVariant {
+    /// Bounds the page is downscaled to fit, in pixels. Pages are never
+    /// upscaled.
+    pub max_width: Option<u32>,
+    pub max_height: Option<u32>,
+    /// Crops uniform borders before downscaling.
+    pub crop: Option<BorderCrop>,
+}
+
+impl PageVariant {
+    pub fn is_original(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Distinguishes the variant in cache keys; empty for the original page.
+    pub fn cache_tag(&self) -> String {
+        if self.is_original() {
+            return String::new();
+        }
+        let bound = |value: Option<u32>| value.map_or_else(|| "-".to_string(), |value| value.to_string());
+        let crop = self.crop.map_or_else(String::new, |crop| {
+            format!("|crop{}-{}", crop.tolerance, crop.min_content_percent)
+        });
+        format!("|{}x{}{}", bound(self.max_width), bound(self.max_height), crop)
+    }
+}
+
+/// A comic is identified by its path and the version it was read from.
+type ComicKey = (String, FileStamp);
+type PageKey = (ComicKey, i64, PageVariant);
+type BoundsKey = (ComicKey, i64, BorderCrop);
+
+/// Page listings and page bytes within a memory budget, least recently used
+/// first.
+pub struct PageCache {
+    listings: Mutex<LruCache<ComicKey, Arc<Vec<SourcePage>>>>,
+    pages: Mutex<LruCache<PageKey, Arc<Vec<u8>>>>,
+    content_bounds: Mutex<LruCache<BoundsKey, Option<ContentBounds>>>,
+    /// Latest prefetch per comic; older prefetches of the same comic stop
+    /// once a newer one was requested.
+    prefetch_generations: Mutex<HashMap<String, u64>>,
+}
+
+impl Default for PageCache {
+    fn default() -> Self {
+        Self::new(PAGE_CACHE_BUDGET)
+    }
+}
+
+impl PageCache {
+    pub fn new(budget: usize) -> Self {
+        Self {
+            listings: Mutex::new(LruCache::new(MAX_LISTINGS)),
+            pages: Mutex::new(LruCache::new(budget)),
+            content_bounds: Mutex::new(LruCache::new(MAX_CONTENT_BOUNDS)),
+            prefetch_generations: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Pages of the comic at `path`, listed once per version of the comic.
+    pub fn list_pages(&self, path: &str) -> Result<Arc<Vec<SourcePage>>, String> {
+        let stamp = FileStamp::of(Path::new(path))?;
+        if let Some(pages) = self.cached_listing(path, stamp) {
+            return Ok(pages);
+        }
+        let source = open_source_for_path(path)?;
+        self.list_pages_with(source.as_ref(), path, stamp)
+    }
+
+    /// Bytes of `page` of the comic at `path`, processed as `variant` asks.
+    pub fn read_page(&self, path: &str, page: &SourcePage, variant: PageVariant) -> Result<Arc<Vec<u8>>, String> {
+        let stamp = FileStamp::of(Path::new(path))?;
+        if let Some(bytes) = self.cached_page(path, stamp, page.page_number, variant) {
+            return Ok(bytes);
+        }
+        let source = open_source_for_path(path)?;
+        self.read_page_with(source.as_ref(), path, stamp, page, variant)
+    }
+
+    /// Reads the pages around `current_page` of the comic at `path` into the
+    /// cache on a background thread, nearest pages first.
+    pub fn prefetch(
+        &self,
+        app: &AppHandle,
+        path: String,
+        current_page: i64,
+        ahead: usize,
+        behind: usize,
+        variant: PageVariant,
+    ) {
+        if ahead == 0 && behind == 0 {
+            return;
+        }
+        let generation = {
+            let mut generations = lock(&self.prefetch_generations);
+            let generation = generations.entry(path.clone()).or_default();
+            *generation += 1;
+            *generation
+        };
+        let app = app.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            let cache = app.state::<PageCache>();
+            if let Err(e) = cache.prefetch_window(&path, current_page, ahead, behind, variant, generation) {
+                eprintln!("[PageCache][Rust] Failed to prefetch pages of {path}: {e}");
+            }
+            let mut generations = lock(&cache.prefetch_generations);
+            if generations.get(&path) == Some(&generation) {
+                generations.remove(&path);
+            }
+        });
+    }
+
+    fn prefetch_window(
+        &self,
+        path: &str,
+        current_page: i64,
+        ahead: usize,
+        behind: usize,
+        variant: PageVariant,
+        generation: u64,
+    ) -> Result<(), String> {
+        let stamp = FileStamp::of(Path::new(path))?;
+        let source = open_source_for_path(path)?;
+        let pages = match self.cached_listing(path, stamp) {
+            Some(pages) => pages,
+            None => self.list_pages_with(source.as_ref(), path, stamp)?,
+        };
+        let Some(current) = pages.iter().position(|page| page.page_number == current_page) else {
+            return Ok(());
+        };
+
+        // Alternate between the directions, so the previous page is ready
+        // before pages far ahead.
+        let mut window = Vec::with_capacity(ahead + behind);
+        for distance in 1..=ahead.max(behind) {
+            if distance <= ahead && current + distance < pages.len() {
+                window.push(current + distance);
+            }
+            if distance <= behind && distance <= current {
+                window.push(current - distance);
+            }
+        }
+
+        let mut prefetched_bytes = 0;
+        for index in window {
+            if !self.is_current_prefetch(path, generation) {
+                break;
+            }
+            let page = &pages[index];
+            if self.cached_page(path, stamp, page.page_number, variant).is_some() {
+                continue;
+            }
+            prefetched_bytes += self.read_page_with(source.as_ref(), path, stamp, page, variant)?.len();
+            if prefetched_bytes > lock(&self.pages).budget() / PREFETCH_BUDGET_DIVISOR {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    fn is_current_prefetch(&self, path: &str, generation: u64) -> bool {
+        lock(&self.prefetch_generations).get(path) == Some(&generation)
+    }
+
+    fn cached_listing(&self, path: &str, stamp: FileStamp) -> Option<Arc<Vec<SourcePage>>> {
+        lock(&self.listings).get(&(path.to_string(), stamp)).cloned()
+    }
+
+    fn list_pages_with(
+        &self,
+        source: &dyn ComicSource,
+        path: &str,
+        stamp: FileStamp,
+    ) -> Result<Arc<Vec<SourcePage>>, String> {
+        let pages = Arc::new(source.list_pages()?);
+        lock(&self.listings).insert((path.to_string(), stamp), Arc::clone(&pages), 1);
+        Ok(pages)
+    }
+
+    fn cached_page(
+        &self,
+        path: &str,
+        stamp: FileStamp,
+        page_number: i64,
+        variant: PageVariant,
+    ) -> Option<Arc<Vec<u8>>> {
+        lock(&self.pages)
+            .get(&((path.to_string(), stamp), page_number, variant))
+            .cloned()
+    }
+
+    fn read_page_with(
+        &self,
+        source: &dyn ComicSource,
+        path: &str,
+        stamp: FileStamp,
+        page: &SourcePage,
+        variant: PageVariant,
+    ) -> Result<Arc<Vec<u8>>, String> {
+        if variant.is_original() {
+            let bytes = Arc::new(source.read_page(&page.locator)?);
+            self.insert(path, stamp, page.page_number, variant, &bytes);
+            return Ok(bytes);
+        }
+
+        // Only the processed variant is kept, unless the page is served
+        // unchanged because there is nothing to crop and it fits already.
+        let original = match self.cached_page(path, stamp, page.page_number, PageVariant::default()) {
+            Some(bytes) => bytes,
+            None => Arc::new(source.read_page(&page.locator)?),
+        };
+        let mut decoded = None;
+        let bounds = match variant.crop {
+            None => None,
+            Some(crop) => match self.cached_bounds(path, stamp, page.page_number, crop) {
+                Some(bounds) => bounds,
+                None => {
+                    let image = decode_page(&original)?;
+                    let bounds = detect_content_bounds(&image, crop);
+                    self.insert_bounds(path, stamp, page.page_number, crop, bounds);
+                    decoded = Some(image);
+                    bounds
+                }
+            },
+        };
+        let Some(processed) = process_page(&original, decoded, bounds, variant.max_width, variant.max_height)? else {
+            self.insert(path, stamp, page.page_number, PageVariant::default(), &original);
+            return Ok(original);
+        };
+        let bytes = Arc::new(processed);
+        self.insert(path, stamp, page.page_number, variant, &bytes);
+        Ok(bytes)
+    }
+
+    /// Content bounds detected before, `None` when the page was not analysed
+    /// with `crop` yet.
+    fn cached_bounds(
+        &self,
+        path: &str,
+        stamp: FileStamp,
+        page_number: i64,
+        crop: BorderCrop,
+    ) -> Option<Option<ContentBounds>> {
+        lock(&self.content_bounds)
+            .get(&((path.to_string(), stamp), page_number, crop))
+            .copied()
+    }
+
+    fn insert_bounds(
+        &self,
+        path: &str,
+        stamp: FileStamp,
+        page_number: i64,
+        crop: BorderCrop,
+        bounds: Option<ContentBounds>,
+    ) {
+        lock(&self.content_bounds).insert(((path.to_string(), stamp), page_number, crop), bounds, 1);
+    }
+
+    fn insert(&self, path: &str, stamp: FileStamp, page_number: i64, variant: PageVariant, bytes: &Arc<Vec<u8>>) {
+        lock(&self.pages).insert(
+            ((path.to_string(), stamp), page_number, variant),
+            Arc::clone(bytes),
+            bytes.len(),
+        );
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
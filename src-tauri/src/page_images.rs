@@ -0,0 +1,252 @@
+//! Processing of page images before they are served, built on the same
+//! decoding and resizing pipeline as thumbnails.
+
+use std::io::Cursor;
+
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::PngEncoder;
+use image::{DynamicImage, GenericImageView, ImageReader};
+use serde::Deserialize;
+
+use crate::thumbnail_settings::fit_within;
+
+/// Resized pages are displayed at full size, so they are encoded at a higher
+/// quality than thumbnails.
+const PAGE_JPEG_QUALITY: u8 = 90;
+/// Share of a line's pixels that may differ from the border, so scanner dust
+/// does not end a border.
+const BORDER_NOISE_RATIO: f32 = 0.005;
+
+/// Parameters of the automatic border crop.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BorderCrop {
+    /// Largest brightness difference, 0-255, to the border that still counts
+    /// as border.
+    pub tolerance: u8,
+    /// Pages are not cropped below this share of their width or height, in
+    /// percent, so blank and mostly empty pages stay intact.
+    pub min_content_percent: u8,
+}
+
+impl Default for BorderCrop {
+    fn default() -> Self {
+        Self {
+            tolerance: 24,
+            min_content_percent: 50,
+        }
+    }
+}
+
+/// Region of a page that is left after cropping its borders, in pixels.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ContentBounds {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+pub fn decode_page(bytes: &[u8]) -> Result<DynamicImage, String> {
+    image::load_from_memory(bytes).map_err(|e| format!("Failed to decode image bytes: {e}"))
+}
+
+/// Finds uniform borders along the edges of `image`. Each edge is compared to
+/// its own outermost line, so white and black borders are both detected.
+/// Returns `None` when there is nothing to crop or the content would end up
+/// smaller than `crop` allows.
+pub fn detect_content_bounds(image: &DynamicImage, crop: BorderCrop) -> Option<ContentBounds> {
+    let luma = image.to_luma8();
+    let (width, height) = luma.dimensions();
+    if width == 0 || height == 0 {
+        return None;
+    }
+    let pixel = |x: u32, y: u32| luma.get_pixel(x, y).0[0];
+    let row = |y: u32, from: u32, to: u32| (from..=to).map(move |x| pixel(x, y));
+    let column = |x: u32, from: u32, to: u32| (from..=to).map(move |y| pixel(x, y));
+
+    let top_reference = median(row(0, 0, width - 1));
+    let top = (0..height).find(|&y| !is_border_line(row(y, 0, width - 1), top_reference, crop.tolerance))?;
+    let bottom_reference = median(row(height - 1, 0, width - 1));
+    let bottom = (top..height)
+        .rev()
+        .find(|&y| !is_border_line(row(y, 0, width - 1), bottom_reference, crop.tolerance))?;
+    let left_reference = median(column(0, top, bottom));
+    let left = (0..width).find(|&x| !is_border_line(column(x, top, bottom), left_reference, crop.tolerance))?;
+    let right_reference = median(column(width - 1, top, bottom));
+    let right = (left..width)
+        .rev()
+        .find(|&x| !is_border_line(column(x, top, bottom), right_reference, crop.tolerance))?;
+
+    let bounds = ContentBounds {
+        x: left,
+        y: top,
+        width: right - left + 1,
+        height: bottom - top + 1,
+    };
+    let min_content = u64::from(crop.min_content_percent.min(100));
+    let too_small = u64::from(bounds.width) * 100 < u64::from(width) * min_content
+        || u64::from(bounds.height) * 100 < u64::from(height) * min_content;
+    if too_small || (bounds.width == width && bounds.height == height) {
+        return None;
+    }
+    Some(bounds)
+}
+
+/// Crops the encoded page `bytes` to `bounds` and downscales it to fit within
+/// the given size. `decoded` is the page if it was decoded already. Returns
+/// `None` when the page is served unchanged.
+pub fn process_page(
+    bytes: &[u8],
+    decoded: Option<DynamicImage>,
+    bounds: Option<ContentBounds>,
+    max_width: Option<u32>,
+    max_height: Option<u32>,
+) -> Result<Option<Vec<u8>>, String> {
+    let (width, height) = match (bounds, &decoded) {
+        (Some(bounds), _) => (bounds.width, bounds.height),
+        (None, Some(image)) => image.dimensions(),
+        // Reading the header is enough to skip pages that are small already.
+        (None, None) => ImageReader::new(Cursor::new(bytes))
+            .with_guessed_format()
+            .map_err(|e| format!("Failed to read page image: {e}"))?
+            .into_dimensions()
+            .map_err(|e| format!("Failed to read page dimensions: {e}"))?,
+    };
+    let max_width = max_width.unwrap_or(width);
+    let max_height = max_height.unwrap_or(height);
+    if bounds.is_none() && width <= max_width && height <= max_height {
+        return Ok(None);
+    }
+
+    let image = match decoded {
+        Some(image) => image,
+        None => decode_page(bytes)?,
+    };
+    let image = match bounds {
+        Some(bounds) => image.crop_imm(bounds.x, bounds.y, bounds.width, bounds.height),
+        None => image,
+    };
+    encode_page(&fit_within(&image, max_width, max_height)).map(Some)
+}
+
+fn median(values: impl Iterator<Item = u8>) -> u8 {
+    let mut values: Vec<u8> = values.collect();
+    values.sort_unstable();
+    values.get(values.len() / 2).copied().unwrap_or_default()
+}
+
+fn is_border_line(values: impl Iterator<Item = u8>, reference: u8, tolerance: u8) -> bool {
+    let (mut total, mut differing) = (0usize, 0usize);
+    for value in values {
+        total += 1;
+        if value.abs_diff(reference) > tolerance {
+            differing += 1;
+        }
+    }
+    differing as f32 <= total as f32 * BORDER_NOISE_RATIO
+}
+
+/// Encodes a processed page as JPEG, or as PNG when it has transparency.
+fn encode_page(image: &DynamicImage) -> Result<Vec<u8>, String> {
+    let mut encoded = Vec::new();
+    if image.color().has_alpha() {
+        image.write_with_encoder(PngEncoder::new(&mut encoded))
+    } else {
+        DynamicImage::ImageRgb8(image.to_rgb8())
+            .write_with_encoder(JpegEncoder::new_with_quality(&mut encoded, PAGE_JPEG_QUALITY))
+    }
+    .map_err(|e| format!("Failed to encode page: {e}"))?;
+    Ok(encoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{GrayImage, Luma};
+
+    use super::*;
+
+    /// `width` x `height` page of `border` with a `content` block spanning
+    /// the given inclusive pixel ranges.
+    fn page(width: u32, height: u32, border: u8, content: u8, x: (u32, u32), y: (u32, u32)) -> GrayImage {
+        GrayImage::from_fn(width, height, |px, py| {
+            let inside = (x.0..=x.1).contains(&px) && (y.0..=y.1).contains(&py);
+            Luma([if inside { content } else { border }])
+        })
+    }
+
+    #[test]
+    fn uniform_pages_are_not_cropped() {
+        for value in [0, 255] {
+            let image = DynamicImage::ImageLuma8(GrayImage::from_pixel(100, 150, Luma([value])));
+            assert_eq!(detect_content_bounds(&image, BorderCrop::default()), None);
+        }
+    }
+
+    #[test]
+    fn pages_without_borders_are_not_cropped() {
+        let image = DynamicImage::ImageLuma8(page(100, 150, 255, 0, (0, 99), (0, 149)));
+        assert_eq!(detect_content_bounds(&image, BorderCrop::default()), None);
+    }
+
+    #[test]
+    fn white_and_black_borders_are_cropped() {
+        for (border, content) in [(255, 20), (0, 230)] {
+            let image = DynamicImage::ImageLuma8(page(100, 150, border, content, (10, 89), (5, 139)));
+            assert_eq!(
+                detect_content_bounds(&image, BorderCrop::default()),
+                Some(ContentBounds {
+                    x: 10,
+                    y: 5,
+                    width: 80,
+                    height: 135,
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn scanner_noise_does_not_end_a_border() {
+        let mut image = page(400, 600, 255, 0, (40, 359), (30, 569));
+        // One dust pixel in every border line, and slight paper grain.
+        for y in 0..30 {
+            image.put_pixel((y * 13) % 400, y, Luma([0]));
+        }
+        for x in 0..40 {
+            image.put_pixel(x, 100 + x * 7, Luma([0]));
+            image.put_pixel(x, 101 + x * 7, Luma([240]));
+        }
+
+        let bounds = detect_content_bounds(&DynamicImage::ImageLuma8(image), BorderCrop::default());
+
+        assert_eq!(
+            bounds,
+            Some(ContentBounds {
+                x: 40,
+                y: 30,
+                width: 320,
+                height: 540,
+            })
+        );
+    }
+
+    #[test]
+    fn mostly_empty_pages_are_not_cropped() {
+        let image = DynamicImage::ImageLuma8(page(100, 150, 255, 0, (40, 59), (10, 139)));
+        assert_eq!(detect_content_bounds(&image, BorderCrop::default()), None);
+
+        let crop = BorderCrop {
+            min_content_percent: 10,
+            ..BorderCrop::default()
+        };
+        assert_eq!(
+            detect_content_bounds(&image, crop),
+            Some(ContentBounds {
+                x: 40,
+                y: 10,
+                width: 20,
+                height: 130,
+            })
+        );
+    }
+}
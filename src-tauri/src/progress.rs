@@ -0,0 +1,252 @@
+//! Aggregated indexing progress. Workers update shared counters and events are
+//! emitted at most every [`EMIT_INTERVAL`], apart from phase changes.
+
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+use crate::storage::StorageKind;
+
+pub const INDEXING_PROGRESS_EVENT: &str = "indexing-progress";
+const EMIT_INTERVAL: Duration = Duration::from_millis(200);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum IndexingPhase {
+    Scanning,
+    Indexing,
+    Finalizing,
+    Completed,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct IndexingProgressEventPayload {
+    base_path: String,
+    phase: IndexingPhase,
+    current_path: Option<String>,
+    comics_done: usize,
+    comics_total: usize,
+    pages_done: usize,
+    pages_total: usize,
+    bytes_processed: u64,
+    percentage: f64,
+    elapsed_ms: u64,
+    eta_ms: Option<u64>,
+    pages_per_second: f64,
+    bytes_per_second: f64,
+    /// Storage of the index path and the number of comics read at the same
+    /// time, once indexing started.
+    storage: Option<StorageKind>,
+    workers: usize,
+    comics_active: usize,
+}
+
+/// Progress of one indexing run, shared by reference between its workers.
+pub struct IndexingProgress<'a> {
+    app: &'a AppHandle,
+    base_path: &'a str,
+    /// Whether progress events are emitted at all.
+    emits: bool,
+    started: Instant,
+    phase: Mutex<IndexingPhase>,
+    current_path: Mutex<Option<String>>,
+    comics_total: AtomicUsize,
+    comics_done: AtomicUsize,
+    comics_active: AtomicUsize,
+    pages_total: AtomicUsize,
+    pages_done: AtomicUsize,
+    // Pages of comics that are still being processed, to account for partly
+    // indexed comics in the percentage.
+    active_pages_total: AtomicUsize,
+    active_pages_done: AtomicUsize,
+    bytes_processed: AtomicU64,
+    concurrency: Mutex<Option<(StorageKind, usize)>>,
+    last_emit: Mutex<Option<Instant>>,
+}
+
+impl<'a> IndexingProgress<'a> {
+    pub fn new(app: &'a AppHandle, base_path: &'a str) -> Self {
+        Self::with_events(app, base_path, true)
+    }
+
+    /// Progress that is tracked but never reported, for work outside of
+    /// indexing runs that listeners must not mistake for one.
+    pub fn silent(app: &'a AppHandle, base_path: &'a str) -> Self {
+        Self::with_events(app, base_path, false)
+    }
+
+    fn with_events(app: &'a AppHandle, base_path: &'a str, emits: bool) -> Self {
+        Self {
+            app,
+            base_path,
+            emits,
+            started: Instant::now(),
+            phase: Mutex::new(IndexingPhase::Scanning),
+            current_path: Mutex::new(None),
+            comics_total: AtomicUsize::new(0),
+            comics_done: AtomicUsize::new(0),
+            comics_active: AtomicUsize::new(0),
+            pages_total: AtomicUsize::new(0),
+            pages_done: AtomicUsize::new(0),
+            active_pages_total: AtomicUsize::new(0),
+            active_pages_done: AtomicUsize::new(0),
+            bytes_processed: AtomicU64::new(0),
+            concurrency: Mutex::new(None),
+            last_emit: Mutex::new(None),
+        }
+    }
+
+    pub fn set_phase(&self, phase: IndexingPhase) {
+        *self.phase.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = phase;
+        self.emit();
+    }
+
+    pub fn set_comics(&self, done: usize, total: usize) {
+        self.comics_done.store(done, Ordering::Relaxed);
+        self.comics_total.store(total, Ordering::Relaxed);
+    }
+
+    pub fn set_concurrency(&self, storage: StorageKind, workers: usize) {
+        *self.concurrency.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some((storage, workers));
+    }
+
+    pub fn start_comic(&self, comic_path: &str) -> ComicProgress<'_, 'a> {
+        self.comics_active.fetch_add(1, Ordering::Relaxed);
+        *self.current_path.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) =
+            Some(comic_path.to_string());
+        self.emit_throttled();
+        ComicProgress {
+            progress: self,
+            pages_discovered: AtomicUsize::new(0),
+            pages_done: AtomicUsize::new(0),
+        }
+    }
+
+    fn emit_throttled(&self) {
+        // Workers that lose the race simply skip the event.
+        let Ok(mut last_emit) = self.last_emit.try_lock() else {
+            return;
+        };
+        if last_emit.is_some_and(|last| last.elapsed() < EMIT_INTERVAL) {
+            return;
+        }
+        *last_emit = Some(Instant::now());
+        drop(last_emit);
+        self.send(self.snapshot());
+    }
+
+    fn emit(&self) {
+        *self.last_emit.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(Instant::now());
+        self.send(self.snapshot());
+    }
+
+    fn send(&self, payload: IndexingProgressEventPayload) {
+        if !self.emits {
+            return;
+        }
+        if let Err(error) = self.app.emit(INDEXING_PROGRESS_EVENT, payload) {
+            eprintln!("[Indexing][Rust] Failed to emit progress event: {error}");
+        }
+    }
+
+    fn snapshot(&self) -> IndexingProgressEventPayload {
+        let phase = *self.phase.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let comics_total = self.comics_total.load(Ordering::Relaxed);
+        let comics_done = self.comics_done.load(Ordering::Relaxed).min(comics_total);
+        let comics_active = self.comics_active.load(Ordering::Relaxed);
+        let active_pages_total = self.active_pages_total.load(Ordering::Relaxed);
+        let active_pages_done = self.active_pages_done.load(Ordering::Relaxed);
+        let pages_done = self.pages_done.load(Ordering::Relaxed);
+        let bytes_processed = self.bytes_processed.load(Ordering::Relaxed);
+
+        let fraction = match phase {
+            IndexingPhase::Finalizing | IndexingPhase::Completed => 1.0,
+            _ if comics_total == 0 => 0.0,
+            _ => {
+                let active_fraction = if active_pages_total == 0 {
+                    0.0
+                } else {
+                    active_pages_done as f64 / active_pages_total as f64
+                };
+                ((comics_done as f64 + active_fraction * comics_active as f64) / comics_total as f64)
+                    .clamp(0.0, 1.0)
+            }
+        };
+
+        let elapsed = self.started.elapsed();
+        let elapsed_secs = elapsed.as_secs_f64();
+        let eta_ms = (phase == IndexingPhase::Indexing && fraction > 0.0)
+            .then(|| (elapsed_secs * (1.0 - fraction) / fraction * 1000.0) as u64);
+        let per_second = |count: f64| if elapsed_secs > 0.0 { count / elapsed_secs } else { 0.0 };
+        let concurrency = *self.concurrency.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        IndexingProgressEventPayload {
+            base_path: self.base_path.to_string(),
+            phase,
+            current_path: self
+                .current_path
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .clone(),
+            comics_done,
+            comics_total,
+            pages_done,
+            pages_total: self.pages_total.load(Ordering::Relaxed),
+            bytes_processed,
+            percentage: fraction * 100.0,
+            elapsed_ms: elapsed.as_millis() as u64,
+            eta_ms,
+            pages_per_second: per_second(pages_done as f64),
+            bytes_per_second: per_second(bytes_processed as f64),
+            storage: concurrency.map(|(storage, _)| storage),
+            workers: concurrency.map_or(0, |(_, workers)| workers),
+            comics_active,
+        }
+    }
+}
+
+/// Progress of one comic within a run. Dropping it marks the comic as done,
+/// whether or not it was indexed successfully.
+pub struct ComicProgress<'p, 'a> {
+    progress: &'p IndexingProgress<'a>,
+    pages_discovered: AtomicUsize,
+    pages_done: AtomicUsize,
+}
+
+impl ComicProgress<'_, '_> {
+    pub fn pages_discovered(&self, count: usize) {
+        self.pages_discovered.fetch_add(count, Ordering::Relaxed);
+        self.progress.pages_total.fetch_add(count, Ordering::Relaxed);
+        self.progress.active_pages_total.fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// `bytes` is the size of the page data that had to be read, zero for
+    /// pages whose thumbnail was already cached.
+    pub fn page_done(&self, bytes: usize) {
+        self.pages_done.fetch_add(1, Ordering::Relaxed);
+        let progress = self.progress;
+        progress.pages_done.fetch_add(1, Ordering::Relaxed);
+        progress.active_pages_done.fetch_add(1, Ordering::Relaxed);
+        progress.bytes_processed.fetch_add(bytes as u64, Ordering::Relaxed);
+        progress.emit_throttled();
+    }
+}
+
+impl Drop for ComicProgress<'_, '_> {
+    fn drop(&mut self) {
+        let progress = self.progress;
+        progress
+            .active_pages_total
+            .fetch_sub(*self.pages_discovered.get_mut(), Ordering::Relaxed);
+        progress
+            .active_pages_done
+            .fetch_sub(*self.pages_done.get_mut(), Ordering::Relaxed);
+        progress.comics_active.fetch_sub(1, Ordering::Relaxed);
+        progress.comics_done.fetch_add(1, Ordering::Relaxed);
+        progress.emit_throttled();
+    }
+}
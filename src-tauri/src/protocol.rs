@@ -0,0 +1,319 @@
+//! `comic://` protocol serving page images, so the webview loads pages as
+//! plain URLs instead of receiving their bytes as JSON arrays over IPC.
+//!
+//! Pages are addressed by comic path and page number:
+//! `comic://localhost/{percent-encoded comic path}?page={n}`, or
+//! `http://comic.localhost/...` on Windows, as built by
+//! `convertFileSrc(comicPath, 'comic')`. Optional `width` and `height`
+//! parameters downscale the page to fit within those bounds, and `crop=1`
+//! crops its borders, tuned by `cropTolerance` and `cropMinContent`.
+//!
+//! Thumbnails are served from the same scheme with `?thumbnail=1` in place of
+//! the page number, e.g. `comic://localhost/{percent-encoded thumbnail path}?thumbnail=1`,
+//! so that every load counts as a use of the thumbnail for cache eviction.
+//! An optional `size` parameter, the longest edge the thumbnail is displayed
+//! at in physical pixels, selects the smallest generated tier that covers it.
+//! Only files inside the thumbnail directory are served.
+
+use std::fs;
+use std::path::Path;
+
+use percent_encoding::percent_decode_str;
+use tauri::http::{header, Method, Request, Response, StatusCode};
+use tauri::{AppHandle, Manager};
+
+use crate::cache_keys::content_key;
+use crate::page_cache::{PageCache, PageVariant};
+use crate::page_images::BorderCrop;
+use crate::thumbnail_cache::ThumbnailCache;
+use crate::thumbnail_settings::{ThumbnailConfig, ThumbnailTier};
+
+pub const COMIC_PROTOCOL: &str = "comic";
+/// Pages of a comic rarely change; the ETag catches those that do once the
+/// cached copy expires.
+const CACHE_CONTROL: &str = "private, max-age=3600";
+/// Thumbnails are revalidated on every load, so each use is recorded.
+const THUMBNAIL_CACHE_CONTROL: &str = "private, no-cache";
+
+/// Requested part of a response body, as inclusive byte offsets.
+#[derive(Debug, PartialEq, Eq)]
+enum ByteRange {
+    Full,
+    Partial(usize, usize),
+    Unsatisfiable,
+}
+
+pub fn handle_request(app: &AppHandle, request: &Request<Vec<u8>>) -> Response<Vec<u8>> {
+    let served = if query_param::<u8>(request, "thumbnail") == Some(1) {
+        serve_thumbnail(app, request)
+    } else {
+        serve_page(app, request)
+    };
+    served.unwrap_or_else(|(status, message)| {
+        if status == StatusCode::INTERNAL_SERVER_ERROR {
+            eprintln!("[Protocol][Rust] Failed to serve {}: {}", request.uri(), message);
+        }
+        response(status, CACHE_CONTROL)
+            .header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+            .body(message.into_bytes())
+            .unwrap_or_default()
+    })
+}
+
+type Served = Result<Response<Vec<u8>>, (StatusCode, String)>;
+
+fn serve_page(app: &AppHandle, request: &Request<Vec<u8>>) -> Served {
+    ensure_readable(request)?;
+    let comic_path = request_path(request, "comic")?;
+    let page_number = query_param::<i64>(request, "page")
+        .ok_or_else(|| (StatusCode::BAD_REQUEST, "Missing page number".to_string()))?;
+    let variant = PageVariant {
+        max_width: query_param::<u32>(request, "width").filter(|width| *width > 0),
+        max_height: query_param::<u32>(request, "height").filter(|height| *height > 0),
+        crop: query_param::<u8>(request, "crop").filter(|crop| *crop == 1).map(|_| {
+            let defaults = BorderCrop::default();
+            BorderCrop {
+                tolerance: query_param(request, "cropTolerance").unwrap_or(defaults.tolerance),
+                min_content_percent: query_param(request, "cropMinContent").unwrap_or(defaults.min_content_percent),
+            }
+        }),
+    };
+
+    if !Path::new(&comic_path).exists() {
+        return Err((StatusCode::NOT_FOUND, format!("Comic not found: {comic_path}")));
+    }
+    let cache = app.state::<PageCache>();
+    let pages = cache
+        .list_pages(&comic_path)
+        .map_err(|message| (StatusCode::NOT_FOUND, message))?;
+    let page = pages
+        .iter()
+        .find(|page| page.page_number == page_number)
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                format!("Page {page_number} not found in {comic_path}"),
+            )
+        })?;
+
+    let etag = format!(
+        "\"{}\"",
+        content_key(&format!("{}{}", page.fingerprint, variant.cache_tag()))
+    );
+    if is_not_modified(request, &etag) {
+        return not_modified(&etag, CACHE_CONTROL);
+    }
+
+    let bytes = cache
+        .read_page(&comic_path, page, variant)
+        .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    let content_type = image::guess_format(&bytes)
+        .or_else(|_| image::ImageFormat::from_path(&page.file_name))
+        .map(|format| format.to_mime_type())
+        .unwrap_or("application/octet-stream");
+    send(request, &bytes, content_type, &etag, CACHE_CONTROL)
+}
+
+/// Serves a thumbnail file, or its tier for the requested size, and records
+/// its use.
+fn serve_thumbnail(app: &AppHandle, request: &Request<Vec<u8>>) -> Served {
+    ensure_readable(request)?;
+    let requested = request_path(request, "thumbnail")?;
+    let thumb_root = crate::ensure_thumb_dir(app)
+        .and_then(|root| fs::canonicalize(&root).map_err(|e| format!("Failed to resolve {}: {e}", root.display())))
+        .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    let path = fs::canonicalize(&requested)
+        .ok()
+        .filter(|path| path.starts_with(&thumb_root) && path.is_file())
+        .ok_or_else(|| (StatusCode::NOT_FOUND, format!("Thumbnail not found: {requested}")))?;
+    let path = match query_param::<u32>(request, "size").filter(|size| *size > 0) {
+        Some(size) => {
+            let settings = app.state::<ThumbnailConfig>().get(app);
+            ThumbnailTier::path_for_size(&path, size, &settings).unwrap_or(path)
+        }
+        None => path,
+    };
+
+    app.state::<ThumbnailCache>().record_hit(&path);
+    // Thumbnail files are written once under their final name and replaced
+    // by new files rather than rewritten, so path and size identify them.
+    let len = fs::metadata(&path).map(|metadata| metadata.len()).unwrap_or_default();
+    let etag = format!("\"{}\"", content_key(&format!("{}:{}", path.display(), len)));
+    if is_not_modified(request, &etag) {
+        return not_modified(&etag, THUMBNAIL_CACHE_CONTROL);
+    }
+
+    let bytes = fs::read(&path).map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to read thumbnail {}: {e}", path.display()),
+        )
+    })?;
+    let content_type = image::ImageFormat::from_path(&path)
+        .map(|format| format.to_mime_type())
+        .unwrap_or("application/octet-stream");
+    send(request, &bytes, content_type, &etag, THUMBNAIL_CACHE_CONTROL)
+}
+
+fn ensure_readable(request: &Request<Vec<u8>>) -> Result<(), (StatusCode, String)> {
+    let method = request.method();
+    if method != Method::GET && method != Method::HEAD {
+        return Err((StatusCode::METHOD_NOT_ALLOWED, format!("Unsupported method {method}")));
+    }
+    Ok(())
+}
+
+/// Decoded file path of a request, `kind` names it in errors.
+fn request_path(request: &Request<Vec<u8>>, kind: &str) -> Result<String, (StatusCode, String)> {
+    let path = percent_decode_str(request.uri().path().trim_start_matches('/'))
+        .decode_utf8()
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid {kind} path: {e}")))?
+        .into_owned();
+    if path.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, format!("Missing {kind} path")));
+    }
+    Ok(path)
+}
+
+fn is_not_modified(request: &Request<Vec<u8>>, etag: &str) -> bool {
+    request
+        .headers()
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.split(',').any(|candidate| candidate.trim() == etag))
+}
+
+fn not_modified(etag: &str, cache_control: &str) -> Served {
+    response(StatusCode::NOT_MODIFIED, cache_control)
+        .header(header::ETAG, etag)
+        .body(Vec::new())
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+/// Sends `bytes`, or the part of them the `Range` header asks for.
+fn send(request: &Request<Vec<u8>>, bytes: &[u8], content_type: &str, etag: &str, cache_control: &str) -> Served {
+    let range = request
+        .headers()
+        .get(header::RANGE)
+        .and_then(|value| value.to_str().ok())
+        .map_or(ByteRange::Full, |value| parse_range(value, bytes.len()));
+
+    let builder = response(StatusCode::OK, cache_control)
+        .header(header::CONTENT_TYPE, content_type)
+        .header(header::ETAG, etag);
+    let (builder, body) = match range {
+        ByteRange::Full => (builder, bytes.to_vec()),
+        ByteRange::Partial(start, end) => (
+            builder
+                .status(StatusCode::PARTIAL_CONTENT)
+                .header(header::CONTENT_RANGE, format!("bytes {}-{}/{}", start, end, bytes.len())),
+            bytes[start..=end].to_vec(),
+        ),
+        ByteRange::Unsatisfiable => (
+            builder
+                .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                .header(header::CONTENT_RANGE, format!("bytes */{}", bytes.len())),
+            Vec::new(),
+        ),
+    };
+    let body = if request.method() == Method::HEAD { Vec::new() } else { body };
+    builder
+        .body(body)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+fn query_param<T: std::str::FromStr>(request: &Request<Vec<u8>>, name: &str) -> Option<T> {
+    request.uri().query()?.split('&').find_map(|pair| {
+        let (key, value) = pair.split_once('=')?;
+        (key == name).then(|| value.parse().ok()).flatten()
+    })
+}
+
+fn response(status: StatusCode, cache_control: &str) -> tauri::http::response::Builder {
+    Response::builder()
+        .status(status)
+        .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+        .header(header::ACCEPT_RANGES, "bytes")
+        .header(header::CACHE_CONTROL, cache_control)
+}
+
+/// Parses a `Range` header for a body of `len` bytes. Only single `bytes`
+/// ranges are supported; anything else is answered with the full body, which
+/// RFC 9110 permits.
+fn parse_range(value: &str, len: usize) -> ByteRange {
+    let Some(spec) = value.trim().strip_prefix("bytes=") else {
+        return ByteRange::Full;
+    };
+    let Some((start, end)) = spec.split_once('-').filter(|_| !spec.contains(',')) else {
+        return ByteRange::Full;
+    };
+    let (start, end) = (start.trim(), end.trim());
+
+    if start.is_empty() {
+        // Suffix range: the last `end` bytes.
+        return match end.parse::<usize>() {
+            Ok(0) => ByteRange::Unsatisfiable,
+            Ok(_) if len == 0 => ByteRange::Unsatisfiable,
+            Ok(suffix) => ByteRange::Partial(len.saturating_sub(suffix), len - 1),
+            Err(_) => ByteRange::Full,
+        };
+    }
+    let Ok(start) = start.parse::<usize>() else {
+        return ByteRange::Full;
+    };
+    let end = if end.is_empty() {
+        len.saturating_sub(1)
+    } else {
+        match end.parse::<usize>() {
+            Ok(end) => end.min(len.saturating_sub(1)),
+            Err(_) => return ByteRange::Full,
+        }
+    };
+    if start >= len || end < start {
+        ByteRange::Unsatisfiable
+    } else {
+        ByteRange::Partial(start, end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn closed_ranges_are_clamped_to_the_body() {
+        assert_eq!(parse_range("bytes=0-99", 1000), ByteRange::Partial(0, 99));
+        assert_eq!(parse_range(" bytes=10-10 ", 1000), ByteRange::Partial(10, 10));
+        assert_eq!(parse_range("bytes=900-5000", 1000), ByteRange::Partial(900, 999));
+    }
+
+    #[test]
+    fn open_ranges_run_to_the_end() {
+        assert_eq!(parse_range("bytes=0-", 1000), ByteRange::Partial(0, 999));
+        assert_eq!(parse_range("bytes=999-", 1000), ByteRange::Partial(999, 999));
+    }
+
+    #[test]
+    fn suffix_ranges_count_from_the_end() {
+        assert_eq!(parse_range("bytes=-100", 1000), ByteRange::Partial(900, 999));
+        assert_eq!(parse_range("bytes=-5000", 1000), ByteRange::Partial(0, 999));
+        assert_eq!(parse_range("bytes=-0", 1000), ByteRange::Unsatisfiable);
+        assert_eq!(parse_range("bytes=-100", 0), ByteRange::Unsatisfiable);
+    }
+
+    #[test]
+    fn ranges_outside_the_body_are_unsatisfiable() {
+        assert_eq!(parse_range("bytes=1000-", 1000), ByteRange::Unsatisfiable);
+        assert_eq!(parse_range("bytes=1000-1100", 1000), ByteRange::Unsatisfiable);
+        assert_eq!(parse_range("bytes=50-10", 1000), ByteRange::Unsatisfiable);
+        assert_eq!(parse_range("bytes=0-", 0), ByteRange::Unsatisfiable);
+    }
+
+    #[test]
+    fn unsupported_or_malformed_ranges_serve_the_full_body() {
+        let values = ["", "items=0-10", "bytes=0-10,20-30", "bytes=abc-", "bytes=0-xyz", "bytes=-abc", "bytes=10"];
+        for value in values {
+            assert_eq!(parse_range(value, 1000), ByteRange::Full, "{value}");
+        }
+    }
+}
@@ -0,0 +1,274 @@
+//! Process wide caches that let archive sources serve single pages without
+//! re-reading the whole archive: a pool of open readers for formats with a
+//! central directory, and decompressed entries for sequential formats.
+//!
+//! Entries are tied to the size and modification time of the archive when
+//! they were created and are not used once the file changes.
+
+use std::fs;
+use std::ops::{Deref, DerefMut};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::lru::LruCache;
+
+/// Identifies one version of an archive file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FileStamp {
+    len: u64,
+    modified: Option<SystemTime>,
+}
+
+impl FileStamp {
+    pub fn of(path: &Path) -> Result<Self, String> {
+        let metadata = fs::metadata(path)
+            .map_err(|e| format!("Failed to read metadata of {}: {e}", path.display()))?;
+        Ok(Self {
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+        })
+    }
+}
+
+struct IdleHandle<T> {
+    path: PathBuf,
+    stamp: FileStamp,
+    handle: T,
+    idle_since: Instant,
+}
+
+struct PoolState<T> {
+    idle: Vec<IdleHandle<T>>,
+    /// Path of every reader that is checked out, once per reader.
+    checked_out: Vec<PathBuf>,
+    /// Archives being replaced, which cannot be checked out meanwhile.
+    replacing: Vec<PathBuf>,
+}
+
+/// Bounded pool of open archive readers. Readers are checked out for
+/// exclusive use and returned when the [`PooledHandle`] is dropped; readers
+/// that stay idle for longer than the idle timeout are closed.
+pub struct HandlePool<T> {
+    max_idle: usize,
+    idle_timeout: Duration,
+    state: Mutex<PoolState<T>>,
+    changed: Condvar,
+    sweeper_running: AtomicBool,
+}
+
+impl<T: Send + 'static> HandlePool<T> {
+    pub const fn new(max_idle: usize, idle_timeout: Duration) -> Self {
+        Self {
+            max_idle,
+            idle_timeout,
+            state: Mutex::new(PoolState {
+                idle: Vec::new(),
+                checked_out: Vec::new(),
+                replacing: Vec::new(),
+            }),
+            changed: Condvar::new(),
+            sweeper_running: AtomicBool::new(false),
+        }
+    }
+
+    /// Reuses an idle reader of the current version of `path`, or opens a new
+    /// one with `open`. Waits while the archive is being replaced.
+    pub fn checkout(
+        &'static self,
+        path: &Path,
+        open: impl FnOnce() -> Result<T, String>,
+    ) -> Result<PooledHandle<T>, String> {
+        let reused = {
+            let mut state = self.lock();
+            while state.replacing.iter().any(|replacing| replacing == path) {
+                state = self.changed.wait(state).unwrap_or_else(|poisoned| poisoned.into_inner());
+            }
+            state.checked_out.push(path.to_path_buf());
+            FileStamp::of(path).map(|stamp| {
+                state.idle.retain(|entry| entry.path != path || entry.stamp == stamp);
+                let reused = state
+                    .idle
+                    .iter()
+                    .position(|entry| entry.path == path)
+                    .map(|index| state.idle.remove(index).handle);
+                (stamp, reused)
+            })
+        };
+        let opened = reused.and_then(|(stamp, reused)| match reused {
+            Some(handle) => Ok((stamp, handle)),
+            None => open().map(|handle| (stamp, handle)),
+        });
+        let (stamp, handle) = match opened {
+            Ok(opened) => opened,
+            Err(message) => {
+                self.release(path);
+                return Err(message);
+            }
+        };
+        Ok(PooledHandle {
+            pool: self,
+            path: path.to_path_buf(),
+            stamp,
+            handle: Some(handle),
+        })
+    }
+
+    /// Replaces the archive at `path` with `replace`, e.g. a rename. Waits
+    /// until all readers of `path` are returned, closes them and keeps new
+    /// readers from being opened until `replace` finished, as Windows cannot
+    /// replace a file that is still open.
+    pub fn replace<R>(&self, path: &Path, replace: impl FnOnce() -> Result<R, String>) -> Result<R, String> {
+        let mut state = self.lock();
+        while state.replacing.iter().any(|replacing| replacing == path) {
+            state = self.changed.wait(state).unwrap_or_else(|poisoned| poisoned.into_inner());
+        }
+        state.replacing.push(path.to_path_buf());
+        while state.checked_out.iter().any(|checked_out| checked_out == path) {
+            state = self.changed.wait(state).unwrap_or_else(|poisoned| poisoned.into_inner());
+        }
+        state.idle.retain(|entry| entry.path != path);
+        drop(state);
+
+        let result = replace();
+
+        let mut state = self.lock();
+        if let Some(index) = state.replacing.iter().position(|replacing| replacing == path) {
+            state.replacing.swap_remove(index);
+        }
+        drop(state);
+        self.changed.notify_all();
+        result
+    }
+
+    /// Marks one reader of `path` as no longer checked out.
+    fn release(&self, path: &Path) {
+        let mut state = self.lock();
+        if let Some(index) = state.checked_out.iter().position(|checked_out| checked_out == path) {
+            state.checked_out.swap_remove(index);
+        }
+        drop(state);
+        self.changed.notify_all();
+    }
+
+    fn checkin(&'static self, path: PathBuf, stamp: FileStamp, handle: T) {
+        // A reader of a replaced file would keep the old file open.
+        if FileStamp::of(&path).ok() != Some(stamp) {
+            drop(handle);
+            self.release(&path);
+            return;
+        }
+        let mut state = self.lock();
+        if let Some(index) = state.checked_out.iter().position(|checked_out| *checked_out == path) {
+            state.checked_out.swap_remove(index);
+        }
+        state.idle.push(IdleHandle {
+            path,
+            stamp,
+            handle,
+            idle_since: Instant::now(),
+        });
+        if state.idle.len() > self.max_idle {
+            // The oldest idle reader is always first, readers are only appended.
+            state.idle.remove(0);
+        }
+        drop(state);
+        self.changed.notify_all();
+        self.ensure_sweeper();
+    }
+
+    /// Closes expired readers in the background while any are idle.
+    fn ensure_sweeper(&'static self) {
+        if self.sweeper_running.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        let spawned = thread::Builder::new()
+            .name("archive-handle-sweeper".to_string())
+            .spawn(move || loop {
+                thread::sleep(self.idle_timeout / 2);
+                let mut state = self.lock();
+                state.idle.retain(|entry| entry.idle_since.elapsed() < self.idle_timeout);
+                if state.idle.is_empty() {
+                    self.sweeper_running.store(false, Ordering::Release);
+                    return;
+                }
+            });
+        if let Err(e) = spawned {
+            eprintln!("[Sources][Rust] Failed to start archive handle sweeper: {e}");
+            self.sweeper_running.store(false, Ordering::Release);
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, PoolState<T>> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Reader checked out of a [`HandlePool`], returned to it on drop.
+pub struct PooledHandle<T: Send + 'static> {
+    pool: &'static HandlePool<T>,
+    path: PathBuf,
+    stamp: FileStamp,
+    handle: Option<T>,
+}
+
+impl<T: Send + 'static> Deref for PooledHandle<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.handle.as_ref().expect("pooled handle is present until dropped")
+    }
+}
+
+impl<T: Send + 'static> DerefMut for PooledHandle<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.handle.as_mut().expect("pooled handle is present until dropped")
+    }
+}
+
+impl<T: Send + 'static> Drop for PooledHandle<T> {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            self.pool
+                .checkin(std::mem::take(&mut self.path), self.stamp, handle);
+        }
+    }
+}
+
+/// Decompressed entry of one version of an archive.
+type EntryKey = (PathBuf, FileStamp, String);
+
+/// Decompressed entries of sequential archives within a memory budget, least
+/// recently used first. Entries of replaced archives are no longer found and
+/// age out.
+pub struct DecompressedCache {
+    entries: Mutex<LruCache<EntryKey, Vec<u8>>>,
+}
+
+impl DecompressedCache {
+    pub fn new(budget: usize) -> Self {
+        Self {
+            entries: Mutex::new(LruCache::new(budget)),
+        }
+    }
+
+    pub fn get(&self, path: &Path, stamp: FileStamp, name: &str) -> Option<Vec<u8>> {
+        self.lock().get(&(path.to_path_buf(), stamp, name.to_string())).cloned()
+    }
+
+    /// Adds entries read in one pass over an archive, evicting the least
+    /// recently used entries to stay within the budget.
+    pub fn insert_all(&self, path: &Path, stamp: FileStamp, read: impl IntoIterator<Item = (String, Vec<u8>)>) {
+        let mut entries = self.lock();
+        for (name, bytes) in read {
+            let weight = bytes.len();
+            entries.insert((path.to_path_buf(), stamp, name), bytes, weight);
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, LruCache<EntryKey, Vec<u8>>> {
+        self.entries.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
@@ -0,0 +1,128 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use sevenz_rust::{Password, SevenZReader};
+
+use super::{
+    ensure_all_found, entry_names, pages_from_entries, read_visited_entries, ArchiveEntry, ComicSource, PageLocator,
+    PageVisitor, SourcePage,
+};
+use crate::comic_info::is_comic_info_name;
+use crate::is_image_entry_name;
+
+pub struct Cb7Source {
+    path: PathBuf,
+}
+
+impl Cb7Source {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    fn open(&self) -> Result<SevenZReader<std::fs::File>, String> {
+        SevenZReader::open(&self.path, Password::empty())
+            .map_err(|e| format!("Failed to open CB7 archive {}: {e}", self.path.display()))
+    }
+}
+
+impl ComicSource for Cb7Source {
+    fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn format(&self) -> &'static str {
+        "CB7"
+    }
+
+    fn source_type(&self) -> &'static str {
+        "archive"
+    }
+
+    fn list_pages(&self) -> Result<Vec<SourcePage>, String> {
+        let reader = self.open()?;
+        let entries: Vec<ArchiveEntry> = reader
+            .archive()
+            .files
+            .iter()
+            .filter(|entry| !entry.is_directory() && entry.has_stream())
+            .filter_map(|entry| {
+                let name = entry.name().replace('\\', "/");
+                is_image_entry_name(&name).then(|| {
+                    // Entries without a stored CRC fall back to their timestamp.
+                    let checksum = if entry.has_crc {
+                        entry.crc
+                    } else {
+                        entry.last_modified_date.to_raw()
+                    };
+                    ArchiveEntry::new(name, entry.size(), checksum)
+                })
+            })
+            .collect();
+        Ok(pages_from_entries(entries))
+    }
+
+    fn read_pages(&self, locators: &[PageLocator]) -> Result<Vec<Vec<u8>>, String> {
+        read_visited_entries(self, locators)
+    }
+
+    /// Decodes the requested entries in a single pass. Solid blocks have to be
+    /// decompressed sequentially, so unrequested entries are drained instead of
+    /// being skipped.
+    fn visit_pages(&self, locators: &[PageLocator], visit: &mut PageVisitor) -> Result<(), String> {
+        let names = entry_names(self.format(), locators)?;
+        let mut remaining: HashSet<&str> = names.into_iter().collect();
+        let mut reader = self.open()?;
+
+        // Errors of `visit` stop the pass and are returned as they are.
+        let mut visit_error = None;
+        reader
+            .for_each_entries(|entry, data| {
+                let name = entry.name().replace('\\', "/");
+                if remaining.remove(name.as_str()) {
+                    let mut bytes = Vec::with_capacity(entry.size() as usize);
+                    data.read_to_end(&mut bytes)?;
+                    if let Err(message) = visit(&PageLocator::ArchiveEntry(name), bytes) {
+                        visit_error = Some(message);
+                        return Ok(false);
+                    }
+                } else {
+                    std::io::copy(data, &mut std::io::sink())?;
+                }
+                Ok(!remaining.is_empty())
+            })
+            .map_err(|e| format!("Failed reading CB7 archive {}: {e}", self.path.display()))?;
+
+        match visit_error {
+            Some(message) => Err(message),
+            None => ensure_all_found(self.format(), &remaining),
+        }
+    }
+
+    fn comic_info_xml(&self) -> Result<Option<Vec<u8>>, String> {
+        let mut reader = self.open()?;
+        let has_comic_info = reader
+            .archive()
+            .files
+            .iter()
+            .any(|entry| entry.has_stream() && is_comic_info_name(entry.name()));
+        if !has_comic_info {
+            return Ok(None);
+        }
+
+        let mut comic_info = None;
+        reader
+            .for_each_entries(|entry, data| {
+                if is_comic_info_name(entry.name()) {
+                    let mut bytes = Vec::with_capacity(entry.size() as usize);
+                    data.read_to_end(&mut bytes)?;
+                    comic_info = Some(bytes);
+                    return Ok(false);
+                }
+                std::io::copy(data, &mut std::io::sink())?;
+                Ok(true)
+            })
+            .map_err(|e| format!("Failed reading CB7 archive {}: {e}", self.path.display()))?;
+
+        Ok(comic_info)
+    }
+}
@@ -0,0 +1,207 @@
+use std::collections::{HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use unrar::Archive;
+
+use super::archive_cache::{DecompressedCache, FileStamp};
+use super::{
+    ensure_all_found, entry_names, pages_from_entries, read_visited_entries, ArchiveEntry, ComicSource, PageLocator,
+    PageVisitor, SourcePage,
+};
+use crate::comic_info::is_comic_info_name;
+use crate::is_image_entry_name;
+
+/// Memory for decompressed RAR pages. The page cache is sized so that both
+/// together stay within one budget.
+pub const DECOMPRESSED_PAGES_BUDGET: usize = 96 * 1024 * 1024;
+/// Pages kept around a requested page on a miss: a few behind for paging
+/// back, more ahead for reading on.
+const WINDOW_PAGES_BEHIND: usize = 2;
+const WINDOW_PAGES_AHEAD: usize = 8;
+
+/// RAR archives can only be read front to back, so single page reads keep
+/// the pages decompressed along the way.
+static DECOMPRESSED_PAGES: LazyLock<DecompressedCache> =
+    LazyLock::new(|| DecompressedCache::new(DECOMPRESSED_PAGES_BUDGET));
+
+pub struct CbrSource {
+    path: PathBuf,
+}
+
+impl CbrSource {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Reads the image entries around `name` in one pass: up to
+    /// [`WINDOW_PAGES_BEHIND`] entries before it and [`WINDOW_PAGES_AHEAD`]
+    /// after it, stopping early once half of the cache budget has been read.
+    fn read_window(&self, name: &str) -> Result<Vec<(String, Vec<u8>)>, String> {
+        let window_budget = DECOMPRESSED_PAGES_BUDGET / 2;
+        let mut window: VecDeque<(String, Vec<u8>)> = VecDeque::new();
+        let mut window_size = 0;
+        let mut read_ahead = None;
+
+        let mut archive = Archive::new(&self.path)
+            .open_for_processing()
+            .map_err(|e| format!("Failed to open CBR for processing {}: {e}", self.path.display()))?;
+        while let Some(before_file) = archive
+            .read_header()
+            .map_err(|e| format!("Failed reading CBR header {}: {e}", self.path.display()))?
+        {
+            let current_name = before_file.entry().filename.to_string_lossy().replace('\\', "/");
+            if !is_image_entry_name(&current_name) {
+                archive = before_file
+                    .skip()
+                    .map_err(|e| format!("Failed skipping CBR entry while reading: {e}"))?;
+                continue;
+            }
+
+            let (data, next_archive) = before_file
+                .read()
+                .map_err(|e| format!("Failed reading CBR entry {}: {e}", current_name))?;
+            archive = next_archive;
+            if current_name == name {
+                read_ahead = Some(0);
+            } else if let Some(read_ahead) = read_ahead.as_mut() {
+                *read_ahead += 1;
+            }
+            window_size += data.len();
+            window.push_back((current_name, data));
+
+            match read_ahead {
+                Some(read_ahead) if read_ahead >= WINDOW_PAGES_AHEAD || window_size >= window_budget => break,
+                Some(_) => {}
+                None => {
+                    while window.len() > WINDOW_PAGES_BEHIND
+                        || (window_size > window_budget && !window.is_empty())
+                    {
+                        if let Some((_, evicted)) = window.pop_front() {
+                            window_size -= evicted.len();
+                        }
+                    }
+                }
+            }
+        }
+
+        if read_ahead.is_none() {
+            return Err(format!("{} entry not found: {}", self.format(), name));
+        }
+        Ok(window.into())
+    }
+}
+
+impl ComicSource for CbrSource {
+    fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn format(&self) -> &'static str {
+        "CBR"
+    }
+
+    fn source_type(&self) -> &'static str {
+        "archive"
+    }
+
+    fn list_pages(&self) -> Result<Vec<SourcePage>, String> {
+        let mut entries = Vec::new();
+        let listed = Archive::new(&self.path)
+            .open_for_listing()
+            .map_err(|e| format!("Failed to open CBR for listing {}: {e}", self.path.display()))?;
+
+        for header in listed {
+            let header = header
+                .map_err(|e| format!("Failed reading CBR header {}: {e}", self.path.display()))?;
+            let name = header.filename.to_string_lossy().replace('\\', "/");
+            if is_image_entry_name(&name) {
+                entries.push(ArchiveEntry::new(name, header.unpacked_size, header.file_crc));
+            }
+        }
+
+        Ok(pages_from_entries(entries))
+    }
+
+    fn read_pages(&self, locators: &[PageLocator]) -> Result<Vec<Vec<u8>>, String> {
+        read_visited_entries(self, locators)
+    }
+
+    /// RAR archives are walked sequentially, so all requested entries are
+    /// visited in a single pass over the file.
+    fn visit_pages(&self, locators: &[PageLocator], visit: &mut PageVisitor) -> Result<(), String> {
+        let names = entry_names(self.format(), locators)?;
+        let mut remaining: HashSet<&str> = names.into_iter().collect();
+
+        let mut archive = Archive::new(&self.path)
+            .open_for_processing()
+            .map_err(|e| format!("Failed to open CBR for processing {}: {e}", self.path.display()))?;
+
+        while !remaining.is_empty() {
+            let Some(before_file) = archive
+                .read_header()
+                .map_err(|e| format!("Failed reading CBR header {}: {e}", self.path.display()))?
+            else {
+                break;
+            };
+
+            let current_name = before_file.entry().filename.to_string_lossy().replace('\\', "/");
+            if remaining.remove(current_name.as_str()) {
+                let (data, next_archive) = before_file
+                    .read()
+                    .map_err(|e| format!("Failed reading CBR entry {}: {e}", current_name))?;
+                archive = next_archive;
+                visit(&PageLocator::ArchiveEntry(current_name), data)?;
+            } else {
+                archive = before_file
+                    .skip()
+                    .map_err(|e| format!("Failed skipping CBR entry while reading: {e}"))?;
+            }
+        }
+
+        ensure_all_found(self.format(), &remaining)
+    }
+
+    /// Served from the decompressed page cache, filling it with the pages
+    /// around `locator` on a miss.
+    fn read_page(&self, locator: &PageLocator) -> Result<Vec<u8>, String> {
+        let name = entry_names(self.format(), std::slice::from_ref(locator))?[0];
+        let stamp = FileStamp::of(&self.path)?;
+        if let Some(bytes) = DECOMPRESSED_PAGES.get(&self.path, stamp, name) {
+            return Ok(bytes);
+        }
+
+        let window = self.read_window(name)?;
+        let bytes = window
+            .iter()
+            .find(|(entry_name, _)| entry_name == name)
+            .map(|(_, bytes)| bytes.clone())
+            .ok_or_else(|| format!("{} entry not found: {}", self.format(), name))?;
+        DECOMPRESSED_PAGES.insert_all(&self.path, stamp, window);
+        Ok(bytes)
+    }
+
+    fn comic_info_xml(&self) -> Result<Option<Vec<u8>>, String> {
+        let mut archive = Archive::new(&self.path)
+            .open_for_processing()
+            .map_err(|e| format!("Failed to open CBR for processing {}: {e}", self.path.display()))?;
+
+        while let Some(before_file) = archive
+            .read_header()
+            .map_err(|e| format!("Failed reading CBR header {}: {e}", self.path.display()))?
+        {
+            let current_name = before_file.entry().filename.to_string_lossy().replace('\\', "/");
+            if is_comic_info_name(&current_name) {
+                let (data, _) = before_file
+                    .read()
+                    .map_err(|e| format!("Failed reading CBR entry {}: {e}", current_name))?;
+                return Ok(Some(data));
+            }
+            archive = before_file
+                .skip()
+                .map_err(|e| format!("Failed skipping CBR entry while reading: {e}"))?;
+        }
+
+        Ok(None)
+    }
+}
@@ -0,0 +1,150 @@
+use std::collections::HashSet;
+use std::fs;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+
+use tar::Archive;
+
+use super::{
+    ensure_all_found, entry_names, pages_from_entries, read_visited_entries, ArchiveEntry, ComicSource, PageLocator,
+    PageVisitor, SourcePage,
+};
+use crate::comic_info::is_comic_info_name;
+use crate::is_image_entry_name;
+
+pub struct CbtSource {
+    path: PathBuf,
+}
+
+impl CbtSource {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Opens the tar stream, transparently unwrapping gzip or zstd compression
+    /// based on the leading magic bytes.
+    fn open(&self) -> Result<Archive<Box<dyn Read>>, String> {
+        let file = fs::File::open(&self.path)
+            .map_err(|e| format!("Failed to open CBT archive {}: {e}", self.path.display()))?;
+        let mut reader = BufReader::new(file);
+        let magic = reader
+            .fill_buf()
+            .map_err(|e| format!("Failed to read CBT archive {}: {e}", self.path.display()))?;
+
+        let inner: Box<dyn Read> = if magic.starts_with(&[0x1f, 0x8b]) {
+            Box::new(flate2::read::MultiGzDecoder::new(reader))
+        } else if magic.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            let decoder = zstd::stream::read::Decoder::with_buffer(reader)
+                .map_err(|e| format!("Failed to open zstd stream {}: {e}", self.path.display()))?;
+            Box::new(decoder)
+        } else {
+            Box::new(reader)
+        };
+        Ok(Archive::new(inner))
+    }
+}
+
+impl ComicSource for CbtSource {
+    fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn format(&self) -> &'static str {
+        "CBT"
+    }
+
+    fn source_type(&self) -> &'static str {
+        "archive"
+    }
+
+    fn list_pages(&self) -> Result<Vec<SourcePage>, String> {
+        let mut archive = self.open()?;
+        let entries = archive
+            .entries()
+            .map_err(|e| format!("Failed to read CBT archive {}: {e}", self.path.display()))?;
+
+        let mut image_entries = Vec::new();
+        for entry in entries {
+            let entry = entry
+                .map_err(|e| format!("Failed reading CBT entry {}: {e}", self.path.display()))?;
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+            let name = entry
+                .path()
+                .map_err(|e| format!("Failed reading CBT entry path {}: {e}", self.path.display()))?
+                .to_string_lossy()
+                .replace('\\', "/");
+            if is_image_entry_name(&name) {
+                // Tar stores no checksum of the contents, only a modification time.
+                let header = entry.header();
+                let modified = header.mtime().unwrap_or_default();
+                image_entries.push(ArchiveEntry::new(name, header.size().unwrap_or_default(), modified));
+            }
+        }
+        Ok(pages_from_entries(image_entries))
+    }
+
+    fn read_pages(&self, locators: &[PageLocator]) -> Result<Vec<Vec<u8>>, String> {
+        read_visited_entries(self, locators)
+    }
+
+    fn visit_pages(&self, locators: &[PageLocator], visit: &mut PageVisitor) -> Result<(), String> {
+        let names = entry_names(self.format(), locators)?;
+        let mut remaining: HashSet<&str> = names.into_iter().collect();
+        let mut archive = self.open()?;
+        let entries = archive
+            .entries()
+            .map_err(|e| format!("Failed to read CBT archive {}: {e}", self.path.display()))?;
+
+        for entry in entries {
+            if remaining.is_empty() {
+                break;
+            }
+            let mut entry = entry
+                .map_err(|e| format!("Failed reading CBT entry {}: {e}", self.path.display()))?;
+            let name = entry
+                .path()
+                .map_err(|e| format!("Failed reading CBT entry path {}: {e}", self.path.display()))?
+                .to_string_lossy()
+                .replace('\\', "/");
+            if !remaining.remove(name.as_str()) {
+                continue;
+            }
+            let mut bytes = Vec::with_capacity(entry.size() as usize);
+            entry
+                .read_to_end(&mut bytes)
+                .map_err(|e| format!("Failed reading CBT entry {}: {e}", name))?;
+            visit(&PageLocator::ArchiveEntry(name), bytes)?;
+        }
+
+        ensure_all_found(self.format(), &remaining)
+    }
+
+    fn comic_info_xml(&self) -> Result<Option<Vec<u8>>, String> {
+        let mut archive = self.open()?;
+        let entries = archive
+            .entries()
+            .map_err(|e| format!("Failed to read CBT archive {}: {e}", self.path.display()))?;
+
+        for entry in entries {
+            let mut entry = entry
+                .map_err(|e| format!("Failed reading CBT entry {}: {e}", self.path.display()))?;
+            let name = entry
+                .path()
+                .map_err(|e| format!("Failed reading CBT entry path {}: {e}", self.path.display()))?
+                .to_string_lossy()
+                .replace('\\', "/");
+            if !entry.header().entry_type().is_file() || !is_comic_info_name(&name) {
+                continue;
+            }
+            let mut bytes = Vec::with_capacity(entry.size() as usize);
+            entry
+                .read_to_end(&mut bytes)
+                .map_err(|e| format!("Failed reading CBT entry {}: {e}", name))?;
+            return Ok(Some(bytes));
+        }
+
+        Ok(None)
+    }
+}
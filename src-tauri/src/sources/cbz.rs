@@ -0,0 +1,271 @@
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use super::archive_cache::{HandlePool, PooledHandle};
+use super::{entry_names, pages_from_entries, ArchiveEntry, ComicSource, PageLocator, SourcePage};
+use crate::comic_info::{
+    is_comic_info_name, update_comic_info, ComicInfoUpdate, COMIC_INFO_FILE_NAME,
+};
+use crate::is_image_entry_name;
+
+/// Open archives keep their parsed central directory, so reading one more
+/// page is a single seek.
+static OPEN_ARCHIVES: HandlePool<ZipArchive<fs::File>> = HandlePool::new(16, Duration::from_secs(60));
+
+pub struct CbzSource {
+    path: PathBuf,
+}
+
+impl CbzSource {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    fn open(&self) -> Result<PooledHandle<ZipArchive<fs::File>>, String> {
+        OPEN_ARCHIVES.checkout(&self.path, || self.open_unpooled())
+    }
+
+    fn open_unpooled(&self) -> Result<ZipArchive<fs::File>, String> {
+        let file = fs::File::open(&self.path)
+            .map_err(|e| format!("Failed to open CBZ archive {}: {e}", self.path.display()))?;
+        ZipArchive::new(file)
+            .map_err(|e| format!("Failed to read CBZ archive {}: {e}", self.path.display()))
+    }
+
+    /// Returns the name and contents of the ComicInfo.xml entry, if any.
+    fn read_comic_info_entry(
+        archive: &mut ZipArchive<fs::File>,
+    ) -> Result<Option<(String, Vec<u8>)>, String> {
+        let Some(entry_path) = archive
+            .file_names()
+            .find(|name| is_comic_info_name(name))
+            .map(str::to_string)
+        else {
+            return Ok(None);
+        };
+
+        let mut file = archive
+            .by_name(&entry_path)
+            .map_err(|e| format!("Failed to find CBZ entry {}: {e}", entry_path))?;
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)
+            .map_err(|e| format!("Failed to read CBZ entry {}: {e}", entry_path))?;
+        Ok(Some((entry_path, bytes)))
+    }
+
+    /// Writes a copy of `archive` to `target` with the ComicInfo.xml entry
+    /// replaced. All other entries are copied raw, without recompression.
+    fn write_archive_copy(
+        &self,
+        archive: &mut ZipArchive<fs::File>,
+        target: &Path,
+        entry_path: &str,
+        xml: &[u8],
+    ) -> Result<(), String> {
+        let file = fs::File::create(target)
+            .map_err(|e| format!("Failed to create {}: {e}", target.display()))?;
+        let mut writer = ZipWriter::new(file);
+        writer.set_raw_comment(archive.comment().into());
+
+        for idx in 0..archive.len() {
+            let entry = archive
+                .by_index_raw(idx)
+                .map_err(|e| format!("Failed to read CBZ entry at index {}: {e}", idx))?;
+            if entry.name() == entry_path {
+                continue;
+            }
+            let name = entry.name().to_string();
+            writer
+                .raw_copy_file(entry)
+                .map_err(|e| format!("Failed to copy CBZ entry {}: {e}", name))?;
+        }
+
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        writer
+            .start_file(entry_path, options)
+            .and_then(|_| writer.write_all(xml).map_err(Into::into))
+            .map_err(|e| format!("Failed to write CBZ entry {}: {e}", entry_path))?;
+        let file = writer
+            .finish()
+            .map_err(|e| format!("Failed to finish CBZ archive {}: {e}", target.display()))?;
+        file.sync_all()
+            .map_err(|e| format!("Failed to flush {}: {e}", target.display()))?;
+
+        if let Ok(meta) = fs::metadata(&self.path) {
+            let _ = fs::set_permissions(target, meta.permissions());
+        }
+        Ok(())
+    }
+}
+
+impl ComicSource for CbzSource {
+    fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn format(&self) -> &'static str {
+        "CBZ"
+    }
+
+    fn source_type(&self) -> &'static str {
+        "archive"
+    }
+
+    fn list_pages(&self) -> Result<Vec<SourcePage>, String> {
+        let mut archive = self.open()?;
+
+        let mut entries = Vec::new();
+        for idx in 0..archive.len() {
+            let file = archive
+                .by_index_raw(idx)
+                .map_err(|e| format!("Failed to read CBZ entry at index {}: {e}", idx))?;
+            if !file.is_file() {
+                continue;
+            }
+            let name = file.name().replace('\\', "/");
+            if is_image_entry_name(&name) {
+                entries.push(ArchiveEntry::new(name, file.size(), file.crc32()));
+            }
+        }
+        Ok(pages_from_entries(entries))
+    }
+
+    fn read_pages(&self, locators: &[PageLocator]) -> Result<Vec<Vec<u8>>, String> {
+        let names = entry_names(self.format(), locators)?;
+        let mut archive = self.open()?;
+
+        let mut output = Vec::with_capacity(names.len());
+        for entry_path in names {
+            let mut file = archive
+                .by_name(entry_path)
+                .map_err(|e| format!("Failed to find CBZ entry {}: {e}", entry_path))?;
+            let mut bytes = Vec::new();
+            file.read_to_end(&mut bytes)
+                .map_err(|e| format!("Failed to read CBZ entry {}: {e}", entry_path))?;
+            output.push(bytes);
+        }
+        Ok(output)
+    }
+
+    fn random_access(&self) -> bool {
+        true
+    }
+
+    fn comic_info_xml(&self) -> Result<Option<Vec<u8>>, String> {
+        let mut archive = self.open()?;
+        Ok(Self::read_comic_info_entry(&mut archive)?.map(|(_, bytes)| bytes))
+    }
+
+    /// Rebuilds the archive next to the original and renames it into place, so
+    /// the comic is never left half written.
+    fn write_comic_info(&self, update: &ComicInfoUpdate) -> Result<(), String> {
+        let file_name = self
+            .path
+            .file_name()
+            .and_then(|s| s.to_str())
+            .ok_or_else(|| format!("Invalid CBZ path: {}", self.path.display()))?;
+        let temp_path = self.path.with_file_name(format!(".{}.comic-shelf-tmp", file_name));
+
+        // The copy is read through a reader of its own, closed before the
+        // rename. Pooled readers are closed and kept closed until the archive
+        // is replaced, as Windows cannot replace a file that is still open.
+        let result = self.open_unpooled().and_then(|mut archive| {
+            let (entry_path, existing) = match Self::read_comic_info_entry(&mut archive)? {
+                Some((entry_path, bytes)) => (entry_path, Some(bytes)),
+                None => (COMIC_INFO_FILE_NAME.to_string(), None),
+            };
+            let xml = update_comic_info(existing.as_deref(), update)?;
+            self.write_archive_copy(&mut archive, &temp_path, &entry_path, &xml)
+        });
+        if let Err(message) = result {
+            let _ = fs::remove_file(&temp_path);
+            return Err(message);
+        }
+
+        OPEN_ARCHIVES.replace(&self.path, || {
+            fs::rename(&temp_path, &self.path).map_err(|e| {
+                let _ = fs::remove_file(&temp_path);
+                format!("Failed to replace CBZ archive {}: {e}", self.path.display())
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::process;
+
+    use super::*;
+    use crate::comic_info::parse_comic_info;
+
+    /// Name, compression, CRC and contents of every entry except ComicInfo.xml.
+    fn entry_snapshot(path: &Path) -> Vec<(String, CompressionMethod, u32, Vec<u8>)> {
+        let mut archive = ZipArchive::new(fs::File::open(path).unwrap()).unwrap();
+        let mut entries = Vec::new();
+        for idx in 0..archive.len() {
+            let mut entry = archive.by_index(idx).unwrap();
+            if is_comic_info_name(entry.name()) {
+                continue;
+            }
+            let mut bytes = Vec::new();
+            entry.read_to_end(&mut bytes).unwrap();
+            entries.push((entry.name().to_string(), entry.compression(), entry.crc32(), bytes));
+        }
+        entries
+    }
+
+    #[test]
+    fn writing_comic_info_keeps_other_entries() {
+        let dir = env::temp_dir().join(format!("comic-shelf-cbz-test-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("comic.cbz");
+
+        let mut writer = ZipWriter::new(fs::File::create(&path).unwrap());
+        writer.set_comment("archive comment");
+        let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+        let deflated = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        let comic_info = b"<ComicInfo><Series>Old</Series><Notes>kept</Notes></ComicInfo>".to_vec();
+        for (name, options, bytes) in [
+            ("01.jpg", stored, vec![1u8; 64]),
+            ("ComicInfo.xml", deflated, comic_info),
+            ("sub/02.png", deflated, (0..=255u8).cycle().take(4096).collect()),
+            ("readme.txt", deflated, b"not a page".to_vec()),
+        ] {
+            writer.start_file(name, options).unwrap();
+            writer.write_all(&bytes).unwrap();
+        }
+        writer.finish().unwrap();
+
+        let source = CbzSource::new(path.clone());
+        let before = entry_snapshot(&path);
+        // Leaves a pooled reader of the old archive behind.
+        assert_eq!(source.list_pages().unwrap().len(), 2);
+
+        let update = ComicInfoUpdate {
+            series: Some("New".to_string()),
+            ..ComicInfoUpdate::default()
+        };
+        source.write_comic_info(&update).unwrap();
+
+        assert_eq!(entry_snapshot(&path), before);
+        let archive = ZipArchive::new(fs::File::open(&path).unwrap()).unwrap();
+        assert_eq!(archive.comment(), b"archive comment");
+        let xml = source.comic_info_xml().unwrap().unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&xml),
+            "<ComicInfo><Series>New</Series><Notes>kept</Notes></ComicInfo>"
+        );
+        assert_eq!(parse_comic_info(&xml).unwrap().series.as_deref(), Some("New"));
+        let page = source.read_page(&PageLocator::ArchiveEntry("01.jpg".to_string()));
+        assert_eq!(page.unwrap(), vec![1u8; 64]);
+        assert!(!dir.join(".comic.cbz.comic-shelf-tmp").exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
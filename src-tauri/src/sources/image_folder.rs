@@ -0,0 +1,142 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use rayon::prelude::*;
+
+use super::{system_time_to_ms, ComicSource, PageLocator, SourceMetadata, SourcePage};
+use crate::comic_info::is_comic_info_name;
+use crate::{is_image_file, natural_cmp, to_forward_slash_path};
+
+/// A directory whose image files are the pages of a comic.
+pub struct ImageFolderSource {
+    path: PathBuf,
+}
+
+impl ImageFolderSource {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    fn image_files(&self) -> Result<Vec<PathBuf>, String> {
+        if !self.path.exists() || !self.path.is_dir() {
+            return Err(format!("Comic path is not a directory: {}", self.path.display()));
+        }
+
+        let entries = fs::read_dir(&self.path)
+            .map_err(|e| format!("Failed to read comic dir {}: {e}", self.path.display()))?;
+        let mut files: Vec<PathBuf> = entries
+            .filter_map(Result::ok)
+            .map(|e| e.path())
+            .filter(|p| p.is_file() && is_image_file(p))
+            .collect();
+
+        files.sort_by(|a, b| {
+            let aa = a.file_name().and_then(|s| s.to_str()).unwrap_or_default();
+            let bb = b.file_name().and_then(|s| s.to_str()).unwrap_or_default();
+            natural_cmp(aa, bb)
+        });
+        Ok(files)
+    }
+
+    fn comic_info_path(&self) -> Result<Option<PathBuf>, String> {
+        let entries = fs::read_dir(&self.path)
+            .map_err(|e| format!("Failed to read comic dir {}: {e}", self.path.display()))?;
+        Ok(entries.filter_map(Result::ok).map(|e| e.path()).find(|p| {
+            p.is_file()
+                && p.file_name()
+                    .and_then(|s| s.to_str())
+                    .is_some_and(is_comic_info_name)
+        }))
+    }
+}
+
+impl ComicSource for ImageFolderSource {
+    fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn format(&self) -> &'static str {
+        "image"
+    }
+
+    fn source_type(&self) -> &'static str {
+        "image"
+    }
+
+    fn list_pages(&self) -> Result<Vec<SourcePage>, String> {
+        Ok(self
+            .image_files()?
+            .iter()
+            .enumerate()
+            .map(|(idx, path)| {
+                let file_name = path
+                    .file_name()
+                    .and_then(|s| s.to_str())
+                    .unwrap_or_default()
+                    .to_string();
+                // Name, size and modification time survive moving the folder,
+                // so moved comics keep their thumbnails.
+                let metadata = fs::metadata(path).ok();
+                let fingerprint = format!(
+                    "{}:{}:{:x}",
+                    file_name,
+                    metadata.as_ref().map(|meta| meta.len()).unwrap_or_default(),
+                    metadata
+                        .and_then(|meta| meta.modified().ok())
+                        .and_then(system_time_to_ms)
+                        .unwrap_or_default()
+                );
+                SourcePage {
+                    page_number: (idx + 1) as i64,
+                    file_name,
+                    locator: PageLocator::File(to_forward_slash_path(path)),
+                    fingerprint,
+                }
+            })
+            .collect())
+    }
+
+    fn read_pages(&self, locators: &[PageLocator]) -> Result<Vec<Vec<u8>>, String> {
+        locators
+            .par_iter()
+            .map(|locator| match locator {
+                PageLocator::File(file_path) => fs::read(file_path)
+                    .map_err(|e| format!("Failed to read source image {}: {e}", file_path)),
+                other => Err(format!("Image folders cannot read page {:?}", other)),
+            })
+            .collect()
+    }
+
+    fn random_access(&self) -> bool {
+        true
+    }
+
+    /// Folders have no meaningful size or timestamp of their own, so the
+    /// metadata is aggregated over the contained page images and the
+    /// ComicInfo.xml, whose edits change the comic's metadata.
+    fn metadata(&self) -> Result<SourceMetadata, String> {
+        let files = self.image_files()?;
+        let mut metadata = SourceMetadata {
+            entry_count: Some(files.len()),
+            ..SourceMetadata::default()
+        };
+        for file in files.into_iter().chain(self.comic_info_path()?) {
+            let Ok(meta) = fs::metadata(&file) else {
+                continue;
+            };
+            metadata.file_size += meta.len();
+            let modified = meta.modified().ok().and_then(system_time_to_ms);
+            metadata.modified_ms = metadata.modified_ms.max(modified);
+        }
+        Ok(metadata)
+    }
+
+    fn comic_info_xml(&self) -> Result<Option<Vec<u8>>, String> {
+        match self.comic_info_path()? {
+            Some(path) => fs::read(&path)
+                .map(Some)
+                .map_err(|e| format!("Failed to read {}: {e}", path.display())),
+            None => Ok(None),
+        }
+    }
+}
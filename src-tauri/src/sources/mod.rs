@@ -0,0 +1,336 @@
+//! Comic source backends.
+//!
+//! Every supported format implements [`ComicSource`] exactly once. Indexing,
+//! thumbnail generation and the reader commands only talk to the trait, so a
+//! new format only needs a new implementation and an entry in
+//! [`ARCHIVE_FORMATS`] (or a dedicated branch in [`open_source`]).
+
+mod archive_cache;
+mod cb7;
+mod cbr;
+mod cbt;
+mod cbz;
+mod image_folder;
+mod pdf;
+
+use std::fmt::LowerHex;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+pub use archive_cache::FileStamp;
+pub use cb7::Cb7Source;
+pub use cbr::{CbrSource, DECOMPRESSED_PAGES_BUDGET};
+pub use cbt::CbtSource;
+pub use cbz::CbzSource;
+pub use image_folder::ImageFolderSource;
+pub use pdf::PdfSource;
+
+use crate::comic_info::ComicInfoUpdate;
+use crate::{extension_of, natural_cmp, PDF_EXTENSION};
+
+/// Identifies a single page inside a comic source.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PageLocator {
+    /// Image file on disk, used by folder comics.
+    File(String),
+    /// Entry name (forward slashes) inside an archive.
+    ArchiveEntry(String),
+    /// 1-based page number inside a PDF document.
+    PdfPage(i64),
+}
+
+#[derive(Clone, Debug)]
+pub struct SourcePage {
+    pub page_number: i64,
+    pub file_name: String,
+    pub locator: PageLocator,
+    /// Describes the page contents without reading them, e.g. entry name,
+    /// size and CRC. Pages with equal fingerprints share a thumbnail.
+    pub fingerprint: String,
+}
+
+/// File level facts about a source, independent of its page contents.
+#[derive(Clone, Debug, Default)]
+pub struct SourceMetadata {
+    pub file_size: u64,
+    pub modified_ms: Option<i64>,
+    /// Number of pages, for sources that know it without listing.
+    pub entry_count: Option<usize>,
+}
+
+pub trait ComicSource: Send + Sync {
+    fn path(&self) -> &Path;
+
+    /// Short human readable format name used in logs and progress messages.
+    fn format(&self) -> &'static str;
+
+    /// Source type as stored in the `source_type` database columns.
+    fn source_type(&self) -> &'static str;
+
+    /// Lists all pages in reading order.
+    fn list_pages(&self) -> Result<Vec<SourcePage>, String>;
+
+    /// Reads the raw image bytes of the given pages, in the requested order.
+    fn read_pages(&self, locators: &[PageLocator]) -> Result<Vec<Vec<u8>>, String>;
+
+    /// Reads the given pages and passes each one to `visit` as soon as it is
+    /// read, in the order the source stores them. Sequential sources read them
+    /// in a single pass without holding more than one page in memory.
+    fn visit_pages(&self, locators: &[PageLocator], visit: &mut PageVisitor) -> Result<(), String> {
+        for locator in locators {
+            visit(locator, self.read_page(locator)?)?;
+        }
+        Ok(())
+    }
+
+    fn read_page(&self, locator: &PageLocator) -> Result<Vec<u8>, String> {
+        self.read_pages(std::slice::from_ref(locator))?
+            .pop()
+            .ok_or_else(|| format!("{} page not found: {:?}", self.format(), locator))
+    }
+
+    /// Bytes to generate a thumbnail from. Backends that rasterize pages can
+    /// render at a lower resolution here.
+    fn read_page_preview(&self, locator: &PageLocator) -> Result<Vec<u8>, String> {
+        self.read_page(locator)
+    }
+
+    /// Whether single pages can be read cheaply and concurrently. Sources that
+    /// must be decoded front to back should be read with one `visit_pages` or
+    /// `read_pages` call.
+    fn random_access(&self) -> bool {
+        false
+    }
+
+    fn metadata(&self) -> Result<SourceMetadata, String> {
+        file_metadata(self.path())
+    }
+
+    /// Raw contents of an embedded `ComicInfo.xml`, if the source has one.
+    fn comic_info_xml(&self) -> Result<Option<Vec<u8>>, String> {
+        Ok(None)
+    }
+
+    /// Merges `update` into the embedded ComicInfo.xml. Formats that cannot be
+    /// rewritten safely keep this default and refuse.
+    fn write_comic_info(&self, _update: &ComicInfoUpdate) -> Result<(), String> {
+        Err(format!(
+            "Writing ComicInfo.xml is not supported for {} files, only CBZ archives can be updated: {}",
+            self.format(),
+            self.path().display()
+        ))
+    }
+}
+
+/// Receives pages read by [`ComicSource::visit_pages`].
+pub type PageVisitor<'a> = dyn FnMut(&PageLocator, Vec<u8>) -> Result<(), String> + 'a;
+
+type ArchiveConstructor = fn(PathBuf) -> Box<dyn ComicSource>;
+
+/// Archive backends keyed by lower-case file extension.
+const ARCHIVE_FORMATS: &[(&str, ArchiveConstructor)] = &[
+    ("cbz", |path| Box::new(CbzSource::new(path))),
+    ("cbr", |path| Box::new(CbrSource::new(path))),
+    ("cb7", |path| Box::new(Cb7Source::new(path))),
+    ("7z", |path| Box::new(Cb7Source::new(path))),
+    ("cbt", |path| Box::new(CbtSource::new(path))),
+];
+
+pub fn is_archive_extension(ext: &str) -> bool {
+    ARCHIVE_FORMATS.iter().any(|(known, _)| *known == ext)
+}
+
+pub fn open_archive_source(path: &str) -> Result<Box<dyn ComicSource>, String> {
+    let archive_path = PathBuf::from(path);
+    let ext = extension_of(&archive_path).unwrap_or_default();
+    ARCHIVE_FORMATS
+        .iter()
+        .find(|(known, _)| *known == ext)
+        .map(|(_, constructor)| constructor(archive_path.clone()))
+        .ok_or_else(|| format!("Unsupported archive extension for file: {}", archive_path.display()))
+}
+
+pub fn open_source(path: &str, source_type: &str) -> Result<Box<dyn ComicSource>, String> {
+    match source_type {
+        "image" => Ok(Box::new(ImageFolderSource::new(PathBuf::from(path)))),
+        "pdf" => Ok(Box::new(PdfSource::new(PathBuf::from(path)))),
+        "archive" => open_archive_source(path),
+        _ => Err(format!("Unsupported source type '{}' for {}", source_type, path)),
+    }
+}
+
+/// Opens a comic by its path alone, inferring the source type the same way
+/// the scanner does.
+pub fn open_source_for_path(path: &str) -> Result<Box<dyn ComicSource>, String> {
+    let source_path = Path::new(path);
+    if source_path.is_dir() {
+        return open_source(path, "image");
+    }
+    match extension_of(source_path) {
+        Some(ext) if ext == PDF_EXTENSION => open_source(path, "pdf"),
+        _ => open_archive_source(path),
+    }
+}
+
+fn file_metadata(path: &Path) -> Result<SourceMetadata, String> {
+    let meta = fs::metadata(path)
+        .map_err(|e| format!("Failed to read metadata of {}: {e}", path.display()))?;
+    Ok(SourceMetadata {
+        file_size: meta.len(),
+        modified_ms: meta.modified().ok().and_then(system_time_to_ms),
+        entry_count: None,
+    })
+}
+
+fn system_time_to_ms(time: std::time::SystemTime) -> Option<i64> {
+    time.duration_since(UNIX_EPOCH)
+        .ok()
+        .map(|duration| duration.as_millis() as i64)
+}
+
+fn entry_file_name(entry_path: &str) -> String {
+    Path::new(entry_path)
+        .file_name()
+        .and_then(|s| s.to_str())
+        .unwrap_or(entry_path)
+        .to_string()
+}
+
+/// Image entry of an archive with the fingerprint of its contents.
+struct ArchiveEntry {
+    name: String,
+    fingerprint: String,
+}
+
+impl ArchiveEntry {
+    /// `checksum` is the entry's CRC where the format stores one, or its
+    /// modification time otherwise.
+    fn new(name: String, size: u64, checksum: impl LowerHex) -> Self {
+        let fingerprint = format!("{}:{}:{:x}", name, size, checksum);
+        Self { name, fingerprint }
+    }
+}
+
+/// Sorts archive entries naturally by name and turns them into pages.
+fn pages_from_entries(mut entries: Vec<ArchiveEntry>) -> Vec<SourcePage> {
+    entries.sort_by(|a, b| natural_cmp(&a.name, &b.name));
+    entries
+        .into_iter()
+        .enumerate()
+        .map(|(idx, entry)| SourcePage {
+            page_number: (idx + 1) as i64,
+            file_name: entry_file_name(&entry.name),
+            locator: PageLocator::ArchiveEntry(entry.name),
+            fingerprint: entry.fingerprint,
+        })
+        .collect()
+}
+
+/// Extracts the entry names from archive locators, rejecting other kinds.
+fn entry_names<'a>(format: &str, locators: &'a [PageLocator]) -> Result<Vec<&'a str>, String> {
+    locators
+        .iter()
+        .map(|locator| match locator {
+            PageLocator::ArchiveEntry(name) => Ok(name.as_str()),
+            other => Err(format!("{} sources cannot read page {:?}", format, other)),
+        })
+        .collect()
+}
+
+/// Reads archive entries through `visit_pages`, in the requested order.
+fn read_visited_entries(source: &dyn ComicSource, locators: &[PageLocator]) -> Result<Vec<Vec<u8>>, String> {
+    let names = entry_names(source.format(), locators)?;
+    let mut found = std::collections::HashMap::new();
+    source.visit_pages(locators, &mut |locator, bytes| {
+        if let PageLocator::ArchiveEntry(name) = locator {
+            found.insert(name.clone(), bytes);
+        }
+        Ok(())
+    })?;
+    order_entries(source.format(), &names, found)
+}
+
+/// Fails for the first requested entry that an archive pass did not find.
+fn ensure_all_found(format: &str, remaining: &std::collections::HashSet<&str>) -> Result<(), String> {
+    match remaining.iter().next() {
+        Some(name) => Err(format!("{} entry not found: {}", format, name)),
+        None => Ok(()),
+    }
+}
+
+/// Orders entries read in a single archive pass by the requested locators.
+fn order_entries(
+    format: &str,
+    names: &[&str],
+    mut found: std::collections::HashMap<String, Vec<u8>>,
+) -> Result<Vec<Vec<u8>>, String> {
+    let mut output = Vec::with_capacity(names.len());
+    for (idx, name) in names.iter().enumerate() {
+        let repeated_later = names[idx + 1..].contains(name);
+        let data = if repeated_later {
+            found.get(*name).cloned()
+        } else {
+            found.remove(*name)
+        };
+        output.push(data.ok_or_else(|| format!("{} entry not found: {}", format, name))?);
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn pages_are_sorted_naturally_by_entry_name() {
+        let names = ["chapter 2/page10.jpg", "chapter 2/page2.jpg", "Chapter 1/Page1.jpg", "chapter 10/page1.jpg"];
+        let entries = names
+            .into_iter()
+            .map(|name| ArchiveEntry::new(name.to_string(), 1, 0u32))
+            .collect();
+
+        let pages = pages_from_entries(entries);
+
+        let order: Vec<(i64, &str, &str)> = pages
+            .iter()
+            .map(|page| match &page.locator {
+                PageLocator::ArchiveEntry(name) => (page.page_number, name.as_str(), page.file_name.as_str()),
+                other => panic!("unexpected locator {:?}", other),
+            })
+            .collect();
+        assert_eq!(
+            order,
+            vec![
+                (1, "Chapter 1/Page1.jpg", "Page1.jpg"),
+                (2, "chapter 2/page2.jpg", "page2.jpg"),
+                (3, "chapter 2/page10.jpg", "page10.jpg"),
+                (4, "chapter 10/page1.jpg", "page1.jpg"),
+            ]
+        );
+    }
+
+    #[test]
+    fn entries_are_returned_in_requested_order() {
+        let found = HashMap::from([
+            ("a.jpg".to_string(), vec![1]),
+            ("b.jpg".to_string(), vec![2]),
+            ("c.jpg".to_string(), vec![3]),
+        ]);
+
+        let ordered = order_entries("CBR", &["c.jpg", "a.jpg", "c.jpg"], found).unwrap();
+
+        assert_eq!(ordered, vec![vec![3], vec![1], vec![3]]);
+    }
+
+    #[test]
+    fn missing_entries_are_an_error() {
+        let found = HashMap::from([("a.jpg".to_string(), vec![1])]);
+
+        let error = order_entries("CBR", &["a.jpg", "missing.jpg"], found).unwrap_err();
+
+        assert!(error.contains("missing.jpg"), "{error}");
+    }
+}
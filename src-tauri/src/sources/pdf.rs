@@ -0,0 +1,308 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+
+use hayro::{render, InterpreterSettings, Pdf, RenderSettings};
+use image::codecs::png::PngEncoder;
+use image::{DynamicImage, ExtendedColorType, ImageEncoder, RgbImage};
+use lopdf::{Dictionary, Document, Object, ObjectId, Stream};
+
+use super::{ComicSource, PageLocator, SourcePage};
+
+/// Scale used when the reader requests a full page (2.0 = 144 dpi).
+pub const PDF_RENDER_SCALE: f32 = 2.0;
+/// Scale used for thumbnail sources; 72 dpi is already larger than any thumbnail.
+const PDF_PREVIEW_SCALE: f32 = 1.0;
+const PDF_MAX_RENDER_SCALE: f32 = 8.0;
+/// Content stream operators that may surround the single image of a scanned
+/// page without drawing anything themselves.
+const IMAGE_PAGE_OPERATORS: [&str; 8] = ["q", "Q", "cm", "re", "W", "W*", "n", "Do"];
+
+pub struct PdfSource {
+    path: PathBuf,
+    data: OnceLock<Arc<Vec<u8>>>,
+    document: OnceLock<Document>,
+}
+
+impl PdfSource {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            data: OnceLock::new(),
+            document: OnceLock::new(),
+        }
+    }
+
+    /// Reads the document once and shares the bytes between parallel renders.
+    fn data(&self) -> Result<Arc<Vec<u8>>, String> {
+        if let Some(data) = self.data.get() {
+            return Ok(data.clone());
+        }
+        let bytes = fs::read(&self.path).map_err(|e| format!("Failed to read pdf: {e}"))?;
+        Ok(self.data.get_or_init(|| Arc::new(bytes)).clone())
+    }
+
+    fn document(&self) -> Result<&Document, String> {
+        if let Some(document) = self.document.get() {
+            return Ok(document);
+        }
+        let data = self.data()?;
+        let document = Document::load_mem(&data).map_err(|e| format!("Failed to parse pdf document: {e}"))?;
+        Ok(self.document.get_or_init(|| document))
+    }
+
+    pub fn page_count(&self) -> Result<i64, String> {
+        Ok(self.document()?.get_pages().len() as i64)
+    }
+
+    /// Returns the page image without rasterizing when the page consists of
+    /// nothing but one embedded JPEG or Flate image, which is how almost all
+    /// scanned comics are stored. JPEG data is passed through unchanged and
+    /// Flate pixels are wrapped losslessly into a PNG.
+    ///
+    /// JPEG 2000 (JPX) images are detected as well but still go through the
+    /// renderer, since neither the `image` crate nor most webviews decode them.
+    pub fn extract_page_image(&self, page_number: i64) -> Result<Option<Vec<u8>>, String> {
+        let document = self.document()?;
+        let page_id = u32::try_from(page_number)
+            .ok()
+            .and_then(|number| document.get_pages().get(&number).copied())
+            .ok_or_else(|| format!("PDF page {} not found in {}", page_number, self.path.display()))?;
+
+        let Some(stream) = single_page_image(document, page_id) else {
+            return Ok(None);
+        };
+        if is_masked_image(&stream.dict) {
+            return Ok(None);
+        }
+
+        let filters = stream.filters().unwrap_or_default();
+        let components = stream
+            .dict
+            .get_deref(b"ColorSpace", document)
+            .ok()
+            .and_then(|color_space| color_space_components(document, color_space));
+
+        match (filters.as_slice(), components) {
+            ([b"DCTDecode"], Some(1 | 3)) => Ok(Some(stream.content.clone())),
+            ([] | [b"FlateDecode"], Some(components @ (1 | 3))) => Ok(flate_image_to_png(stream, components)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Embedded page image if available, otherwise the page rendered at `scale`.
+    fn page_bytes(&self, page_number: i64, scale: f32) -> Result<Vec<u8>, String> {
+        match self.extract_page_image(page_number)? {
+            Some(bytes) => Ok(bytes),
+            None => self.render_page(page_number, scale),
+        }
+    }
+
+    /// Rasterizes a 1-based page and returns it as PNG bytes on a white
+    /// background.
+    pub fn render_page(&self, page_number: i64, scale: f32) -> Result<Vec<u8>, String> {
+        if !(scale > 0.0 && scale <= PDF_MAX_RENDER_SCALE) {
+            return Err(format!("Invalid pdf render scale {}", scale));
+        }
+        let pdf = Pdf::new(self.data()?)
+            .map_err(|e| format!("Failed to parse pdf document {}: {e:?}", self.path.display()))?;
+        let page = usize::try_from(page_number - 1)
+            .ok()
+            .and_then(|index| pdf.pages().get(index))
+            .ok_or_else(|| format!("PDF page {} not found in {}", page_number, self.path.display()))?;
+
+        let render_settings = RenderSettings {
+            x_scale: scale,
+            y_scale: scale,
+            ..Default::default()
+        };
+        let pixmap = render(page, &InterpreterSettings::default(), &render_settings);
+        let rendered = image::load_from_memory(&pixmap.take_png())
+            .map_err(|e| format!("Failed to decode rendered pdf page {}: {e}", page_number))?;
+
+        encode_png(&flatten_on_white(rendered))
+    }
+}
+
+/// Pages are rendered with transparency; composite onto white so the result
+/// looks like paper and can be JPEG encoded for thumbnails.
+fn flatten_on_white(image: DynamicImage) -> RgbImage {
+    let rgba = image.to_rgba8();
+    RgbImage::from_fn(rgba.width(), rgba.height(), |x, y| {
+        let [r, g, b, a] = rgba.get_pixel(x, y).0;
+        let alpha = a as u32;
+        let blend = |channel: u8| ((channel as u32 * alpha + 255 * (255 - alpha)) / 255) as u8;
+        image::Rgb([blend(r), blend(g), blend(b)])
+    })
+}
+
+fn encode_png(image: &RgbImage) -> Result<Vec<u8>, String> {
+    let mut encoded = Vec::new();
+    PngEncoder::new(&mut encoded)
+        .write_image(image.as_raw(), image.width(), image.height(), ExtendedColorType::Rgb8)
+        .map_err(|e| format!("Failed to encode rendered pdf page: {e}"))?;
+    Ok(encoded)
+}
+
+/// Finds the image XObject of a page whose content stream does nothing but
+/// place exactly one image (optionally clipped and transformed).
+fn single_page_image(document: &Document, page_id: ObjectId) -> Option<&Stream> {
+    let content = document.get_and_decode_page_content(page_id).ok()?;
+    if content
+        .operations
+        .iter()
+        .any(|operation| !IMAGE_PAGE_OPERATORS.contains(&operation.operator.as_str()))
+    {
+        return None;
+    }
+
+    let mut draws = content.operations.iter().filter(|operation| operation.operator == "Do");
+    let name = draws.next()?.operands.first()?.as_name().ok()?;
+    if draws.next().is_some() {
+        return None;
+    }
+
+    let stream = find_xobject(document, page_id, name)?;
+    let is_image = stream
+        .dict
+        .get(b"Subtype")
+        .and_then(Object::as_name)
+        .map(|subtype| subtype == b"Image")
+        .unwrap_or(false);
+    is_image.then_some(stream)
+}
+
+fn find_xobject<'a>(document: &'a Document, page_id: ObjectId, name: &[u8]) -> Option<&'a Stream> {
+    let (page_resources, inherited_ids) = document.get_page_resources(page_id).ok()?;
+    let inherited = inherited_ids
+        .into_iter()
+        .filter_map(|id| document.get_dictionary(id).ok());
+
+    page_resources.into_iter().chain(inherited).find_map(|resources| {
+        resources
+            .get_deref(b"XObject", document)
+            .and_then(Object::as_dict)
+            .and_then(|xobjects| xobjects.get_deref(name, document))
+            .and_then(Object::as_stream)
+            .ok()
+    })
+}
+
+/// Images with transparency, stencil masks or inverted decode ranges only look
+/// right when composited by the renderer.
+fn is_masked_image(dict: &Dictionary) -> bool {
+    let image_mask = dict
+        .get(b"ImageMask")
+        .and_then(Object::as_bool)
+        .unwrap_or(false);
+    image_mask || dict.has(b"SMask") || dict.has(b"Mask") || dict.has(b"Decode")
+}
+
+fn color_space_components(document: &Document, color_space: &Object) -> Option<u8> {
+    if let Ok(name) = color_space.as_name() {
+        return match name {
+            b"DeviceGray" | b"CalGray" => Some(1),
+            b"DeviceRGB" | b"CalRGB" => Some(3),
+            b"DeviceCMYK" => Some(4),
+            _ => None,
+        };
+    }
+
+    let array = color_space.as_array().ok()?;
+    match array.first()?.as_name().ok()? {
+        b"ICCBased" => {
+            let (_, profile) = document.dereference(array.get(1)?).ok()?;
+            let components = profile.as_stream().ok()?.dict.get(b"N").and_then(Object::as_i64).ok()?;
+            u8::try_from(components).ok()
+        }
+        b"CalGray" => Some(1),
+        b"CalRGB" => Some(3),
+        _ => None,
+    }
+}
+
+fn flate_image_to_png(stream: &Stream, components: u8) -> Option<Vec<u8>> {
+    let dict = &stream.dict;
+    let width = u32::try_from(dict.get(b"Width").and_then(Object::as_i64).ok()?).ok()?;
+    let height = u32::try_from(dict.get(b"Height").and_then(Object::as_i64).ok()?).ok()?;
+    let bits = dict.get(b"BitsPerComponent").and_then(Object::as_i64).ok()?;
+    if bits != 8 {
+        return None;
+    }
+
+    let pixels = stream.get_plain_content().ok()?;
+    let expected_len = width as usize * height as usize * components as usize;
+    if pixels.len() < expected_len {
+        return None;
+    }
+
+    let color_type = if components == 1 {
+        ExtendedColorType::L8
+    } else {
+        ExtendedColorType::Rgb8
+    };
+    let mut encoded = Vec::new();
+    PngEncoder::new(&mut encoded)
+        .write_image(&pixels[..expected_len], width, height, color_type)
+        .ok()?;
+    Some(encoded)
+}
+
+fn pdf_page_numbers(format: &str, locators: &[PageLocator]) -> Result<Vec<i64>, String> {
+    locators
+        .iter()
+        .map(|locator| match locator {
+            PageLocator::PdfPage(page_number) => Ok(*page_number),
+            other => Err(format!("{} sources cannot read page {:?}", format, other)),
+        })
+        .collect()
+}
+
+impl ComicSource for PdfSource {
+    fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn format(&self) -> &'static str {
+        "PDF"
+    }
+
+    fn source_type(&self) -> &'static str {
+        "pdf"
+    }
+
+    /// Pages carry no checksum of their own, so they are fingerprinted by
+    /// the size and modification time of the whole document.
+    fn list_pages(&self) -> Result<Vec<SourcePage>, String> {
+        let metadata = self.metadata()?;
+        Ok((1..=self.page_count()?)
+            .map(|page_number| SourcePage {
+                page_number,
+                file_name: format!("page-{}.pdf", page_number),
+                locator: PageLocator::PdfPage(page_number),
+                fingerprint: format!(
+                    "pdf-page-{}:{}:{:x}",
+                    page_number,
+                    metadata.file_size,
+                    metadata.modified_ms.unwrap_or_default()
+                ),
+            })
+            .collect())
+    }
+
+    fn read_pages(&self, locators: &[PageLocator]) -> Result<Vec<Vec<u8>>, String> {
+        pdf_page_numbers(self.format(), locators)?
+            .into_iter()
+            .map(|page_number| self.page_bytes(page_number, PDF_RENDER_SCALE))
+            .collect()
+    }
+
+    fn read_page_preview(&self, locator: &PageLocator) -> Result<Vec<u8>, String> {
+        let page_number = pdf_page_numbers(self.format(), std::slice::from_ref(locator))?[0];
+        self.page_bytes(page_number, PDF_PREVIEW_SCALE)
+    }
+
+    fn random_access(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,165 @@
+//! Detection of the kind of storage an index path lives on, and the I/O
+//! concurrency indexing uses for it.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Condvar, Mutex};
+
+use serde::Serialize;
+
+use crate::normalize_path_string;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StorageKind {
+    SolidState,
+    Rotational,
+    Network,
+}
+
+/// Number of comics read at the same time, split by source kind. Archives and
+/// PDFs are read as one long stream, so on spinning disks a second concurrent
+/// reader mostly adds seeks; image folders are many small reads either way.
+#[derive(Clone, Copy, Debug)]
+pub struct IoConcurrency {
+    pub files: usize,
+    pub folders: usize,
+}
+
+impl IoConcurrency {
+    pub fn for_storage(kind: StorageKind) -> Self {
+        match kind {
+            StorageKind::SolidState => {
+                let parallelism = num_cpus::get().max(2);
+                Self {
+                    files: parallelism,
+                    folders: parallelism,
+                }
+            }
+            StorageKind::Rotational => Self { files: 1, folders: 2 },
+            // Latency rather than seeking dominates on network shares.
+            StorageKind::Network => Self { files: 4, folders: 4 },
+        }
+    }
+
+    pub fn max(&self) -> usize {
+        self.files.max(self.folders)
+    }
+}
+
+/// Counting semaphore bounding how many workers read from the disk at once.
+pub struct IoPermits {
+    available: Mutex<usize>,
+    released: Condvar,
+}
+
+pub struct IoPermit<'a> {
+    permits: &'a IoPermits,
+}
+
+impl IoPermits {
+    pub fn new(count: usize) -> Self {
+        Self {
+            available: Mutex::new(count.max(1)),
+            released: Condvar::new(),
+        }
+    }
+
+    pub fn acquire(&self) -> IoPermit<'_> {
+        let mut available = self.available.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        while *available == 0 {
+            available = self
+                .released
+                .wait(available)
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+        }
+        *available -= 1;
+        IoPermit { permits: self }
+    }
+}
+
+impl Drop for IoPermit<'_> {
+    fn drop(&mut self) {
+        *self
+            .permits
+            .available
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) += 1;
+        self.permits.released.notify_one();
+    }
+}
+
+pub fn detect_storage_kind(path: &Path) -> StorageKind {
+    if is_network_path(path) {
+        StorageKind::Network
+    } else if is_rotational(path) {
+        StorageKind::Rotational
+    } else {
+        StorageKind::SolidState
+    }
+}
+
+/// Detects paths on network shares, where native notifications only report
+/// local changes and reads are latency bound.
+pub fn is_network_path(path: &Path) -> bool {
+    let normalized = normalize_path_string(&path.to_string_lossy());
+    if normalized.starts_with("//") {
+        return true;
+    }
+    network_mount_points().iter().any(|mount_point| path.starts_with(mount_point))
+}
+
+#[cfg(target_os = "linux")]
+fn network_mount_points() -> Vec<PathBuf> {
+    /// Filesystem types on which native change notifications miss remote changes.
+    const NETWORK_FILESYSTEMS: [&str; 9] = [
+        "nfs", "nfs4", "cifs", "smb3", "smbfs", "afpfs", "9p", "fuse.sshfs", "fuse.rclone",
+    ];
+
+    let Ok(mounts) = std::fs::read_to_string("/proc/mounts") else {
+        return Vec::new();
+    };
+    mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let mount_point = fields.nth(1)?;
+            let fs_type = fields.next()?;
+            NETWORK_FILESYSTEMS
+                .contains(&fs_type)
+                .then(|| PathBuf::from(mount_point.replace("\\040", " ")))
+        })
+        .collect()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn network_mount_points() -> Vec<PathBuf> {
+    Vec::new()
+}
+
+/// Looks up the block device holding `path` in sysfs. Partitions have no queue
+/// of their own, so the parent disk is checked as well. Anything that cannot be
+/// resolved is treated as solid state.
+#[cfg(target_os = "linux")]
+fn is_rotational(path: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    let Ok(metadata) = std::fs::metadata(path) else {
+        return false;
+    };
+    let dev = metadata.dev();
+    let major = ((dev >> 32) & 0xffff_f000) | ((dev >> 8) & 0x0000_0fff);
+    let minor = ((dev >> 12) & 0xffff_ff00) | (dev & 0x0000_00ff);
+    let device_dir = PathBuf::from(format!("/sys/dev/block/{}:{}", major, minor));
+    [
+        device_dir.join("queue/rotational"),
+        device_dir.join("../queue/rotational"),
+    ]
+    .iter()
+    .find_map(|flag| std::fs::read_to_string(flag).ok())
+    .is_some_and(|flag| flag.trim() == "1")
+}
+
+#[cfg(not(target_os = "linux"))]
+fn is_rotational(_path: &Path) -> bool {
+    false
+}
@@ -0,0 +1,272 @@
+//! Size budget of the thumbnail cache. The modification time of a thumbnail
+//! file is its last use: accesses bump it, and when the cache outgrows the
+//! budget the least recently used page thumbnails are evicted first. Covers
+//! are pinned and never evicted. Only the content-addressed page thumbnails
+//! below `pages/` count towards the budget.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime};
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::db::LibraryDatabase;
+use crate::index_state::{defer_retries, oldest_running_since, try_lock_index_state, IndexState};
+use crate::thumbnail_settings::{ThumbnailSettings, ThumbnailTier};
+use crate::{ensure_indexed_thumb_root, normalize_path_string, to_forward_slash_path, INDEXED_PAGES_DIR};
+
+/// Accesses within this interval of the last recorded one are not written to
+/// disk again.
+const TOUCH_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// Includes WebP so thumbnails from earlier versions still count against the
+/// budget and get cleaned up.
+const THUMBNAIL_EXTENSIONS: [&str; 3] = ["jpg", "webp", "png"];
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThumbnailCacheStats {
+    pub size_bytes: u64,
+    pub file_count: usize,
+    pub pinned_bytes: u64,
+    pub budget_bytes: Option<u64>,
+    /// Counters since the app was started.
+    pub hits: u64,
+    pub misses: u64,
+    pub hit_rate: Option<f64>,
+    pub evicted_files: u64,
+    pub evicted_bytes: u64,
+}
+
+/// A thumbnail and its tiers, evicted together.
+#[derive(Default)]
+struct CacheEntry {
+    files: Vec<PathBuf>,
+    bytes: u64,
+    last_used: Option<SystemTime>,
+}
+
+/// Cache statistics.
+#[derive(Default)]
+pub struct ThumbnailCache {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evicted_files: AtomicU64,
+    evicted_bytes: AtomicU64,
+}
+
+impl ThumbnailCache {
+    /// Records the use of an existing thumbnail.
+    pub fn record_hit(&self, path: &Path) {
+        self.hits.fetch_add(1, Ordering::Relaxed);
+        touch(path);
+    }
+
+    /// Records a thumbnail that had to be generated.
+    pub fn record_miss(&self) {
+        self.misses.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn stats(&self, app: &AppHandle, settings: &ThumbnailSettings) -> Result<ThumbnailCacheStats, String> {
+        let entries = collect_entries(&page_thumbnail_root(app)?);
+        let pinned = pinned_thumbnails(app)?;
+        let hits = self.hits.load(Ordering::Relaxed);
+        let misses = self.misses.load(Ordering::Relaxed);
+        Ok(ThumbnailCacheStats {
+            size_bytes: entries.values().map(|entry| entry.bytes).sum(),
+            file_count: entries.values().map(|entry| entry.files.len()).sum(),
+            pinned_bytes: entries
+                .iter()
+                .filter(|(key, _)| pinned.contains(*key))
+                .map(|(_, entry)| entry.bytes)
+                .sum(),
+            budget_bytes: settings.cache_budget_bytes(),
+            hits,
+            misses,
+            hit_rate: (hits + misses > 0).then(|| hits as f64 / (hits + misses) as f64),
+            evicted_files: self.evicted_files.load(Ordering::Relaxed),
+            evicted_bytes: self.evicted_bytes.load(Ordering::Relaxed),
+        })
+    }
+
+    /// Evicts least recently used page thumbnails until the cache fits the
+    /// budget. Pages of evicted thumbnails are marked as having none, so they
+    /// are generated again when needed, and their comics are indexed again by
+    /// the next run. Thumbnails of running indexing work are spared.
+    pub fn enforce_budget(&self, app: &AppHandle, settings: &ThumbnailSettings) -> Result<(), String> {
+        let Some(budget) = settings.cache_budget_bytes() else {
+            return Ok(());
+        };
+        let entries = collect_entries(&page_thumbnail_root(app)?);
+        let mut size: u64 = entries.values().map(|entry| entry.bytes).sum();
+        if size <= budget {
+            return Ok(());
+        }
+
+        let pinned = pinned_thumbnails(app)?;
+        let in_flight_since = in_flight_since();
+        let mut evictable: Vec<(String, CacheEntry)> = entries
+            .into_iter()
+            .filter(|(key, entry)| !pinned.contains(key) && !is_in_flight(entry.last_used, in_flight_since))
+            .collect();
+        evictable.sort_by_key(|(_, entry)| entry.last_used);
+
+        let mut evicted = Vec::new();
+        for (key, entry) in evictable {
+            if size <= budget {
+                break;
+            }
+            size -= entry.bytes;
+            evicted.push((key, entry));
+        }
+
+        let evicted_keys: Vec<String> = evicted.iter().map(|(key, _)| key.clone()).collect();
+        let affected_comics = app.state::<LibraryDatabase>().clear_thumbnail_paths(app, &evicted_keys)?;
+
+        let (mut removed_files, mut removed_bytes) = (0, 0);
+        for (_, entry) in &evicted {
+            for file in &entry.files {
+                match fs::remove_file(file) {
+                    Ok(()) => removed_files += 1,
+                    Err(e) => eprintln!(
+                        "[Thumbnails][Rust] Failed to evict thumbnail {}: {e}",
+                        file.display()
+                    ),
+                }
+            }
+            removed_bytes += entry.bytes;
+        }
+        self.evicted_files.fetch_add(removed_files, Ordering::Relaxed);
+        self.evicted_bytes.fetch_add(removed_bytes, Ordering::Relaxed);
+        println!(
+            "[Thumbnails][Rust] Evicted {} thumbnails ({} bytes) to fit the cache budget of {} bytes",
+            evicted.len(),
+            removed_bytes,
+            budget
+        );
+        retry_comics_next_run(app, &affected_comics)
+    }
+}
+
+/// Root of the content-addressed page thumbnails.
+fn page_thumbnail_root(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(ensure_indexed_thumb_root(app)?.join(INDEXED_PAGES_DIR))
+}
+
+/// Drops the fingerprints of comics that lost thumbnails, so that a quick
+/// run does not skip them as unchanged. Index paths that other work is
+/// running on are not waited for; their comics are marked once that work
+/// saved its state.
+fn retry_comics_next_run(app: &AppHandle, comic_paths: &[String]) -> Result<(), String> {
+    if comic_paths.is_empty() {
+        return Ok(());
+    }
+    for base_path in app.state::<LibraryDatabase>().index_paths(app)? {
+        let comics: Vec<&String> = comic_paths
+            .iter()
+            .filter(|comic_path| Path::new(comic_path.as_str()).starts_with(&base_path))
+            .collect();
+        if comics.is_empty() {
+            continue;
+        }
+        let Some(_state_guard) = try_lock_index_state(&base_path) else {
+            defer_retries(comics.into_iter().cloned());
+            continue;
+        };
+        let mut state = IndexState::load(app, &base_path);
+        let mut changed = false;
+        for comic_path in comics {
+            if state.contains(comic_path) {
+                state.retry_next_run(comic_path.clone());
+                changed = true;
+            }
+        }
+        if changed {
+            state.save(app, &base_path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Thumbnails used since this time may have been written or reused by running
+/// indexing work that has not saved them to the database yet. Uses within
+/// [`TOUCH_INTERVAL`] of the previous one are not recorded, hence the margin.
+pub fn in_flight_since() -> Option<SystemTime> {
+    oldest_running_since().map(|started| started.checked_sub(TOUCH_INTERVAL).unwrap_or(started))
+}
+
+pub fn is_in_flight(last_used: Option<SystemTime>, in_flight_since: Option<SystemTime>) -> bool {
+    match (last_used, in_flight_since) {
+        (Some(last_used), Some(since)) => last_used >= since,
+        // Without a modification time the thumbnail cannot be told apart.
+        (None, Some(_)) => true,
+        (_, None) => false,
+    }
+}
+
+/// Bumps the modification time of a thumbnail, at most once per
+/// [`TOUCH_INTERVAL`].
+fn touch(path: &Path) {
+    let now = SystemTime::now();
+    let recently_used = fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .is_ok_and(|modified| now.duration_since(modified).unwrap_or_default() < TOUCH_INTERVAL);
+    if recently_used {
+        return;
+    }
+    if let Err(e) = fs::File::options()
+        .write(true)
+        .open(path)
+        .and_then(|file| file.set_modified(now))
+    {
+        eprintln!("[Thumbnails][Rust] Failed to update access time of {}: {e}", path.display());
+    }
+}
+
+/// Medium tier paths of the cover thumbnails, with forward slashes.
+fn pinned_thumbnails(app: &AppHandle) -> Result<HashSet<String>, String> {
+    Ok(app
+        .state::<LibraryDatabase>()
+        .cover_thumbnail_paths(app)?
+        .into_iter()
+        .map(|path| normalize_path_string(&path))
+        .collect())
+}
+
+/// All thumbnail files below `pages_root`, grouped by the forward slash path
+/// of their medium tier.
+fn collect_entries(pages_root: &Path) -> HashMap<String, CacheEntry> {
+    let mut entries: HashMap<String, CacheEntry> = HashMap::new();
+    let mut pending_dirs = vec![pages_root.to_path_buf()];
+    while let Some(dir) = pending_dirs.pop() {
+        let Ok(dir_entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for dir_entry in dir_entries.flatten() {
+            let path = dir_entry.path();
+            let Ok(metadata) = dir_entry.metadata() else {
+                continue;
+            };
+            if metadata.is_dir() {
+                pending_dirs.push(path);
+                continue;
+            }
+            let is_thumbnail = path
+                .extension()
+                .and_then(|extension| extension.to_str())
+                .is_some_and(|extension| THUMBNAIL_EXTENSIONS.contains(&extension));
+            if !is_thumbnail {
+                continue;
+            }
+            let entry = entries
+                .entry(to_forward_slash_path(&ThumbnailTier::medium_path(&path)))
+                .or_default();
+            entry.bytes += metadata.len();
+            entry.last_used = entry.last_used.max(metadata.modified().ok());
+            entry.files.push(path);
+        }
+    }
+    entries
+}
@@ -0,0 +1,271 @@
+//! User configurable thumbnail output. The settings are stored next to the
+//! thumbnail cache and are part of every cached thumbnail's key, so changing
+//! them only regenerates thumbnails that were produced with other settings.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::PngEncoder;
+use image::imageops::FilterType;
+use image::{DynamicImage, GenericImageView};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+const SETTINGS_FILE_NAME: &str = "settings.json";
+const MIN_SIZE: u32 = 64;
+const MAX_SIZE: u32 = 2048;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ThumbnailFormat {
+    /// Settings saved while WebP thumbnails were offered switch to JPEG; the
+    /// WebP encoder is lossless only, which made them larger than JPEGs.
+    #[default]
+    #[serde(alias = "webp")]
+    Jpeg,
+    Png,
+}
+
+impl ThumbnailFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Jpeg => "jpg",
+            Self::Png => "png",
+        }
+    }
+}
+
+/// Sizes every thumbnail is generated in, relative to the configured size.
+/// The medium tier is the path stored in the database, the other tiers are
+/// stored next to it as `{stem}@{tier}.{ext}`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ThumbnailTier {
+    Small,
+    Medium,
+    Large,
+}
+
+impl ThumbnailTier {
+    /// Ordered from the largest tier, so each tier can be downscaled from the
+    /// previous one.
+    pub const ALL: [Self; 3] = [Self::Large, Self::Medium, Self::Small];
+
+    pub fn max_size(self, settings: &ThumbnailSettings) -> u32 {
+        match self {
+            Self::Small => settings.max_size / 2,
+            Self::Medium => settings.max_size,
+            Self::Large => settings.max_size * 2,
+        }
+    }
+
+    fn suffix(self) -> Option<&'static str> {
+        match self {
+            Self::Small => Some("small"),
+            Self::Medium => None,
+            Self::Large => Some("large"),
+        }
+    }
+
+    /// Path of this tier of the thumbnail stored at `medium_path`.
+    pub fn path(self, medium_path: &Path) -> PathBuf {
+        let Some(suffix) = self.suffix() else {
+            return medium_path.to_path_buf();
+        };
+        let stem = medium_path.file_stem().and_then(|stem| stem.to_str()).unwrap_or_default();
+        let mut file_name = format!("{}@{}", stem, suffix);
+        if let Some(extension) = medium_path.extension().and_then(|extension| extension.to_str()) {
+            file_name.push('.');
+            file_name.push_str(extension);
+        }
+        medium_path.with_file_name(file_name)
+    }
+
+    /// Path of the smallest existing tier of the thumbnail stored at
+    /// `medium_path` whose longest edge covers `display_size` pixels, or of
+    /// the largest existing tier when none does.
+    pub fn path_for_size(medium_path: &Path, display_size: u32, settings: &ThumbnailSettings) -> Option<PathBuf> {
+        let mut tiers = Self::ALL;
+        tiers.sort_by_key(|tier| tier.max_size(settings));
+        let smallest_fitting = tiers
+            .iter()
+            .position(|tier| tier.max_size(settings) >= display_size)
+            .unwrap_or(tiers.len() - 1);
+        tiers[smallest_fitting..]
+            .iter()
+            .chain(tiers[..smallest_fitting].iter().rev())
+            .map(|tier| tier.path(medium_path))
+            .find(|path| path.exists())
+    }
+
+    /// Path of the medium tier that `path` belongs to.
+    pub fn medium_path(path: &Path) -> PathBuf {
+        let Some(file_name) = path.file_name().and_then(|name| name.to_str()) else {
+            return path.to_path_buf();
+        };
+        let Some((stem, rest)) = file_name.split_once('@') else {
+            return path.to_path_buf();
+        };
+        let extension = rest.split_once('.').map(|(_, extension)| extension);
+        match extension {
+            Some(extension) => path.with_file_name(format!("{}.{}", stem, extension)),
+            None => path.with_file_name(stem),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ThumbnailSettings {
+    /// Longest edge of the medium tier in pixels. Smaller images are not
+    /// upscaled.
+    pub max_size: u32,
+    /// JPEG quality from 1 to 100.
+    pub quality: u8,
+    pub format: ThumbnailFormat,
+    /// Size the thumbnail cache is kept under, 0 for no limit.
+    pub cache_budget_mb: u64,
+}
+
+impl Default for ThumbnailSettings {
+    fn default() -> Self {
+        Self {
+            max_size: 300,
+            quality: 80,
+            format: ThumbnailFormat::Jpeg,
+            cache_budget_mb: 1024,
+        }
+    }
+}
+
+impl ThumbnailSettings {
+    fn validate(&self) -> Result<(), String> {
+        if !(MIN_SIZE..=MAX_SIZE).contains(&self.max_size) {
+            return Err(format!(
+                "Thumbnail size must be between {} and {} pixels, got {}",
+                MIN_SIZE, MAX_SIZE, self.max_size
+            ));
+        }
+        if !(1..=100).contains(&self.quality) {
+            return Err(format!("Thumbnail quality must be between 1 and 100, got {}", self.quality));
+        }
+        Ok(())
+    }
+
+    pub fn cache_budget_bytes(&self) -> Option<u64> {
+        (self.cache_budget_mb > 0).then(|| self.cache_budget_mb * 1024 * 1024)
+    }
+
+    /// Whether both settings produce the same thumbnail files.
+    pub fn produces_same_thumbnails(&self, other: &Self) -> bool {
+        self.format == other.format && self.cache_tag() == other.cache_tag()
+    }
+
+    /// Distinguishes thumbnails produced with different settings in cache
+    /// keys. Only settings that affect the output of the format are included.
+    pub fn cache_tag(&self) -> String {
+        match self.format {
+            ThumbnailFormat::Jpeg => format!("{}-q{}", self.max_size, self.quality),
+            ThumbnailFormat::Png => self.max_size.to_string(),
+        }
+    }
+
+    /// Encodes every tier of `image` from the one decoded image, largest tier
+    /// first.
+    pub fn encode_tiers(&self, image: &DynamicImage) -> Result<Vec<(ThumbnailTier, Vec<u8>)>, String> {
+        let mut encoded = Vec::with_capacity(ThumbnailTier::ALL.len());
+        let mut previous: Option<DynamicImage> = None;
+        for tier in ThumbnailTier::ALL {
+            let max_size = tier.max_size(self);
+            let resized = fit_within(previous.as_ref().unwrap_or(image), max_size, max_size);
+            encoded.push((tier, self.encode(&resized)?));
+            previous = Some(resized);
+        }
+        Ok(encoded)
+    }
+
+    fn encode(&self, resized: &DynamicImage) -> Result<Vec<u8>, String> {
+        let mut encoded = Vec::new();
+        match self.format {
+            // JPEG has no alpha channel.
+            ThumbnailFormat::Jpeg => DynamicImage::ImageRgb8(resized.to_rgb8())
+                .write_with_encoder(JpegEncoder::new_with_quality(&mut encoded, self.quality)),
+            ThumbnailFormat::Png => resized.write_with_encoder(PngEncoder::new(&mut encoded)),
+        }
+        .map_err(|e| format!("Failed to encode {} thumbnail: {e}", self.format.extension()))?;
+        Ok(encoded)
+    }
+}
+
+/// Downscales `image` to fit within `max_width` x `max_height`, keeping its
+/// aspect ratio.
+pub fn fit_within(image: &DynamicImage, max_width: u32, max_height: u32) -> DynamicImage {
+    let (width, height) = image.dimensions();
+    let scale = (max_width as f32 / width as f32).min(max_height as f32 / height as f32);
+    if scale >= 1.0 {
+        return image.clone();
+    }
+    let target_w = ((width as f32) * scale).round() as u32;
+    let target_h = ((height as f32) * scale).round() as u32;
+    image.resize(target_w.max(1), target_h.max(1), FilterType::Lanczos3)
+}
+
+/// Current thumbnail settings, loaded from disk on first use.
+#[derive(Default)]
+pub struct ThumbnailConfig {
+    settings: RwLock<Option<ThumbnailSettings>>,
+}
+
+impl ThumbnailConfig {
+    pub fn get(&self, app: &AppHandle) -> ThumbnailSettings {
+        if let Some(settings) = *self.settings.read().unwrap_or_else(|poisoned| poisoned.into_inner()) {
+            return settings;
+        }
+        let mut settings = self.settings.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+        *settings.get_or_insert_with(|| load_settings(app))
+    }
+
+    pub fn set(&self, app: &AppHandle, settings: ThumbnailSettings) -> Result<(), String> {
+        settings.validate()?;
+        let path = settings_file_path(app)?;
+        let bytes = serde_json::to_vec_pretty(&settings)
+            .map_err(|e| format!("Failed to serialize thumbnail settings: {e}"))?;
+        fs::write(&path, bytes)
+            .map_err(|e| format!("Failed to write thumbnail settings {}: {e}", path.display()))?;
+        *self.settings.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(settings);
+        Ok(())
+    }
+}
+
+/// Missing or invalid settings fall back to the defaults.
+fn load_settings(app: &AppHandle) -> ThumbnailSettings {
+    let Ok(path) = settings_file_path(app) else {
+        return ThumbnailSettings::default();
+    };
+    let Ok(bytes) = fs::read(&path) else {
+        return ThumbnailSettings::default();
+    };
+    serde_json::from_slice::<ThumbnailSettings>(&bytes)
+        .map_err(|e| e.to_string())
+        .and_then(|settings| settings.validate().map(|_| settings))
+        .unwrap_or_else(|message| {
+            eprintln!(
+                "[Thumbnails][Rust] Ignoring invalid thumbnail settings {}: {}",
+                path.display(),
+                message
+            );
+            ThumbnailSettings::default()
+        })
+}
+
+fn settings_file_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let thumb_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to resolve app data dir: {e}"))?
+        .join("thumbnails");
+    fs::create_dir_all(&thumb_dir)
+        .map_err(|e| format!("Failed to create thumbnail dir {}: {e}", thumb_dir.display()))?;
+    Ok(thumb_dir.join(SETTINGS_FILE_NAME))
+}
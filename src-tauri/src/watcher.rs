@@ -0,0 +1,235 @@
+//! Live watching of index path roots. Filesystem events are debounced per
+//! root and only the comics affected by the changed paths are re-indexed.
+
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use notify::{Config, Event, EventKind, PollWatcher, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+use crate::comic_info::MetadataPrecedence;
+use crate::storage::is_network_path;
+use crate::{normalize_path_string, reindex_changed_paths_impl, BuildIndexPayloadResult};
+
+pub const LIBRARY_CHANGED_EVENT: &str = "library-changed";
+/// Quiet period after the last event before a batch of changes is indexed.
+const DEBOUNCE_INTERVAL: Duration = Duration::from_secs(2);
+/// Upper bound for delaying a batch while events keep arriving, e.g. during a
+/// large copy into the library.
+const MAX_DEBOUNCE_DELAY: Duration = Duration::from_secs(15);
+const POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchedIndexPath {
+    pub path: String,
+    pub pattern: String,
+    pub metadata_precedence: Option<MetadataPrecedence>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchStatusPayload {
+    path: String,
+    polling: bool,
+    error: Option<String>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LibraryChangedEventPayload {
+    base_path: String,
+    #[serde(flatten)]
+    result: BuildIndexPayloadResult,
+}
+
+struct ActiveWatcher {
+    config: WatchedIndexPath,
+    polling: bool,
+    // Dropping the watcher closes the event channel, which stops the worker.
+    _watcher: Box<dyn Watcher + Send>,
+}
+
+/// Watchers of all index paths, keyed by normalized root path.
+#[derive(Default)]
+pub struct LibraryWatchers {
+    watchers: Mutex<HashMap<String, ActiveWatcher>>,
+}
+
+impl LibraryWatchers {
+    /// Replaces the watched roots with `index_paths`. Roots whose settings did
+    /// not change keep their running watcher.
+    pub fn watch(&self, app: &AppHandle, index_paths: Vec<WatchedIndexPath>) -> Vec<WatchStatusPayload> {
+        let mut watchers = self.watchers.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let requested: HashSet<String> = index_paths
+            .iter()
+            .map(|index_path| normalize_path_string(&index_path.path))
+            .collect();
+        watchers.retain(|path, _| requested.contains(path));
+
+        let mut statuses = Vec::with_capacity(index_paths.len());
+        for index_path in index_paths {
+            let key = normalize_path_string(&index_path.path);
+            if let Some(active) = watchers.get(&key) {
+                if active.config == index_path {
+                    statuses.push(WatchStatusPayload {
+                        path: index_path.path,
+                        polling: active.polling,
+                        error: None,
+                    });
+                    continue;
+                }
+                watchers.remove(&key);
+            }
+
+            match start_watcher(app, index_path.clone()) {
+                Ok(active) => {
+                    statuses.push(WatchStatusPayload {
+                        path: index_path.path,
+                        polling: active.polling,
+                        error: None,
+                    });
+                    watchers.insert(key, active);
+                }
+                Err(message) => {
+                    eprintln!("[Watcher][Rust] {}", message);
+                    statuses.push(WatchStatusPayload {
+                        path: index_path.path,
+                        polling: false,
+                        error: Some(message),
+                    });
+                }
+            }
+        }
+        statuses
+    }
+
+    pub fn unwatch_all(&self) {
+        self.watchers
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clear();
+    }
+}
+
+fn start_watcher(app: &AppHandle, config: WatchedIndexPath) -> Result<ActiveWatcher, String> {
+    let root = PathBuf::from(&config.path);
+    if !root.is_dir() {
+        return Err(format!("Index path is not a readable directory: {}", root.display()));
+    }
+
+    let (sender, receiver) = mpsc::channel();
+    let native = if is_network_path(&root) {
+        Err(format!("{} is on a network filesystem", root.display()))
+    } else {
+        RecommendedWatcher::new(sender.clone(), Config::default())
+            .and_then(|mut watcher| watcher.watch(&root, RecursiveMode::Recursive).map(|_| watcher))
+            .map_err(|e| format!("Failed to watch {}: {e}", root.display()))
+    };
+
+    let (watcher, polling): (Box<dyn Watcher + Send>, bool) = match native {
+        Ok(watcher) => (Box::new(watcher), false),
+        Err(reason) => {
+            println!("[Watcher][Rust] Falling back to polling: {}", reason);
+            let mut watcher =
+                PollWatcher::new(sender, Config::default().with_poll_interval(POLL_INTERVAL))
+                    .map_err(|e| format!("Failed to create polling watcher for {}: {e}", root.display()))?;
+            watcher
+                .watch(&root, RecursiveMode::Recursive)
+                .map_err(|e| format!("Failed to poll {}: {e}", root.display()))?;
+            (Box::new(watcher), true)
+        }
+    };
+
+    println!(
+        "[Watcher][Rust] Watching '{}' ({})",
+        config.path,
+        if polling { "polling" } else { "native" }
+    );
+    let worker_app = app.clone();
+    let worker_config = config.clone();
+    thread::spawn(move || run_worker(worker_app, worker_config, receiver));
+
+    Ok(ActiveWatcher {
+        config,
+        polling,
+        _watcher: watcher,
+    })
+}
+
+/// Collects changed paths until events settle, then re-indexes them. Exits
+/// once the watcher, and with it the sending side of the channel, is dropped.
+fn run_worker(app: AppHandle, config: WatchedIndexPath, receiver: Receiver<notify::Result<Event>>) {
+    let mut pending: HashSet<PathBuf> = HashSet::new();
+    let mut batch_started: Option<Instant> = None;
+
+    loop {
+        let timeout = match batch_started {
+            Some(started) => DEBOUNCE_INTERVAL.min(MAX_DEBOUNCE_DELAY.saturating_sub(started.elapsed())),
+            None => Duration::from_secs(3600),
+        };
+        match receiver.recv_timeout(timeout) {
+            Ok(Ok(event)) => {
+                if matches!(event.kind, EventKind::Access(_)) || event.paths.is_empty() {
+                    continue;
+                }
+                pending.extend(event.paths);
+                batch_started.get_or_insert_with(Instant::now);
+                if batch_started.is_some_and(|started| started.elapsed() < MAX_DEBOUNCE_DELAY) {
+                    continue;
+                }
+            }
+            Ok(Err(e)) => {
+                eprintln!("[Watcher][Rust] Watch error for '{}': {e}", config.path);
+                continue;
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+
+        if pending.is_empty() {
+            continue;
+        }
+        let changed_paths: Vec<PathBuf> = pending.drain().collect();
+        batch_started = None;
+        reindex_changes(&app, &config, &changed_paths);
+    }
+
+    println!("[Watcher][Rust] Stopped watching '{}'", config.path);
+}
+
+fn reindex_changes(app: &AppHandle, config: &WatchedIndexPath, changed_paths: &[PathBuf]) {
+    let result = match reindex_changed_paths_impl(
+        app,
+        &config.path,
+        &config.pattern,
+        config.metadata_precedence.unwrap_or_default(),
+        changed_paths,
+    ) {
+        Ok(result) => result,
+        Err(message) => {
+            eprintln!("[Watcher][Rust] Failed to re-index '{}': {}", config.path, message);
+            return;
+        }
+    };
+
+    if result.added.is_empty()
+        && result.changed.is_empty()
+        && result.removed_comic_paths.is_empty()
+        && result.errors.is_empty()
+    {
+        return;
+    }
+    let payload = LibraryChangedEventPayload {
+        base_path: config.path.clone(),
+        result,
+    };
+    if let Err(e) = app.emit(LIBRARY_CHANGED_EVENT, payload) {
+        eprintln!("[Watcher][Rust] Failed to emit library change event: {e}");
+    }
+}
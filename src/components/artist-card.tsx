@@ -1,6 +1,6 @@
 import React from 'react';
 import type { ArtistMetadata } from '../types/comic';
-import { getImageUrl } from '../utils/image-utils';
+import { getThumbnailUrl, THUMBNAIL_DISPLAY_SIZES } from '../utils/image-utils';
 import { RxPerson } from 'react-icons/rx';
 import { CardItem } from './card-item';
 
@@ -16,7 +16,7 @@ export const ArtistCard: React.FC<ArtistCardProps> = ({ artist, onClick, isFocus
   return (
     <CardItem
       title={artistName}
-      imageUrl={artist.thumbnail_path ? getImageUrl(artist.thumbnail_path) : null}
+      imageUrl={artist.thumbnail_path ? getThumbnailUrl(artist.thumbnail_path, THUMBNAIL_DISPLAY_SIZES.card) : null}
       onOpen={() => onClick(artistName)}
       isFocused={isFocused}
       fallbackIcon={<RxPerson size={48} />}
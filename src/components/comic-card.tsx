@@ -1,6 +1,6 @@
 import React, { useState } from 'react';
 import type { Comic } from '../types/comic';
-import { getImageUrl } from '../utils/image-utils';
+import { getImageUrl, getThumbnailUrl, THUMBNAIL_DISPLAY_SIZES } from '../utils/image-utils';
 import * as comicService from '../services/comic-service';
 import { ComicContextMenu, ComicDropdownMenu } from './comic-context-menu';
 import { CardItem } from './card-item';
@@ -74,7 +74,7 @@ export const ComicCard: React.FC<ComicCardProps> = ({ comic, onOpen, isFocused }
   };
 
   const coverUrl = comic.thumbnail_path 
-    ? getImageUrl(comic.thumbnail_path) 
+    ? getThumbnailUrl(comic.thumbnail_path, THUMBNAIL_DISPLAY_SIZES.card) 
     : (comic.cover_image_path ? getImageUrl(comic.cover_image_path) : '');
 
   const isIndexing = comic.indexing_status === 'pending' || comic.indexing_status === 'processing';
@@ -1,6 +1,6 @@
 import React from 'react';
 import { RxArchive } from 'react-icons/rx';
-import { getImageUrl } from '../utils/image-utils';
+import { getThumbnailUrl, THUMBNAIL_DISPLAY_SIZES } from '../utils/image-utils';
 import { CardItem } from './card-item';
 import { NavigationContextMenu } from './navigation-context-menu';
 
@@ -21,7 +21,7 @@ export const FolderCard: React.FC<FolderCardProps> = ({
   onClick,
   isFocused
 }) => {
-  const coverUrl = thumbnailPath ? getImageUrl(thumbnailPath) : '';
+  const coverUrl = thumbnailPath ? getThumbnailUrl(thumbnailPath, THUMBNAIL_DISPLAY_SIZES.card) : '';
 
   return (
     <CardItem
@@ -3,7 +3,7 @@ import { useIndexing } from '../contexts/indexing-context';
 import { RxReload, RxExclamationTriangle, RxCheckCircled, RxCross2 } from 'react-icons/rx';
 
 export const IndexingStatus: React.FC = () => {
-    const { isIndexing, progress, startIndexing, lastIndexedAt, errors, clearErrors } = useIndexing();
+    const { isIndexing, progress, savedComics, startIndexing, lastIndexedAt, errors, clearErrors } = useIndexing();
     const [showErrors, setShowErrors] = useState(false);
 
     if (!isIndexing && errors.length === 0) {
@@ -57,6 +57,11 @@ export const IndexingStatus: React.FC = () => {
                     <div className="text-[10px] text-gray-500 truncate" title={progress.currentTask || ''}>
                         {progress.currentTask || 'Working...'}
                     </div>
+                    {savedComics > 0 && (
+                        <div className="text-[10px] text-gray-500">
+                            {savedComics} comic{savedComics > 1 ? 's' : ''} updated
+                        </div>
+                    )}
                     <div className="h-1 w-full bg-gray-200 dark:bg-gray-700 rounded-full overflow-hidden">
                         <div 
                             className="h-full bg-blue-500 transition-all duration-300"
@@ -21,7 +21,7 @@ import { useRecentlyViewed } from '../hooks/use-recently-viewed'
 import { useOpenComic } from '../hooks/use-open-comic'
 import { useOpenComicPage } from '../hooks/use-open-comic-page'
 import { ComicContextMenu } from './comic-context-menu'
-import { getImageUrl } from '../utils/image-utils'
+import { getThumbnailUrl, THUMBNAIL_DISPLAY_SIZES } from '../utils/image-utils'
 import * as indexPathService from '../services/index-path-service'
 import { open } from '@tauri-apps/plugin-dialog'
 import type { Comic } from '../types/comic'
@@ -38,7 +38,7 @@ const SmallCard: FC<{
   page?: RecentlyViewedPage
   subBadge?: string
 }> = ({ title, thumbnail, isActive, onClick, onUpdate, comic, page, subBadge }) => {
-  const coverUrl = thumbnail ? getImageUrl(thumbnail) : null
+  const coverUrl = thumbnail ? getThumbnailUrl(thumbnail, THUMBNAIL_DISPLAY_SIZES.sidebar) : null
 
   const content = (
     <button
@@ -1,6 +1,6 @@
 import React, { useState } from 'react';
 import { RxEyeOpen, RxEyeClosed, RxLayers } from 'react-icons/rx';
-import { getImageUrl } from '../utils/image-utils';
+import { getImageUrl, getThumbnailUrl, THUMBNAIL_DISPLAY_SIZES } from '../utils/image-utils';
 import { ComicContextMenu, ComicDropdownMenu } from './comic-context-menu';
 import { FavoriteButton } from './favorite-button';
 import * as comicPageService from '../services/comic-page-service';
@@ -22,7 +22,7 @@ export const PageCard: React.FC<PageCardProps> = ({ page, onOpen, onUpdate, onAd
   const [isViewed, setIsViewed] = useState(page.last_opened_at !== null);
   const [viewCount, setViewCount] = useState(page.view_count);
 
-  const thumbUrl = page.thumbnail_path ? getImageUrl(page.thumbnail_path) : getImageUrl(page.file_path);
+  const thumbUrl = page.thumbnail_path ? getThumbnailUrl(page.thumbnail_path, THUMBNAIL_DISPLAY_SIZES.card) : getImageUrl(page.file_path);
   
   const handleToggleFavorite = async (e?: React.MouseEvent) => {
     e?.stopPropagation();
@@ -4,11 +4,13 @@ import { FavoriteButton } from '../favorite-button';
 import { RxStarFilled, RxLayers, RxCross2 } from 'react-icons/rx';
 import { ComicContextMenu } from '../comic-context-menu';
 import { RenderedPageImage } from './rendered-page-image';
+import type { PageProcessing } from '../../utils/image-utils';
 
 type LazyPageProps = {
   page: ComicPage;
   zoomLevel: number;
   fitMode: 'width' | 'both' | 'none';
+  processing?: PageProcessing;
   onVisible?: (pageNumber: number) => void;
   isFavorite?: boolean;
   isViewed?: boolean;
@@ -23,7 +25,7 @@ type LazyPageProps = {
 };
 
 export const LazyPage: React.ForwardRefExoticComponent<LazyPageProps & React.RefAttributes<HTMLDivElement>> = React.forwardRef<HTMLDivElement, LazyPageProps>(
-  ({ page, zoomLevel, fitMode, onVisible, isFavorite, isViewed, onToggleFavorite, onToggleViewed, onIncrementViewCount, onDecrementViewCount, isGallery, onRemoveFromGallery, onAddToGallery, enableGalleries }, ref) => {
+  ({ page, zoomLevel, fitMode, processing, onVisible, isFavorite, isViewed, onToggleFavorite, onToggleViewed, onIncrementViewCount, onDecrementViewCount, isGallery, onRemoveFromGallery, onAddToGallery, enableGalleries }, ref) => {
     const [isVisible, setIsVisible] = useState(false);
     const [naturalSize, setNaturalSize] = useState<{ width: number; height: number } | null>(null);
     const internalRef = useRef<HTMLDivElement>(null);
@@ -145,6 +147,7 @@ export const LazyPage: React.ForwardRefExoticComponent<LazyPageProps & React.Ref
               page={page}
               alt={`Page ${page.page_number}`}
               className="block"
+              processing={processing}
               onLoad={handleLoad}
               style={{
                 width: isFitWidth 
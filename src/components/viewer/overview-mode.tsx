@@ -5,6 +5,7 @@ import { useTabs } from '../../contexts/tab-context';
 import { Tab } from '../../stores/tab-store';
 import { GridView } from '../grid-view';
 import { useSettings } from '../../contexts/settings-context';
+import { THUMBNAIL_DISPLAY_SIZES } from '../../utils/image-utils';
 
 type OverviewModeProps = {
   comic: Comic;
@@ -82,6 +83,7 @@ export const OverviewMode: React.FC<OverviewModeProps> = ({
             onRemoveFromGallery={() => onRemoveFromGallery?.(page.id)}
             onAddToGallery={() => onAddToGallery?.(page.id)}
             enableGalleries={settings.enableGalleries}
+            thumbnailSize={THUMBNAIL_DISPLAY_SIZES.overview * (gridSize / 100)}
           />
         )}
       />
@@ -1,15 +1,17 @@
 import React, { RefObject, useState, useLayoutEffect } from 'react';
 import { ComicPage } from '../../types/comic';
 import { RenderedPageImage } from './rendered-page-image';
+import type { PageProcessing } from '../../utils/image-utils';
 
 type PageImageProps = {
   page: ComicPage;
   zoomLevel: number; // percentage: 100 = natural size
   fitMode: 'width' | 'both' | 'none';
   containerRef?: RefObject<HTMLDivElement | null>;
+  processing?: PageProcessing;
 };
 
-export const PageImage: React.FC<PageImageProps> = ({ page, zoomLevel, fitMode, containerRef }) => {
+export const PageImage: React.FC<PageImageProps> = ({ page, zoomLevel, fitMode, containerRef, processing }) => {
   const [naturalSize, setNaturalSize] = useState<{ width: number; height: number } | null>(null);
 
   // Reset scroll position when page changes
@@ -39,6 +41,7 @@ export const PageImage: React.FC<PageImageProps> = ({ page, zoomLevel, fitMode,
         page={page}
         alt={`Page ${page.page_number}`}
         className="max-sm:w-full"
+        processing={processing}
         onLoad={handleLoad}
         style={{
           width: isFitWidth 
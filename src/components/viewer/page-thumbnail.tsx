@@ -18,6 +18,8 @@ type PageThumbnailProps = {
   onRemoveFromGallery?: () => void;
   onAddToGallery?: () => void;
   enableGalleries?: boolean;
+  /** Longest edge in CSS pixels the thumbnail is shown at. */
+  thumbnailSize?: number;
 };
 
 export const PageThumbnail: React.FC<PageThumbnailProps> = ({ 
@@ -31,7 +33,8 @@ export const PageThumbnail: React.FC<PageThumbnailProps> = ({
   isGallery,
   onRemoveFromGallery,
   onAddToGallery,
-  enableGalleries
+  enableGalleries,
+  thumbnailSize
 }) => {
   const isViewed = !!page.last_opened_at;
 
@@ -59,6 +62,7 @@ export const PageThumbnail: React.FC<PageThumbnailProps> = ({
           alt={`Page ${page.page_number}`}
           className="w-full h-auto object-contain aspect-3/4 bg-gray-100 dark:bg-gray-800"
           preferThumbnail
+          thumbnailSize={thumbnailSize}
         />
         
         {/* Page number badge */}
@@ -2,6 +2,7 @@ import React, { useEffect, useState } from 'react';
 import type { CSSProperties } from 'react';
 import { ComicPage } from '../../types/comic';
 import { resolvePageImageUrl, resolvePagePreviewUrl } from '../../services/page-source-utils';
+import type { PageProcessing } from '../../utils/image-utils';
 
 type RenderedPageImageProps = {
   page: ComicPage;
@@ -9,6 +10,10 @@ type RenderedPageImageProps = {
   className?: string;
   style?: CSSProperties;
   preferThumbnail?: boolean;
+  /** Longest edge in CSS pixels a thumbnail is shown at, to pick its tier. */
+  thumbnailSize?: number;
+  /** Downscaling and cropping of the page; ignored for thumbnails. */
+  processing?: PageProcessing;
   onLoad?: (e: React.SyntheticEvent<HTMLImageElement>) => void;
 };
 
@@ -18,6 +23,8 @@ export const RenderedPageImage: React.FC<RenderedPageImageProps> = ({
   className,
   style,
   preferThumbnail = false,
+  thumbnailSize,
+  processing,
   onLoad,
 }) => {
   const [src, setSrc] = useState<string>('');
@@ -26,8 +33,8 @@ export const RenderedPageImage: React.FC<RenderedPageImageProps> = ({
     let active = true;
     const load = async () => {
       const nextSrc = preferThumbnail
-        ? await resolvePagePreviewUrl(page)
-        : await resolvePageImageUrl(page, false);
+        ? await resolvePagePreviewUrl(page, thumbnailSize)
+        : await resolvePageImageUrl(page, false, processing);
       if (active) {
         setSrc(nextSrc);
       }
@@ -42,7 +49,7 @@ export const RenderedPageImage: React.FC<RenderedPageImageProps> = ({
     return () => {
       active = false;
     };
-  }, [page, preferThumbnail]);
+  }, [page, preferThumbnail, thumbnailSize, processing]);
 
   if (!src) {
     return <div className={className} style={style} />;
@@ -38,6 +38,9 @@ vi.mock('./viewer-sidebar', () => ({
 // Mock image utils
 vi.mock('../../utils/image-utils', () => ({
   getImageUrl: (path: string) => `asset://${path}`,
+  getThumbnailUrl: (path: string) => `comic://${path}?thumbnail=1`,
+  getComicPageUrl: (path: string, pageNumber: number) => `comic://${path}?page=${pageNumber}`,
+  getPageProcessing: () => undefined,
 }));
 
 describe('ScrollMode', () => {
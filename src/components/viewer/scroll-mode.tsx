@@ -1,4 +1,4 @@
-import React, { useRef, useEffect, useState } from 'react';
+import React, { useRef, useEffect, useState, useMemo } from 'react';
 import { Comic, ComicPage } from '../../types/comic';
 import { useTabs } from '../../contexts/tab-context';
 import { Tab } from '../../stores/tab-store';
@@ -7,8 +7,10 @@ import { LazyPage } from './lazy-page';
 import { ScrollPageIndicator } from './scroll-page-indicator';
 import { ViewerSidebar } from './viewer-sidebar';
 import { useAutoScroll } from '../../hooks/use-auto-scroll';
+import { usePrefetchPages } from '../../hooks/use-prefetch-pages';
 import { useSettings } from '../../contexts/settings-context';
 import { useViewerRef } from '../../contexts/viewer-ref-context';
+import { getPageProcessing } from '../../utils/image-utils';
 
 type ScrollModeProps = {
   comic: Comic;
@@ -43,6 +45,11 @@ export const ScrollMode: React.FC<ScrollModeProps> = ({
   const fitMode = activeTab?.fitMode ?? settings.defaultFitMode ?? 'width';
   const viewMode = activeTab?.viewMode ?? 'scroll';
   const isSidebarCollapsed = activeTab?.sidebarCollapsed ?? false;
+  const { cropBorders, cropTolerance, cropMinContent } = settings;
+  const processing = useMemo(
+    () => getPageProcessing(fitMode, { cropBorders, cropTolerance, cropMinContent }),
+    [fitMode, cropBorders, cropTolerance, cropMinContent]
+  );
   
   const [isScrolling, setIsScrolling] = useState(false);
   const scrollTimeoutRef = useRef<number | null>(null);
@@ -116,6 +123,9 @@ export const ScrollMode: React.FC<ScrollModeProps> = ({
     }
   );
 
+  // Read the pages below the visible one ahead of scrolling
+  usePrefetchPages(pages, currentPage, 'scroll', processing);
+
   // Initial scroll to current page
   useEffect(() => {
     const scrollToInitialPage = () => {
@@ -180,6 +190,7 @@ export const ScrollMode: React.FC<ScrollModeProps> = ({
               page={page}
               zoomLevel={zoomLevel}
               fitMode={fitMode}
+              processing={processing}
               isFavorite={page.is_favorite === 1}
               isViewed={!!page.last_opened_at}
               onToggleFavorite={() => onTogglePageFavorite(page.id)}
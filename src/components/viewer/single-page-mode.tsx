@@ -6,9 +6,11 @@ import { ViewerSidebar } from './viewer-sidebar';
 import { useTabs } from '../../contexts/tab-context';
 import { Tab } from '../../stores/tab-store';
 import { usePreloadImages } from '../../hooks/use-preload-images';
+import { usePrefetchPages } from '../../hooks/use-prefetch-pages';
 import { useSlideshowScroll } from '../../hooks/use-slideshow-scroll';
 import { useSettings } from '../../contexts/settings-context';
 import { useViewerRef } from '../../contexts/viewer-ref-context';
+import { getPageProcessing } from '../../utils/image-utils';
 
 type SinglePageModeProps = {
   comic: Comic;
@@ -45,6 +47,11 @@ export const SinglePageMode: React.FC<SinglePageModeProps> = ({
   const fitMode = activeTab?.fitMode ?? settings.defaultFitMode ?? 'width';
   const viewMode = activeTab?.viewMode ?? 'single';
   const isSidebarCollapsed = activeTab?.sidebarCollapsed ?? false;
+  const { cropBorders, cropTolerance, cropMinContent } = settings;
+  const processing = React.useMemo(
+    () => getPageProcessing(fitMode, { cropBorders, cropTolerance, cropMinContent }),
+    [fitMode, cropBorders, cropTolerance, cropMinContent]
+  );
   
   const { scrollContainerRef: containerRef, registerNextPage, registerPrevPage } = useViewerRef();
 
@@ -59,8 +66,9 @@ export const SinglePageMode: React.FC<SinglePageModeProps> = ({
   }, [registerNextPage, registerPrevPage, currentPage, pages.length]);
 
   // Preload adjacent images
-  usePreloadImages(pages, currentPage);
-  usePreloadImages(pages, currentPage);
+  usePreloadImages(pages, currentPage, 3, processing);
+  usePreloadImages(pages, currentPage, 3, processing);
+  usePrefetchPages(pages, currentPage, 'single', processing);
 
   // Slideshow scroll logic
   useSlideshowScroll({
@@ -135,6 +143,7 @@ export const SinglePageMode: React.FC<SinglePageModeProps> = ({
             zoomLevel={zoomLevel} 
             fitMode={fitMode}
             containerRef={containerRef}
+            processing={processing}
           />
         </div>
         {isSidebarCollapsed && (
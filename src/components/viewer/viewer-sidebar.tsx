@@ -11,6 +11,7 @@ import { FavoriteButton } from '../favorite-button';
 import { ViewCounter } from '../view-counter';
 import { ComicContextMenu } from '../comic-context-menu';
 import { useSettings } from '../../contexts/settings-context';
+import { THUMBNAIL_DISPLAY_SIZES } from '../../utils/image-utils';
 
 type ViewerSidebarProps = {
   pages: ComicPage[];
@@ -302,6 +303,7 @@ export const ViewerSidebar: React.FC<ViewerSidebarProps> = ({
                     alt={`Page ${page.page_number}`}
                     className="w-full h-full object-cover transition-all"
                     preferThumbnail
+                    thumbnailSize={THUMBNAIL_DISPLAY_SIZES.sidebar}
                   />
                   <div className="absolute inset-x-0 bottom-0 bg-black/60 text-white py-0.5 text-[10px] font-bold text-center">
                     {page.page_number}
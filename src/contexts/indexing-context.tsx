@@ -1,11 +1,21 @@
 import React, { createContext, useContext, useState, useCallback, useEffect, useRef } from 'react';
-import { GlobalIndexingProgress, IndexingError, reindexAll } from '../services/indexing-service';
+import { fillPageThumbnails, GlobalIndexingProgress, IndexingCancelledError, IndexingError, reindexAll } from '../services/indexing-service';
+import { cancelIndexingJob, pauseIndexingJob, resumeIndexingJob } from '../services/source-file-service';
 import { useSettings } from './settings-context';
+import { useLibraryWatcher } from '../hooks/use-library-watcher';
 
 type IndexingContextType = {
   isIndexing: boolean;
   progress: GlobalIndexingProgress | null;
+  /** Comics saved to the library by the running indexing, as they stream in. */
+  savedComics: number;
   startIndexing: (mode?: 'quick' | 'full') => Promise<void>;
+  /** Generates the page thumbnails that cover-only indexing deferred. */
+  fillThumbnails: () => Promise<void>;
+  isPaused: boolean;
+  pauseIndexing: () => Promise<void>;
+  resumeIndexing: () => Promise<void>;
+  cancelIndexing: () => Promise<void>;
   lastIndexedAt: string | null;
   errors: IndexingError[];
   clearErrors: () => void;
@@ -18,8 +28,14 @@ export const IndexingProvider: React.FC<{ children: React.ReactNode }> = ({ chil
   const [isIndexing, setIsIndexing] = useState(false);
   const [progress, setProgress] = useState<GlobalIndexingProgress | null>(null);
   const [errors, setErrors] = useState<IndexingError[]>([]);
+  const [savedComics, setSavedComics] = useState(0);
+  const [isPaused, setIsPaused] = useState(false);
+  const jobIdRef = useRef<number | null>(null);
+  const cancelledRef = useRef(false);
   const hasTriggeredInitial = useRef(false);
 
+  useLibraryWatcher(!settingsLoading);
+
   const clearErrors = useCallback(() => {
     setErrors([]);
   }, []);
@@ -30,22 +46,88 @@ export const IndexingProvider: React.FC<{ children: React.ReactNode }> = ({ chil
     setIsIndexing(true);
     setProgress(null);
     setErrors([]); // Clear old errors when starting new indexing
+    setSavedComics(0);
+    setIsPaused(false);
+    cancelledRef.current = false;
     try {
       await reindexAll(mode, (p) => {
         setProgress(p);
         if (p.errors && p.errors.length > 0) {
             setErrors(p.errors);
         }
+      }, {
+        onJobStarted: (jobId) => {
+          jobIdRef.current = jobId;
+          // A cancel before the job of the next index path started.
+          if (cancelledRef.current) {
+            cancelIndexingJob(jobId).catch(console.error);
+          }
+        },
+        onComic: () => setSavedComics((count) => count + 1),
+        coverOnly: settings.coverOnlyThumbnails,
       });
       await updateSettings({ lastIndexedAt: new Date().toISOString() });
     } catch (error) {
+      if (error instanceof IndexingCancelledError) {
+        console.info('Indexing cancelled');
+        return;
+      }
       console.error('Indexing failed:', error);
       setErrors(prev => [...prev, { path: 'Global', message: error instanceof Error ? error.message : String(error) }]);
     } finally {
+      jobIdRef.current = null;
       setIsIndexing(false);
+      setIsPaused(false);
       setProgress(null);
     }
-  }, [isIndexing, updateSettings]);
+  }, [isIndexing, settings.coverOnlyThumbnails, updateSettings]);
+
+  const fillThumbnails = useCallback(async () => {
+    if (isIndexing) return;
+
+    setIsIndexing(true);
+    setProgress(null);
+    setErrors([]);
+    setIsPaused(false);
+    cancelledRef.current = false;
+    try {
+      await fillPageThumbnails((jobId) => {
+        jobIdRef.current = jobId;
+        if (cancelledRef.current) {
+          cancelIndexingJob(jobId).catch(console.error);
+        }
+      });
+    } catch (error) {
+      if (error instanceof IndexingCancelledError) {
+        console.info('Thumbnail fill cancelled');
+        return;
+      }
+      console.error('Thumbnail fill failed:', error);
+      setErrors(prev => [...prev, { path: 'Global', message: error instanceof Error ? error.message : String(error) }]);
+    } finally {
+      jobIdRef.current = null;
+      setIsIndexing(false);
+      setIsPaused(false);
+    }
+  }, [isIndexing]);
+
+  const pauseIndexing = useCallback(async () => {
+    if (jobIdRef.current === null) return;
+    await pauseIndexingJob(jobIdRef.current);
+    setIsPaused(true);
+  }, []);
+
+  const resumeIndexing = useCallback(async () => {
+    if (jobIdRef.current === null) return;
+    await resumeIndexingJob(jobIdRef.current);
+    setIsPaused(false);
+  }, []);
+
+  const cancelIndexing = useCallback(async () => {
+    cancelledRef.current = true;
+    if (jobIdRef.current === null) return;
+    await cancelIndexingJob(jobIdRef.current);
+  }, []);
 
   // Initial indexing trigger
   useEffect(() => {
@@ -58,7 +140,7 @@ export const IndexingProvider: React.FC<{ children: React.ReactNode }> = ({ chil
   }, [startIndexing, settingsLoading, settings.autoReindex]);
 
   return (
-    <IndexingContext.Provider value={{ isIndexing, progress, startIndexing, lastIndexedAt: settings.lastIndexedAt, errors, clearErrors }}>
+    <IndexingContext.Provider value={{ isIndexing, progress, savedComics, startIndexing, fillThumbnails, isPaused, pauseIndexing, resumeIndexing, cancelIndexing, lastIndexedAt: settings.lastIndexedAt, errors, clearErrors }}>
       {children}
     </IndexingContext.Provider>
   );
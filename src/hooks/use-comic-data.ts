@@ -3,6 +3,7 @@ import { Comic, ComicPage } from '../types/comic';
 import * as comicService from '../services/comic-service';
 import * as comicPageService from '../services/comic-page-service';
 import * as galleryService from '../services/gallery-service';
+import * as thumbnailService from '../services/thumbnail-service';
 
 export const useComicData = (idOrSlug: string | number) => {
   const [comic, setComic] = useState<Comic | null>(null);
@@ -74,6 +75,15 @@ export const useComicData = (idOrSlug: string | number) => {
             setPages(p);
             setError(null);
           }
+          // Page thumbnails deferred by cover-only indexing are generated on first open
+          if (p.some(page => !page.thumbnail_path)) {
+            thumbnailService.ensurePageThumbnails(comicId)
+              .then(() => comicPageService.getPagesByComicId(comicId))
+              .then(updated => {
+                if (isMounted) setPages(updated);
+              })
+              .catch(err => console.error('Failed to generate page thumbnails:', err));
+          }
         })
         .catch((err) => {
           if (isMounted) {
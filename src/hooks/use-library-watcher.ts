@@ -0,0 +1,51 @@
+import { useEffect } from 'react';
+import { getAllIndexPaths } from '../services/index-path-service';
+import { listenToLibraryChanges, unwatchIndexPaths, watchIndexPaths } from '../services/source-file-service';
+
+/**
+ * Hook to keep the backend watching the index paths, so comics added, changed
+ * or removed on disk show up in the library without a manual re-index.
+ * The watched set follows changes to the index paths and is dropped on unmount.
+ * @param enabled Whether the index paths are watched at all
+ */
+export const useLibraryWatcher = (enabled: boolean) => {
+  useEffect(() => {
+    if (!enabled) return;
+
+    let disposed = false;
+    const syncWatchedPaths = async () => {
+      try {
+        const paths = await getAllIndexPaths();
+        if (disposed) return;
+        const statuses = await watchIndexPaths(
+          paths.map(({ path, pattern, metadata_precedence }) => ({
+            path,
+            pattern,
+            metadataPrecedence: metadata_precedence,
+          }))
+        );
+        statuses
+          .filter((status) => status.error)
+          .forEach((status) => console.warn(`[Watcher] Could not watch ${status.path}: ${status.error}`));
+      } catch (error) {
+        console.error('[Watcher] Failed to watch index paths', error);
+      }
+    };
+
+    syncWatchedPaths();
+    window.addEventListener('index-paths-updated', syncWatchedPaths);
+    const unlistenPromise = listenToLibraryChanges((event) => {
+      console.info(
+        `[Watcher] Library changed in ${event.basePath}: ${event.added.length} added, ${event.changed.length} changed, ${event.removedComicPaths.length} removed`
+      );
+      window.dispatchEvent(new CustomEvent('library-updated'));
+    });
+
+    return () => {
+      disposed = true;
+      window.removeEventListener('index-paths-updated', syncWatchedPaths);
+      unlistenPromise.then((unlisten) => unlisten());
+      unwatchIndexPaths().catch((error) => console.error('[Watcher] Failed to stop watching index paths', error));
+    };
+  }, [enabled]);
+};
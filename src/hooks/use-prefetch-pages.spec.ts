@@ -0,0 +1,64 @@
+import { describe, it, expect, vi, beforeEach } from 'vitest';
+import { renderHook } from '@testing-library/react';
+import { usePrefetchPages } from './use-prefetch-pages';
+import { prefetchPages } from '../services/source-file-service';
+import { ComicPage } from '../types/comic';
+
+vi.mock('../services/source-file-service', () => ({
+  prefetchPages: vi.fn().mockResolvedValue(undefined),
+}));
+
+const makePage = (pageNumber: number, overrides: Partial<ComicPage> = {}): ComicPage => ({
+  id: pageNumber,
+  comic_id: 1,
+  page_number: pageNumber,
+  file_path: `/comics/a.cbz::${pageNumber}.jpg`,
+  file_name: `${pageNumber}.jpg`,
+  thumbnail_path: null,
+  thumbnail_exists: 0,
+  is_favorite: 0,
+  view_count: 0,
+  last_opened_at: null,
+  ...overrides,
+});
+
+describe('usePrefetchPages', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  it('prefetches around the current archive page', () => {
+    const pages = [1, 2, 3].map((n) => makePage(n, { source_type: 'archive', source_path: '/comics/a.cbz', archive_entry_path: `${n}.jpg` }));
+    renderHook(() => usePrefetchPages(pages, 1, 'single'));
+
+    expect(prefetchPages).toHaveBeenCalledWith('/comics/a.cbz', 2, 'single', { width: undefined, height: undefined });
+  });
+
+  it('uses the PDF page number for PDF pages', () => {
+    const pages = [makePage(1, { source_type: 'pdf', source_path: '/comics/b.pdf', pdf_page_number: 4 })];
+    renderHook(() => usePrefetchPages(pages, 0, 'scroll'));
+
+    expect(prefetchPages).toHaveBeenCalledWith('/comics/b.pdf', 4, 'scroll', { width: undefined, height: undefined });
+  });
+
+  it('skips image pages shown at their original size', () => {
+    renderHook(() => usePrefetchPages([makePage(1, { source_type: 'image' })], 0, 'single'));
+
+    expect(prefetchPages).not.toHaveBeenCalled();
+  });
+
+  it('prefetches downscaled image pages by their folder', () => {
+    const pages = [makePage(1, { source_type: 'image', file_path: '/comics/c/001.jpg' })];
+    renderHook(() => usePrefetchPages(pages, 0, 'single', { width: 1280 }));
+
+    expect(prefetchPages).toHaveBeenCalledWith('/comics/c', 1, 'single', { width: 1280, height: undefined });
+  });
+
+  it('passes the border crop along', () => {
+    const pages = [makePage(1, { source_type: 'archive', source_path: '/comics/a.cbz', archive_entry_path: '1.jpg' })];
+    const crop = { tolerance: 24, minContentPercent: 50 };
+    renderHook(() => usePrefetchPages(pages, 0, 'scroll', { crop }));
+
+    expect(prefetchPages).toHaveBeenCalledWith('/comics/a.cbz', 1, 'scroll', { width: undefined, height: undefined, crop });
+  });
+});
@@ -0,0 +1,34 @@
+import { useEffect } from 'react';
+import { ComicPage } from '../types/comic';
+import { getComicPageRequest } from '../services/page-source-utils';
+import { prefetchPages, type ReaderViewMode } from '../services/source-file-service';
+import type { PageProcessing } from '../utils/image-utils';
+
+/**
+ * Hook to let the backend read the pages around the current one ahead of time.
+ * Only pages served by the `comic://` protocol are prefetched.
+ * @param pages List of comic pages
+ * @param currentPage Current page index
+ * @param viewMode View mode the pages are shown in
+ * @param processing Downscaling and cropping of the pages, if any
+ */
+export const usePrefetchPages = (
+  pages: ComicPage[],
+  currentPage: number,
+  viewMode: ReaderViewMode,
+  processing?: PageProcessing
+) => {
+  const page = pages[currentPage];
+  const request = page ? getComicPageRequest(page, processing) : null;
+  const comicPath = request?.comicPath;
+  const pageNumber = request?.pageNumber;
+
+  useEffect(() => {
+    if (!comicPath || pageNumber === undefined) return;
+
+    const { width, height, crop } = processing ?? {};
+    prefetchPages(comicPath, pageNumber, viewMode, { width, height, crop }).catch((error) => {
+      console.error('Failed to prefetch pages', error);
+    });
+  }, [comicPath, pageNumber, viewMode, processing]);
+};
@@ -1,14 +1,21 @@
 import { useEffect } from 'react';
 import { ComicPage } from '../types/comic';
 import { resolvePageImageUrl } from '../services/page-source-utils';
+import type { PageProcessing } from '../utils/image-utils';
 
 /**
  * Hook to preload adjacent page images for smoother navigation in the viewer.
  * @param pages List of comic pages
  * @param currentPage Current page index
  * @param preloadCount Number of pages to preload ahead
+ * @param processing Downscaling and cropping of the pages, if any
  */
-export const usePreloadImages = (pages: ComicPage[], currentPage: number, preloadCount = 3) => {
+export const usePreloadImages = (
+  pages: ComicPage[],
+  currentPage: number,
+  preloadCount = 3,
+  processing?: PageProcessing
+) => {
   useEffect(() => {
     if (!pages || pages.length === 0) return;
 
@@ -18,7 +25,7 @@ export const usePreloadImages = (pages: ComicPage[], currentPage: number, preloa
     const pagesToPreload = pages.slice(start, end).filter((_, i) => (start + i) !== currentPage);
     
     pagesToPreload.forEach((page) => {
-      resolvePageImageUrl(page)
+      resolvePageImageUrl(page, false, processing)
         .then((src) => {
           const img = new Image();
           img.src = src;
@@ -27,5 +34,5 @@ export const usePreloadImages = (pages: ComicPage[], currentPage: number, preloa
           console.error('Failed to preload page image', error);
         });
     });
-  }, [pages, currentPage, preloadCount]);
+  }, [pages, currentPage, preloadCount, processing]);
 };
@@ -7,6 +7,7 @@ import { useIndexPaths } from '../../hooks/use-index-paths';
 import * as indexPathService from '../../services/index-path-service';
 import { HotkeyInput } from '../../components/settings/hotkey-input';
 import { AppSettings, DEFAULT_SETTINGS } from '../../services/settings-service';
+import type { IndexPath } from '../../types/comic';
 import {
     RxSymbol,
     RxReload,
@@ -21,7 +22,11 @@ import {
     RxTimer,
     RxKeyboard,
     RxArchive,
-    RxLayers
+    RxLayers,
+    RxPause,
+    RxPlay,
+    RxStop,
+    RxImage
 } from 'react-icons/rx';
 import { open } from '@tauri-apps/plugin-dialog';
 
@@ -31,7 +36,7 @@ export const Route = createFileRoute('/settings/')({
 
 function SettingsPage() {
   const { settings, updateSettings, isLoading: loadingSettings } = useSettings();
-  const { isIndexing, startIndexing, lastIndexedAt, progress } = useIndexing();
+  const { isIndexing, startIndexing, fillThumbnails, lastIndexedAt, progress, isPaused, pauseIndexing, resumeIndexing, cancelIndexing } = useIndexing();
   const { indexPaths, refresh: refreshPaths } = useIndexPaths();
   const [saveStatus, setSaveStatus] = useState<'idle' | 'saving' | 'saved'>('idle');
   const [editingPathId, setEditingPathId] = useState<number | null>(null);
@@ -103,6 +108,17 @@ function SettingsPage() {
     }
   };
 
+  const handleUpdateMetadataPrecedence = async (id: number, metadataPrecedence: IndexPath['metadata_precedence']) => {
+    try {
+      await indexPathService.updateIndexPathMetadataPrecedence(id, metadataPrecedence);
+      await refreshPaths();
+      // Trigger indexing to refresh metadata with the new precedence
+      startIndexing();
+    } catch (error) {
+      console.error('Failed to update metadata precedence:', error);
+    }
+  };
+
   const checkDuplicateHotkey = (key: string, name: string) => {
     if (!key) return false;
     const hotkeys = form.getFieldValue('hotkeys') as AppSettings['hotkeys'];
@@ -180,6 +196,45 @@ function SettingsPage() {
                 </form.Field>
               </div>
 
+              <div className="flex items-center justify-between border-b border-slate-800 pb-4">
+                <form.Field name="coverOnlyThumbnails">
+                  {(field) => (
+                    <div className="flex items-center gap-3">
+                      <button
+                        type="button"
+                        role="switch"
+                        aria-checked={field.state.value}
+                        onClick={() => field.handleChange(!field.state.value)}
+                        className={`relative inline-flex h-6 w-11 items-center rounded-full transition-colors focus:outline-none focus:ring-2 focus:ring-blue-500 focus:ring-offset-2 focus:ring-offset-slate-900 ${
+                          field.state.value ? 'bg-blue-600' : 'bg-slate-700'
+                        }`}
+                      >
+                        <span
+                          className={`inline-block h-4 w-4 transform rounded-full bg-white transition-transform ${
+                            field.state.value ? 'translate-x-6' : 'translate-x-1'
+                          }`}
+                        />
+                      </button>
+                      <div>
+                        <span className="text-sm font-medium text-slate-200">Cover thumbnails only</span>
+                        <p className="text-[10px] text-slate-500">Index faster; other pages get thumbnails when a comic is opened.</p>
+                      </div>
+                    </div>
+                  )}
+                </form.Field>
+
+                <button
+                  type="button"
+                  onClick={() => fillThumbnails()}
+                  disabled={isIndexing}
+                  className="flex items-center gap-2 px-4 py-2 bg-slate-800 hover:bg-slate-700 border border-slate-700 rounded-lg text-sm font-medium text-slate-300 transition-all disabled:opacity-50"
+                  title="Generate the page thumbnails that cover-only indexing skipped"
+                >
+                  <RxImage />
+                  Fill Page Thumbnails
+                </button>
+              </div>
+
               <div className="flex items-center justify-between">
                 <div>
                   <h3 className="text-sm font-medium text-slate-200">Index Paths</h3>
@@ -258,6 +313,20 @@ function SettingsPage() {
                             {path.pattern}
                           </button>
                         )}
+                        <label className="flex items-center gap-1.5 mt-1 text-[10px] text-slate-500">
+                          Prefer
+                          <select
+                            value={path.metadata_precedence}
+                            onChange={(e) =>
+                              handleUpdateMetadataPrecedence(path.id!, e.target.value as IndexPath['metadata_precedence'])
+                            }
+                            className="bg-slate-900 border border-slate-700 rounded px-1 py-0.5 text-[10px] text-slate-300 focus:outline-none focus:border-blue-500"
+                            title="Which metadata wins when ComicInfo.xml and the pattern both name a field"
+                          >
+                            <option value="comicInfo">ComicInfo.xml metadata</option>
+                            <option value="pattern">Pattern metadata</option>
+                          </select>
+                        </label>
                       </div>
                       <button
                         type="button"
@@ -279,7 +348,7 @@ function SettingsPage() {
                 {isIndexing && progress && (
                   <div className="flex-1 max-w-md space-y-1">
                     <div className="flex justify-between text-[10px] text-slate-400 uppercase">
-                      <span>{progress.status === 'scanning' ? 'Scanning...' : 'Indexing...'}</span>
+                      <span>{isPaused ? 'Paused' : progress.status === 'scanning' ? 'Scanning...' : 'Indexing...'}</span>
                       <span>{(progress.current ?? 0)} / {(progress.total ?? 0)}</span>
                     </div>
                     <div className="text-[10px] text-slate-400 truncate" title={progress.currentTask || ''}>
@@ -295,6 +364,28 @@ function SettingsPage() {
                 )}
 
                 <div className="flex gap-2">
+                  {isIndexing && (
+                    <>
+                      <button
+                        type="button"
+                        onClick={() => (isPaused ? resumeIndexing() : pauseIndexing()).catch(console.error)}
+                        className="flex items-center gap-2 px-4 py-2 bg-slate-800 hover:bg-slate-700 border border-slate-700 rounded-lg text-sm font-medium text-slate-300 transition-all"
+                      >
+                        {isPaused ? <RxPlay /> : <RxPause />}
+                        {isPaused ? 'Resume' : 'Pause'}
+                      </button>
+
+                      <button
+                        type="button"
+                        onClick={() => cancelIndexing().catch(console.error)}
+                        className="flex items-center gap-2 px-4 py-2 bg-red-600/10 hover:bg-red-600/20 border border-red-600/30 rounded-lg text-sm font-medium text-red-400 transition-all"
+                      >
+                        <RxStop />
+                        Cancel
+                      </button>
+                    </>
+                  )}
+
                   <button
                     type="button"
                     onClick={() => startIndexing('quick')}
@@ -493,6 +584,76 @@ function SettingsPage() {
                 )}
               </form.Field>
 
+              <div className="md:col-span-2 pt-4 border-t border-slate-800 space-y-6">
+                <form.Field name="cropBorders">
+                  {(field) => (
+                    <div className="flex items-center gap-3">
+                      <button
+                        type="button"
+                        role="switch"
+                        aria-checked={field.state.value}
+                        onClick={() => field.handleChange(!field.state.value)}
+                        className={`relative inline-flex h-6 w-11 items-center rounded-full transition-colors focus:outline-none focus:ring-2 focus:ring-blue-500 focus:ring-offset-2 focus:ring-offset-slate-900 ${
+                          field.state.value ? 'bg-blue-600' : 'bg-slate-700'
+                        }`}
+                      >
+                        <span
+                          className={`inline-block h-4 w-4 transform rounded-full bg-white transition-transform ${
+                            field.state.value ? 'translate-x-6' : 'translate-x-1'
+                          }`}
+                        />
+                      </button>
+                      <div>
+                        <span className="text-sm font-medium text-slate-200">Crop page borders</span>
+                        <p className="text-[10px] text-slate-500">Remove uniform white or black scanner borders so pages fill the screen.</p>
+                      </div>
+                    </div>
+                  )}
+                </form.Field>
+
+                <div className="grid grid-cols-1 md:grid-cols-2 gap-8">
+                  <form.Field name="cropTolerance">
+                    {(field) => (
+                      <div className="space-y-3">
+                        <div className="flex justify-between items-center">
+                          <label className="text-sm font-medium text-slate-300">Border Tolerance</label>
+                          <span className="text-blue-400 font-mono font-bold">{field.state.value}</span>
+                        </div>
+                        <input
+                          type="range"
+                          min="0"
+                          max="96"
+                          step="4"
+                          value={field.state.value}
+                          onChange={(e) => field.handleChange(Number(e.target.value))}
+                          className="w-full accent-blue-500 h-1.5 bg-slate-800 rounded-lg appearance-none cursor-pointer"
+                        />
+                      </div>
+                    )}
+                  </form.Field>
+
+                  <form.Field name="cropMinContent">
+                    {(field) => (
+                      <div className="space-y-3">
+                        <div className="flex justify-between items-center">
+                          <label className="text-sm font-medium text-slate-300">Minimum Content Size</label>
+                          <span className="text-blue-400 font-mono font-bold">{field.state.value}%</span>
+                        </div>
+                        <input
+                          type="range"
+                          min="10"
+                          max="95"
+                          step="5"
+                          value={field.state.value}
+                          onChange={(e) => field.handleChange(Number(e.target.value))}
+                          className="w-full accent-blue-500 h-1.5 bg-slate-800 rounded-lg appearance-none cursor-pointer"
+                        />
+                      </div>
+                    )}
+                  </form.Field>
+                </div>
+              </div>
+
               <div className="md:col-span-2 pt-4 border-t border-slate-800">
                 <form.Field name="saveOpenedTabs">
                   {(field) => (
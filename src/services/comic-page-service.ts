@@ -39,7 +39,7 @@ export const insertPages = async (
         page.source_path ?? page.file_path,
         page.archive_entry_path ?? null,
         page.pdf_page_number ?? null,
-        page.thumbnail_path,
+        page.thumbnail_path?.replace(/\\/g, '/') ?? null,
         page.thumbnail_exists,
       ]
     );
@@ -54,7 +54,11 @@ export const insertPages = async (
 
 export const updateThumbnailPath = async (id: number, thumbnailPath: string): Promise<void> => {
   const db = await getDb();
-  await db.execute('UPDATE comic_pages SET thumbnail_path = $1 WHERE id = $2', [thumbnailPath, id]);
+  // Stored with forward slashes, like the paths written by the backend.
+  await db.execute('UPDATE comic_pages SET thumbnail_path = $1 WHERE id = $2', [
+    thumbnailPath.replace(/\\/g, '/'),
+    id,
+  ]);
 };
 
 export const togglePageFavorite = async (id: number): Promise<void> => {
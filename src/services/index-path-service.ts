@@ -12,6 +12,7 @@ export const addIndexPath = async (path: string, pattern: string): Promise<numbe
     'INSERT INTO index_paths (path, pattern) VALUES ($1, $2)',
     [path, pattern]
   );
+  window.dispatchEvent(new CustomEvent('index-paths-updated'));
   return result.lastInsertId ?? 0;
 };
 
@@ -21,9 +22,23 @@ export const updateIndexPath = async (id: number, pattern: string): Promise<void
     'UPDATE index_paths SET pattern = $1 WHERE id = $2',
     [pattern, id]
   );
+  window.dispatchEvent(new CustomEvent('index-paths-updated'));
+};
+
+export const updateIndexPathMetadataPrecedence = async (
+  id: number,
+  metadataPrecedence: IndexPath['metadata_precedence']
+): Promise<void> => {
+  const db = await getDb();
+  await db.execute(
+    'UPDATE index_paths SET metadata_precedence = $1 WHERE id = $2',
+    [metadataPrecedence, id]
+  );
+  window.dispatchEvent(new CustomEvent('index-paths-updated'));
 };
 
 export const removeIndexPath = async (id: number): Promise<void> => {
   const db = await getDb();
   await db.execute('DELETE FROM index_paths WHERE id = $1', [id]);
+  window.dispatchEvent(new CustomEvent('index-paths-updated'));
 };
@@ -1,33 +1,54 @@
 import { describe, it, expect, vi, beforeEach } from 'vitest';
 import * as indexingService from './indexing-service';
+import type { BuildIndexPayloadResult, IndexingJobFinishedEvent } from './source-file-service';
+
+const jobEvents = vi.hoisted(() => ({
+  finishedListener: null as ((event: IndexingJobFinishedEvent) => void) | null,
+}));
 
 vi.mock('./source-file-service', () => ({
-  scanComicCandidates: vi.fn(),
-  getComicPages: vi.fn(),
+  startIndexingJob: vi.fn(),
   listenToRustIndexingProgress: vi.fn().mockResolvedValue(() => {}),
+  listenToIndexingJobFinished: vi.fn(async (onEvent) => {
+    jobEvents.finishedListener = onEvent;
+    return () => {
+      jobEvents.finishedListener = null;
+    };
+  }),
   cleanupIndexedThumbnails: vi.fn(),
 }));
 
-vi.mock('./page-source-utils', () => ({
-  renderPdfPagesToPngBytes: vi.fn(),
+vi.mock('./thumbnail-service', () => ({
+  startThumbnailFillJob: vi.fn(),
 }));
 
 vi.mock('./comic-service', () => ({
-  upsertComic: vi.fn().mockResolvedValue(1),
-  getComicByPath: vi.fn().mockResolvedValue({ id: 1, path: 'base/FolderComic' }),
-  updateIndexingStatus: vi.fn(),
   getAllComics: vi.fn().mockResolvedValue([]),
   deleteComic: vi.fn(),
 }));
 
-vi.mock('./comic-page-service', () => ({
-  getPagesByComicId: vi.fn().mockResolvedValue([]),
-  insertPages: vi.fn(),
+vi.mock('./index-path-service', () => ({
+  getAllIndexPaths: vi.fn().mockResolvedValue([]),
 }));
 
-vi.mock('./thumbnail-service', () => ({
-  generateThumbnailFromBytes: vi.fn().mockResolvedValue('thumb/path.jpg'),
-}));
+const emptyResult = (overrides: Partial<BuildIndexPayloadResult> = {}): BuildIndexPayloadResult => ({
+  added: [],
+  changed: [],
+  removedComicPaths: [],
+  unchangedComicPaths: [],
+  activeComicPaths: [],
+  errors: [],
+  ...overrides,
+});
+
+/** Makes the next job finish with `event` before its ID is returned. */
+const finishNextJob = async (event: Omit<IndexingJobFinishedEvent, 'jobId' | 'basePath'>) => {
+  const sourceFileService = await import('./source-file-service');
+  vi.mocked(sourceFileService.startIndexingJob).mockImplementation(async (basePath) => {
+    jobEvents.finishedListener?.({ jobId: 7, basePath, ...event });
+    return 7;
+  });
+};
 
 describe('indexing-service', () => {
   beforeEach(() => {
@@ -94,109 +115,97 @@ describe('indexing-service', () => {
     });
   });
 
-  it('indexes image, pdf and archive candidates', async () => {
+  it('indexes through a backend job and returns the active comics', async () => {
     const sourceFileService = await import('./source-file-service');
-    const pageSourceUtils = await import('./page-source-utils');
     const comicService = await import('./comic-service');
-
-    vi.mocked(sourceFileService.scanComicCandidates).mockResolvedValue({
-      candidates: [{ path: 'base/FolderComic', sourceType: 'image', title: 'FolderComic' }],
-      errors: [],
+    await finishNextJob({
+      status: 'completed',
+      error: null,
+      result: emptyResult({ activeComicPaths: ['base/FolderComic'], errors: [{ path: 'base/Broken', message: 'bad' }] }),
+    });
+    vi.mocked(comicService.getAllComics).mockResolvedValue([
+      { id: 1, path: 'base/FolderComic' },
+      { id: 2, path: 'base/Broken' },
+      { id: 3, path: 'base/Stale' },
+    ] as any);
+    const onJobStarted = vi.fn();
+
+    const seen = await indexingService.indexComics('base', '{series}', 'quick', undefined, {
+      metadataPrecedence: 'pattern',
+      onJobStarted,
     });
-    vi.mocked(sourceFileService.getComicPages).mockResolvedValue([
-      {
-        pageNumber: 1,
-        filePath: 'base/FolderComic/1.jpg',
-        fileName: '1.jpg',
-        sourceType: 'image',
-        sourcePath: 'base/FolderComic/1.jpg',
-        archiveEntryPath: null,
-        pdfPageNumber: null,
-      } as any,
-    ]);
 
-    vi.mocked(pageSourceUtils.renderPdfPagesToPngBytes).mockImplementation(
-      async (_path, pageNumbers, onPage) => {
-        const map = new Map();
-        for (const num of pageNumbers) {
-          const bytes = new Uint8Array([num]);
-          if (onPage) {
-            await onPage(num, bytes);
-          } else {
-            map.set(num, bytes);
-          }
-        }
-        return map;
-      }
-    );
+    expect(sourceFileService.startIndexingJob).toHaveBeenCalledWith('base', '{series}', expect.any(Function), 'pattern', false, undefined);
+    expect(onJobStarted).toHaveBeenCalledWith(7);
+    expect(seen.has('base/FolderComic')).toBe(true);
+    expect(seen.has('base/Broken')).toBe(true);
+    expect(comicService.deleteComic).toHaveBeenCalledTimes(1);
+    expect(comicService.deleteComic).toHaveBeenCalledWith(3);
+  });
 
-    const seen = await indexingService.indexComics('base', '{series}');
+  it('requests a full reindex in full mode', async () => {
+    const sourceFileService = await import('./source-file-service');
+    await finishNextJob({ status: 'completed', error: null, result: emptyResult() });
 
-    expect(seen.has('base/FolderComic')).toBe(true);
-    expect(comicService.upsertComic).toHaveBeenCalled();
+    await indexingService.indexComics('base', '{series}', 'full');
+
+    expect(sourceFileService.startIndexingJob).toHaveBeenCalledWith('base', '{series}', expect.any(Function), undefined, true, undefined);
   });
 
-  it('indexes comics that do not match the pattern with defaults', async () => {
+  it('requests cover-only thumbnails when asked to', async () => {
     const sourceFileService = await import('./source-file-service');
-    const comicService = await import('./comic-service');
+    await finishNextJob({ status: 'completed', error: null, result: emptyResult() });
 
-    vi.mocked(sourceFileService.scanComicCandidates).mockResolvedValue({
-      candidates: [{ path: 'base/NonMatchingFolder', sourceType: 'image', title: 'NonMatchingFolder' }],
-      errors: [],
-    });
-    vi.mocked(sourceFileService.getComicPages).mockResolvedValue([
-      {
-        pageNumber: 1,
-        filePath: 'base/NonMatchingFolder/1.jpg',
-        fileName: '1.jpg',
-        sourceType: 'image',
-        sourcePath: 'base/NonMatchingFolder/1.jpg',
-        archiveEntryPath: null,
-        pdfPageNumber: null,
-      } as any,
-    ]);
+    await indexingService.indexComics('base', '{series}', 'quick', undefined, { coverOnly: true });
 
-    // Use a pattern that clearly doesn't match a simple folder like 'NonMatchingFolder'
-    // Like '{artist}/{series}' which needs at least two segments
-    const seen = await indexingService.indexComics('base', '{artist}/{series}');
-
-    expect(seen.has('base/NonMatchingFolder')).toBe(true);
-    // Should be called with 'Unknown' artist and 'NonMatchingFolder' as title/series
-    expect(comicService.upsertComic).toHaveBeenCalledWith(
-      expect.objectContaining({
-        artist: 'Unknown',
-        series: 'NonMatchingFolder',
-      })
+    expect(sourceFileService.startIndexingJob).toHaveBeenCalledWith('base', '{series}', expect.any(Function), undefined, false, true);
+  });
+
+  it('rejects when the job is cancelled or fails', async () => {
+    const comicService = await import('./comic-service');
+    await finishNextJob({ status: 'cancelled', error: null, result: null });
+    await expect(indexingService.indexComics('base', '{series}')).rejects.toBeInstanceOf(
+      indexingService.IndexingCancelledError
     );
+
+    await finishNextJob({ status: 'failed', error: 'disk gone', result: null });
+    await expect(indexingService.indexComics('base', '{series}')).rejects.toThrow('disk gone');
+    expect(comicService.deleteComic).not.toHaveBeenCalled();
   });
 
-  it('respects the indexing mode (quick vs full)', async () => {
+  it('indexes every index path with its stored metadata precedence', async () => {
     const sourceFileService = await import('./source-file-service');
-    const comicService = await import('./comic-service');
-    const pageService = await import('./comic-page-service');
+    const indexPathService = await import('./index-path-service');
+    vi.mocked(indexPathService.getAllIndexPaths).mockResolvedValue([
+      { id: 1, path: 'base', pattern: '{series}', metadata_precedence: 'pattern', created_at: '' },
+    ]);
+    await finishNextJob({ status: 'completed', error: null, result: emptyResult() });
+
+    await indexingService.reindexAll('quick', undefined, { onJobStarted: vi.fn() });
+    await indexingService.reindexPathById(1);
+
+    expect(sourceFileService.startIndexingJob).toHaveBeenCalledTimes(2);
+    expect(sourceFileService.startIndexingJob).toHaveBeenNthCalledWith(1, 'base', '{series}', expect.any(Function), 'pattern', false, undefined);
+    expect(sourceFileService.startIndexingJob).toHaveBeenNthCalledWith(2, 'base', '{series}', expect.any(Function), 'pattern', false, undefined);
+  });
 
-    vi.mocked(sourceFileService.scanComicCandidates).mockResolvedValue({
-      candidates: [{ path: 'base/Comic', sourceType: 'image', title: 'Comic' }],
-      errors: [],
+  it('fills deferred page thumbnails of every index path', async () => {
+    const thumbnailService = await import('./thumbnail-service');
+    const indexPathService = await import('./index-path-service');
+    vi.mocked(indexPathService.getAllIndexPaths).mockResolvedValue([
+      { id: 1, path: 'a', pattern: '{series}', metadata_precedence: 'pattern', created_at: '' },
+      { id: 2, path: 'b', pattern: '{series}', metadata_precedence: 'pattern', created_at: '' },
+    ]);
+    vi.mocked(thumbnailService.startThumbnailFillJob).mockImplementation(async (basePath) => {
+      jobEvents.finishedListener?.({ jobId: 9, basePath, status: 'completed', error: null, result: null });
+      return 9;
     });
-    vi.mocked(sourceFileService.getComicPages).mockResolvedValue([]);
-    vi.mocked(comicService.getComicByPath).mockResolvedValue({ id: 1, path: 'base/Comic', indexing_status: 'completed', thumbnail_path: 'thumb.jpg' } as any);
-    vi.mocked(pageService.getPagesByComicId).mockResolvedValue([{ page_number: 1, thumbnail_exists: 1 }] as any);
+    const onJobStarted = vi.fn();
 
-    // Quick mode: Should skip if everything exists
-    await indexingService.indexComics('base', '{series}', 'quick');
-    expect(sourceFileService.getComicPages).not.toHaveBeenCalled();
+    await indexingService.fillPageThumbnails(onJobStarted);
 
-    // Full mode: Should not skip even if everything exists
-    vi.mocked(sourceFileService.getComicPages).mockResolvedValue([]);
-    await indexingService.indexComics('base', '{series}', 'full');
-    expect(sourceFileService.getComicPages).toHaveBeenCalledWith(
-      'base',
-      expect.any(Number),
-      expect.any(Number),
-      'base/Comic',
-      'image',
-      true // fullReindex = true
-    );
+    expect(thumbnailService.startThumbnailFillJob).toHaveBeenNthCalledWith(1, 'a');
+    expect(thumbnailService.startThumbnailFillJob).toHaveBeenNthCalledWith(2, 'b');
+    expect(onJobStarted).toHaveBeenCalledTimes(2);
   });
 });
@@ -1,9 +1,7 @@
 import * as comicService from './comic-service';
-import * as pageService from './comic-page-service';
 import { getAllIndexPaths } from './index-path-service';
 import * as sourceFileService from './source-file-service';
-import * as thumbnailService from './thumbnail-service';
-import { renderPdfPagesToPngBytes } from './page-source-utils';
+import { startThumbnailFillJob } from './thumbnail-service';
 import { isSubPath, normalizePath } from '../utils/image-utils';
 
 const IMAGE_EXTENSIONS = ['jpg', 'jpeg', 'png', 'gif', 'webp', 'bmp'];
@@ -108,310 +106,187 @@ export const extractMetadata = (relativePath: string, pattern: string): Record<s
   return null;
 };
 
-const generatePdfFallbackThumbnails = async (
-  pages: sourceFileService.IndexedPagePayload[],
-  comicId: number,
-  existingThumbnailByPageNumber: Map<number, string>,
-  force: boolean = false,
-  onProgress?: (current: number, total: number) => void
-): Promise<Map<number, string | null>> => {
-  const output = new Map<number, string | null>();
-  if (pages.length === 0 || pages[0]?.sourceType !== 'pdf') {
-    return output;
-  }
+const formatDuration = (ms: number): string => {
+  const totalSeconds = Math.round(ms / 1000);
+  const minutes = Math.floor(totalSeconds / 60);
+  const seconds = totalSeconds % 60;
+  return minutes > 0 ? `${minutes}m ${seconds}s` : `${seconds}s`;
+};
+
+const STORAGE_LABELS: Record<sourceFileService.StorageKind, string> = {
+  solidState: 'SSD',
+  rotational: 'HDD',
+  network: 'network storage',
+};
 
-  if (!force) {
-    pages.forEach((page) => {
-      const existingThumb = existingThumbnailByPageNumber.get(page.pageNumber);
-      if (existingThumb) {
-        output.set(page.pageNumber, normalizePath(existingThumb));
+const describeRustProgress = (event: sourceFileService.RustIndexingProgressEvent): string => {
+  switch (event.phase) {
+    case 'scanning':
+      return 'Scanning for candidates...';
+    case 'finalizing':
+      return 'Finalizing index...';
+    case 'completed':
+      return 'Completed Rust indexing payload';
+    default: {
+      const parts = [`Pages ${event.pagesDone}/${event.pagesTotal}`];
+      if (event.workers > 0) {
+        const storage = event.storage ? ` on ${STORAGE_LABELS[event.storage]}` : '';
+        parts.push(`${event.comicsActive}/${event.workers} workers${storage}`);
       }
-    });
+      if (event.pagesPerSecond > 0) {
+        parts.push(`${event.pagesPerSecond.toFixed(1)} pages/s`);
+      }
+      if (event.etaMs !== null) {
+        parts.push(`ETA ${formatDuration(event.etaMs)}`);
+      }
+      return parts.join(' · ');
+    }
   }
+};
 
-  const pagesToGenerate = pages.filter((page) => !output.has(page.pageNumber));
-  if (pagesToGenerate.length === 0) {
-    return output;
+export class IndexingCancelledError extends Error {
+  constructor(basePath: string) {
+    super(`Indexing of ${basePath} was cancelled`);
+    this.name = 'IndexingCancelledError';
   }
+}
+
+export type IndexComicsOptions = {
+  metadataPrecedence?: sourceFileService.MetadataPrecedence;
+  /**
+   * Generate only cover thumbnails. Other pages get theirs when the comic is
+   * opened or by a thumbnail fill job.
+   */
+  coverOnly?: boolean;
+  /** Called with the ID of the backend job, e.g. to pause or cancel it. */
+  onJobStarted?: (jobId: number) => void;
+  /** Called for every comic the backend job saved to the library. */
+  onComic?: (event: sourceFileService.IndexedComicEvent) => void;
+};
 
-  const sourcePath = normalizePath(pagesToGenerate[0].sourcePath || pagesToGenerate[0].filePath);
-  const pageNumbers = pagesToGenerate.map((page) => page.pdfPageNumber ?? page.pageNumber);
-  
-  // Create a map for fast lookup of pages by their PDF page number
-  const pageByPdfPageNumber = new Map<number, sourceFileService.IndexedPagePayload>();
-  pagesToGenerate.forEach(p => {
-    pageByPdfPageNumber.set(p.pdfPageNumber ?? p.pageNumber, p);
+/**
+ * Starts a backend job with `start` and resolves with the event it finished
+ * with. The job may finish before its ID is known, so finished events are
+ * buffered until then.
+ */
+const runBackendJob = async (
+  start: () => Promise<number>,
+  onJobStarted?: (jobId: number) => void
+): Promise<sourceFileService.IndexingJobFinishedEvent> => {
+  let jobId: number | null = null;
+  const finishedEvents: sourceFileService.IndexingJobFinishedEvent[] = [];
+  let settle: (event: sourceFileService.IndexingJobFinishedEvent) => void = () => {};
+  const finished = new Promise<sourceFileService.IndexingJobFinishedEvent>((resolve) => {
+    settle = resolve;
   });
-  
-  let current = 0;
-  const total = pagesToGenerate.length;
-
-  await renderPdfPagesToPngBytes(sourcePath, pageNumbers, async (pageNumber, bytes) => {
-    current++;
-    onProgress?.(current, total);
-
-    const page = pageByPdfPageNumber.get(pageNumber);
-    if (page) {
-      try {
-        const thumb = await thumbnailService.generateThumbnailFromBytes(bytes, comicId, page.pageNumber);
-        if (thumb) {
-          output.set(page.pageNumber, normalizePath(thumb));
-        } else {
-          console.warn(`[Indexing] thumbnailService returned empty path for PDF page ${pageNumber}`);
-          output.set(page.pageNumber, null);
-        }
-      } catch (err) {
-        console.error(`[Indexing] Failed to generate thumbnail for PDF page ${pageNumber}`, err);
-        output.set(page.pageNumber, null);
-      }
-    } else {
-      console.warn(`[Indexing] Could not find page payload mapping for PDF page ${pageNumber}`);
+  const unlistenFinished = await sourceFileService.listenToIndexingJobFinished((event) => {
+    if (jobId === null) {
+      finishedEvents.push(event);
+    } else if (event.jobId === jobId) {
+      settle(event);
     }
   });
 
-  return output;
+  try {
+    jobId = await start();
+    onJobStarted?.(jobId);
+    const bufferedEvent = finishedEvents.find((event) => event.jobId === jobId);
+    if (bufferedEvent) {
+      settle(bufferedEvent);
+    }
+    return await finished;
+  } finally {
+    await unlistenFinished();
+  }
 };
 
-
-
+/**
+ * Indexes an index path with a backend indexing job, which reads the comics,
+ * generates their thumbnails and saves them to the library. Resolves with the
+ * paths of the comics that are in the library afterwards, and rejects with an
+ * `IndexingCancelledError` when the job is cancelled.
+ */
 export const indexComics = async (
   basePath: string,
   pattern: string,
   mode: 'quick' | 'full' = 'quick',
-  onProgress?: (progress: IndexingProgress) => void
+  onProgress?: (progress: IndexingProgress) => void,
+  options: IndexComicsOptions = {}
 ): Promise<Set<string>> => {
   console.info(`[Indexing] Starting ${mode} index for base path: ${normalizePath(basePath)} (pattern: ${pattern})`);
   const normalizedBasePath = normalizePath(basePath);
-  const fullReindex = mode === 'full';
-  
+  const errors: IndexingError[] = [];
+  let indexedCount = 0;
+
   const unlistenRustProgress = await sourceFileService.listenToRustIndexingProgress((event) => {
     if (normalizePath(event.basePath) !== normalizedBasePath) {
       return;
     }
 
     onProgress?.({
-      current: event.currentComic,
-      total: event.totalComics,
-      currentPath: normalizePath(event.currentPath),
+      current: event.comicsDone,
+      total: event.comicsTotal,
+      currentPath: event.currentPath ? normalizePath(event.currentPath) : '',
       percentage: event.percentage,
-      currentTask: event.currentTask,
-      errors: [],
+      currentTask: describeRustProgress(event),
+      errors: [...errors],
     });
   });
 
   try {
-    // 1. Scan for candidates
+    const { status, error, result } = await runBackendJob(
+      () =>
+        sourceFileService.startIndexingJob(
+          basePath,
+          pattern,
+          (event) => {
+            indexedCount++;
+            window.dispatchEvent(new CustomEvent('library-updated'));
+            options.onComic?.(event);
+          },
+          options.metadataPrecedence,
+          mode === 'full',
+          options.coverOnly
+        ),
+      options.onJobStarted
+    );
+    if (status === 'cancelled') {
+      throw new IndexingCancelledError(normalizedBasePath);
+    }
+    if (status === 'failed' || !result) {
+      throw new Error(error ?? `Indexing of ${normalizedBasePath} failed`);
+    }
+
+    errors.push(...result.errors);
+    // Comics that failed to index keep their last good entry in the library.
+    const activeComicPaths = new Set(
+      [...result.activeComicPaths, ...result.errors.map((indexingError) => indexingError.path)].map(normalizePath)
+    );
     onProgress?.({
-      current: 0,
-      total: 0,
+      current: result.activeComicPaths.length,
+      total: result.activeComicPaths.length,
       currentPath: normalizedBasePath,
-      percentage: 0,
-      currentTask: 'Scanning for candidates...',
-      errors: [],
+      percentage: 100,
+      currentTask: 'Completed',
+      errors: [...errors],
     });
 
-    const { candidates, errors: scanErrors } = await sourceFileService.scanComicCandidates(basePath);
-    const total = candidates.length;
-    console.info(`[Indexing] Found ${total} candidate comics in ${normalizedBasePath}`);
-
-    const activeComicPaths = new Set<string>();
-    const errors: IndexingError[] = [...scanErrors];
-
-    // 2. Initial pass: Upsert all as pending if they match pattern
-    for (let i = 0; i < total; i++) {
-      const candidate = candidates[i];
-      const comicPath = normalizePath(candidate.path);
-      const relativePath = isSubPath(normalizedBasePath, comicPath) 
-        ? comicPath.substring(normalizedBasePath.length).replace(/^[\\/]/, '')
-        : comicPath;
-      
-      let metadata = extractMetadata(relativePath, pattern);
-      if (!metadata) {
-        metadata = {
-          artist: 'Unknown',
-          series: candidate.title,
-          issue: null,
-        };
-      }
-
-      onProgress?.({
-        current: i + 1,
-        total,
-        currentPath: comicPath,
-        percentage: (i / total) * 50, // First 50% for discovery
-        currentTask: 'Discovering comics...',
-        errors: [],
-      });
-
-      try {
-        await comicService.upsertComic({
-          path: comicPath,
-          source_type: candidate.sourceType,
-          title: candidate.title,
-          artist: metadata.artist,
-          series: metadata.series,
-          issue: metadata.issue,
-          cover_image_path: null,
-          page_count: 0,
-          indexing_status: 'pending',
-        });
-        activeComicPaths.add(comicPath);
-      } catch (err) {
-        console.error(`[Indexing] Failed initial upsert for ${comicPath}`, err);
-      }
-    }
-
-    // 3. Second pass: Process each comic
-    const activeComicsArray = Array.from(activeComicPaths);
-    const toProcessCount = activeComicsArray.length;
-
-    for (let i = 0; i < toProcessCount; i++) {
-      const comicPath = activeComicsArray[i];
-      const candidate = candidates.find(c => normalizePath(c.path) === comicPath)!;
-      
-      onProgress?.({
-        current: i + 1,
-        total: toProcessCount,
-        currentPath: comicPath,
-        percentage: 50 + (i / toProcessCount) * 50,
-        currentTask: 'Processing comic content...',
-        errors: [...errors],
-      });
-
-      try {
-        const currentComic = await comicService.getComicByPath(comicPath);
-        if (!currentComic) {
-          throw new Error('Comic not found in database during indexing');
-        }
-        const comicId = currentComic.id;
-
-        const existingPages = await pageService.getPagesByComicId(comicId);
-        const allThumbnailsExist = existingPages.length > 0 && existingPages.every(p => p.thumbnail_exists === 1);
-
-        if (!fullReindex && allThumbnailsExist && currentComic.indexing_status === 'completed' && currentComic.thumbnail_path) {
-          console.info(`[Indexing] Skipping ${comicPath} (already indexed with thumbnails)`);
-          activeComicPaths.add(comicPath);
-          continue;
-        }
-
-        await comicService.updateIndexingStatus(comicId, 'processing');
-
-        const pages = await sourceFileService.getComicPages(
-          basePath,
-          toProcessCount,
-          i + 1,
-          comicPath,
-          candidate.sourceType,
-          fullReindex
-        );
-
-        if (pages.length === 0) {
-          console.warn(`[Indexing] Skipping ${comicPath} (no pages found)`);
-          await comicService.updateIndexingStatus(comicId, 'failed', 'No pages found');
-          continue;
-        }
-
-        const existingThumbnailByPageNumber = new Map<number, string>();
-        existingPages.forEach((page) => {
-          if (page.thumbnail_path) {
-            existingThumbnailByPageNumber.set(page.page_number, page.thumbnail_path);
-          }
-        });
-
-        const thumbnailByPageNumber = await generatePdfFallbackThumbnails(
-          pages,
-          comicId,
-          existingThumbnailByPageNumber,
-          fullReindex,
-          (current, totalPages) => {
-            onProgress?.({
-              current: i + 1,
-              total: toProcessCount,
-              currentPath: comicPath,
-              percentage: 50 + (i / toProcessCount) * 50,
-              currentTask: `Generating PDF thumbnails (${current}/${totalPages})`,
-              errors: [...errors],
-            });
-          }
-        );
-
-        const finalPages = pages.map((page) => {
-          const fallbackThumb = thumbnailByPageNumber.get(page.pageNumber);
-          if (!fallbackThumb) return page;
-          return { ...page, thumbnailPath: fallbackThumb, thumbnailExists: true };
-        });
-
-        const coverImagePath = finalPages.find(p => p.pageNumber === 1)?.filePath ?? finalPages[0].filePath;
-
-        // Update comic with full details
-        const relativePath = isSubPath(normalizedBasePath, comicPath) 
-          ? comicPath.substring(normalizedBasePath.length).replace(/^[\\/]/, '')
-          : comicPath;
-        let metadata = extractMetadata(relativePath, pattern);
-        if (!metadata) {
-          metadata = {
-            artist: 'Unknown',
-            series: candidate.title,
-            issue: null,
-          };
-        }
-
-        await comicService.upsertComic({
-          path: comicPath,
-          source_type: candidate.sourceType,
-          title: candidate.title,
-          artist: metadata.artist,
-          series: metadata.series,
-          issue: metadata.issue,
-          cover_image_path: coverImagePath,
-          page_count: finalPages.length,
-          indexing_status: 'completed',
-        });
-
-        await pageService.insertPages(
-          comicId,
-          finalPages.map((page) => ({
-            page_number: page.pageNumber,
-            file_path: normalizePath(page.filePath),
-            file_name: page.fileName,
-            source_type: page.sourceType,
-            source_path: normalizePath(page.sourcePath ?? page.filePath),
-            archive_entry_path: page.archiveEntryPath,
-            pdf_page_number: page.pdfPageNumber,
-            thumbnail_path: page.thumbnailPath ? normalizePath(page.thumbnailPath) : null,
-            thumbnail_exists: page.thumbnailExists ? 1 : 0,
-          }))
-        );
-        activeComicPaths.add(comicPath);
-      } catch (err) {
-        console.error(`[Indexing] Failed processing ${comicPath}`, err);
-        const errorMsg = err instanceof Error ? err.message : String(err);
-        errors.push({ path: comicPath, message: errorMsg });
-        
-        try {
-          const currentComic = await comicService.getComicByPath(comicPath);
-          if (currentComic) {
-            await comicService.updateIndexingStatus(currentComic.id, 'failed', errorMsg);
-          }
-        } catch (statusErr) {
-          console.error(`[Indexing] Failed to update failure status for ${comicPath}`, statusErr);
-        }
-      }
-    }
-
-    console.info('[Indexing] Running stale comic cleanup for current base path');
+    // Rows the backend has no index state for, e.g. from before it indexed
+    // the library, are not part of its removal diff.
     const allDbComics = await comicService.getAllComics();
     for (const dbComic of allDbComics) {
-      if (isSubPath(basePath, dbComic.path) && !activeComicPaths.has(dbComic.path)) {
+      if (isSubPath(basePath, dbComic.path) && !activeComicPaths.has(normalizePath(dbComic.path))) {
         console.info(`[Indexing] Removing stale comic ${dbComic.path} (id: ${dbComic.id})`);
         await comicService.deleteComic(dbComic.id);
       }
     }
+    if (result.removedComicPaths.length > 0) {
+      window.dispatchEvent(new CustomEvent('library-updated'));
+    }
 
     console.info(
-      `[Indexing] Finished base path ${normalizedBasePath}. Indexed ${activeComicPaths.size}/${toProcessCount} comics. Errors: ${errors.length}`
+      `[Indexing] Finished base path ${normalizedBasePath}. Indexed ${indexedCount}, unchanged ${result.unchangedComicPaths.length}, removed ${result.removedComicPaths.length}. Errors: ${errors.length}`
     );
-
     return activeComicPaths;
   } finally {
     await unlistenRustProgress();
@@ -420,7 +295,8 @@ export const indexComics = async (
 
 export const reindexAll = async (
   mode: 'quick' | 'full' = 'quick',
-  onProgress?: (progress: GlobalIndexingProgress) => void
+  onProgress?: (progress: GlobalIndexingProgress) => void,
+  options: Omit<IndexComicsOptions, 'metadataPrecedence'> = {}
 ): Promise<void> => {
   const paths = await getAllIndexPaths();
   const totalPaths = paths.length;
@@ -452,7 +328,7 @@ export const reindexAll = async (
         totalPaths,
         errors: [...allErrors],
       });
-    });
+    }, { ...options, metadataPrecedence: path.metadata_precedence });
 
     indexedPaths.forEach((indexedPath) => {
       activeComicPaths.add(indexedPath);
@@ -480,11 +356,34 @@ export const reindexAll = async (
 export const reindexPathById = async (
   id: number,
   mode: 'quick' | 'full' = 'quick',
-  onProgress?: (progress: IndexingProgress) => void
+  onProgress?: (progress: IndexingProgress) => void,
+  options: Omit<IndexComicsOptions, 'metadataPrecedence'> = {}
 ): Promise<void> => {
   const paths = await getAllIndexPaths();
   const path = paths.find((currentPath) => currentPath.id === id);
   if (!path) throw new Error(`Index path with ID ${id} not found`);
 
-  await indexComics(path.path, path.pattern, mode, onProgress);
+  await indexComics(path.path, path.pattern, mode, onProgress, {
+    ...options,
+    metadataPrecedence: path.metadata_precedence,
+  });
+};
+
+/**
+ * Generates the page thumbnails that cover-only indexing deferred, with one
+ * backend job per index path. Rejects with an `IndexingCancelledError` when a
+ * job is cancelled.
+ */
+export const fillPageThumbnails = async (onJobStarted?: (jobId: number) => void): Promise<void> => {
+  const paths = await getAllIndexPaths();
+  for (const path of paths) {
+    const normalizedBasePath = normalizePath(path.path);
+    const { status, error } = await runBackendJob(() => startThumbnailFillJob(path.path), onJobStarted);
+    if (status === 'cancelled') {
+      throw new IndexingCancelledError(normalizedBasePath);
+    }
+    if (status === 'failed') {
+      throw new Error(error ?? `Filling thumbnails of ${normalizedBasePath} failed`);
+    }
+  }
 };
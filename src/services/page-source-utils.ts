@@ -1,13 +1,15 @@
 import type { ComicPage } from '../types/comic';
-import { getImageUrl } from '../utils/image-utils';
-import { readArchiveImageEntry, readBinaryFile } from './source-file-service';
+import { getComicPageUrl, getImageUrl, getThumbnailUrl, isPageProcessed, type PageProcessing } from '../utils/image-utils';
+import { readArchiveImageEntry, readPdfPage } from './source-file-service';
 
 const resolvedUrlCache = new Map<string, string>();
-let pdfWorkerInitialized = false;
 
-const toCacheKey = (page: ComicPage, preferThumbnail: boolean): string => {
+const toCacheKey = (page: ComicPage, preferThumbnail: boolean, processing?: PageProcessing): string => {
   return [
     preferThumbnail ? 'thumb' : 'full',
+    processing?.width ?? '',
+    processing?.height ?? '',
+    processing?.crop ? `${processing.crop.tolerance}-${processing.crop.minContentPercent}` : '',
     page.id,
     page.source_type ?? 'image',
     page.source_path ?? '',
@@ -18,219 +20,63 @@ const toCacheKey = (page: ComicPage, preferThumbnail: boolean): string => {
   ].join('|');
 };
 
-const initPdfWorker = async (): Promise<any> => {
-  const pdfjsLib = await import('pdfjs-dist');
-  if (!pdfWorkerInitialized) {
-    const workerModule = await import('pdfjs-dist/build/pdf.worker.min.mjs?url');
-    (pdfjsLib as any).GlobalWorkerOptions.workerSrc = workerModule.default;
-    pdfWorkerInitialized = true;
-  }
-  return pdfjsLib;
-};
-
-const renderPdfPageToBlob = async (pdfPath: string, pageNumber: number, scale: number, mimeType: 'image/jpeg' | 'image/png', quality?: number): Promise<Blob> => {
-  const pdfjsLib = await initPdfWorker();
-  const bytes = await readBinaryFile(pdfPath);
-  const loadingTask = (pdfjsLib as any).getDocument({ data: bytes });
-  const pdf = await loadingTask.promise;
-  const page = await pdf.getPage(pageNumber);
-  const viewport = page.getViewport({ scale });
-  const canvas = document.createElement('canvas');
-  canvas.width = viewport.width;
-  canvas.height = viewport.height;
-  const context = canvas.getContext('2d');
-  if (!context) {
-    throw new Error('Failed to create canvas context for PDF page rendering');
-  }
-
-  try {
-    await page.render({ canvasContext: context, viewport }).promise;
-    const blob = await new Promise<Blob | null>((resolve) => {
-      canvas.toBlob((nextBlob) => resolve(nextBlob), mimeType, quality);
-    });
-
-    if (!blob) {
-      throw new Error('Failed to render PDF page to blob');
-    }
-
-    return blob;
-  } finally {
-    context.clearRect(0, 0, canvas.width, canvas.height);
-    canvas.width = 0;
-    canvas.height = 0;
-
-    try {
-      page.cleanup();
-    } catch {
-      // ignore cleanup errors
-    }
-
-    try {
-      pdf.cleanup();
-    } catch {
-      // ignore cleanup errors
-    }
-
-    try {
-      await pdf.destroy();
-    } catch {
-      // ignore destroy errors
-    }
-
-    try {
-      await loadingTask.destroy();
-    } catch {
-      // ignore destroy errors
-    }
+/**
+ * Comic path and page number a page is requested by from the `comic://`
+ * protocol, or null when it is loaded as a plain file. Image pages only go
+ * through the protocol when they are processed, addressed by their folder.
+ */
+export const getComicPageRequest = (
+  page: ComicPage,
+  processing?: PageProcessing
+): { comicPath: string; pageNumber: number } | null => {
+  const sourceType = page.source_type ?? 'image';
+  const sourcePath = page.source_path ?? page.file_path;
+  if (sourceType === 'pdf') {
+    return { comicPath: sourcePath, pageNumber: page.pdf_page_number ?? page.page_number };
   }
-};
-
-const renderPdfPage = async (pdfPath: string, pageNumber: number): Promise<string> => {
-  const blob = await renderPdfPageToBlob(pdfPath, pageNumber, 1.2, 'image/jpeg', 0.9);
-  return await new Promise<string>((resolve, reject) => {
-    const reader = new FileReader();
-    reader.onloadend = () => {
-      if (typeof reader.result === 'string') {
-        resolve(reader.result);
-      } else {
-        reject(new Error('Failed to convert PDF blob to data URL'));
-      }
-    };
-    reader.onerror = () => reject(new Error('Failed to read PDF blob as data URL'));
-    reader.readAsDataURL(blob);
-  });
-};
 
-export const renderPdfPagesToPngBytes = async (
-  pdfPath: string,
-  pageNumbers: number[],
-  onPage?: (pageNumber: number, bytes: Uint8Array) => Promise<void>
-): Promise<Map<number, Uint8Array>> => {
-  const output = new Map<number, Uint8Array>();
-  if (pageNumbers.length === 0) {
-    return output;
+  if (sourceType === 'archive') {
+    return page.archive_entry_path ? { comicPath: sourcePath, pageNumber: page.page_number } : null;
   }
 
-  const pdfjsLib = await initPdfWorker();
-  const bytes = await readBinaryFile(pdfPath);
-  const loadingTask = (pdfjsLib as any).getDocument({ data: bytes });
-  const pdf = await loadingTask.promise;
-
-  try {
-    for (const pageNumber of pageNumbers) {
-      const page = await pdf.getPage(pageNumber);
-      
-      // Target a maximum dimension of 300px for thumbnails to save memory and IPC bandwidth
-      const viewport1 = page.getViewport({ scale: 1.0 });
-      const targetDim = 300;
-      const scale = Math.min(targetDim / viewport1.width, targetDim / viewport1.height);
-      const viewport = page.getViewport({ scale });
-      
-      const canvas = document.createElement('canvas');
-      canvas.width = viewport.width;
-      canvas.height = viewport.height;
-      const context = canvas.getContext('2d');
-      if (!context) {
-        throw new Error('Failed to create canvas context for PDF thumbnail rendering');
-      }
-
-      try {
-        await page.render({ canvasContext: context, viewport }).promise;
-        const blob = await new Promise<Blob | null>((resolve) => {
-          // Use image/jpeg for smaller payload size if possible, or stick to png for quality
-          canvas.toBlob((nextBlob) => resolve(nextBlob), 'image/jpeg', 0.8);
-        });
-
-        if (!blob) {
-          throw new Error('Failed to render PDF page to blob');
-        }
-
-        const pageBytes = new Uint8Array(await blob.arrayBuffer());
-        if (onPage) {
-          try {
-            await onPage(pageNumber, pageBytes);
-          } catch (err) {
-            console.error(`[PageSourceUtils] onPage callback failed for page ${pageNumber}`, err);
-          }
-        } else {
-          output.set(pageNumber, pageBytes);
-        }
-      } finally {
-        context.clearRect(0, 0, canvas.width, canvas.height);
-        canvas.width = 0;
-        canvas.height = 0;
-        try {
-          page.cleanup();
-        } catch {
-          // ignore cleanup errors
-        }
-      }
-    }
-
-    return output;
-  } finally {
-    try {
-      pdf.cleanup();
-    } catch {
-      // ignore cleanup errors
-    }
-
-    try {
-      await pdf.destroy();
-    } catch {
-      // ignore destroy errors
-    }
-
-    try {
-      await loadingTask.destroy();
-    } catch {
-      // ignore destroy errors
-    }
+  const folderPath = page.file_path.replace(/[\\/][^\\/]*$/, '');
+  if (!isPageProcessed(processing) || !folderPath || folderPath === page.file_path) {
+    return null;
   }
+  return { comicPath: folderPath, pageNumber: page.page_number };
 };
 
-export const resolvePageImageUrl = async (page: ComicPage, preferThumbnail = false): Promise<string> => {
+export const resolvePageImageUrl = async (
+  page: ComicPage,
+  preferThumbnail = false,
+  processing?: PageProcessing
+): Promise<string> => {
   if (preferThumbnail && page.thumbnail_path) {
-    return getImageUrl(page.thumbnail_path);
+    return getThumbnailUrl(page.thumbnail_path);
   }
 
-  const cacheKey = toCacheKey(page, preferThumbnail);
+  const cacheKey = toCacheKey(page, preferThumbnail, processing);
   const cached = resolvedUrlCache.get(cacheKey);
   if (cached) {
     return cached;
   }
 
-  const sourceType = page.source_type ?? 'image';
-  if (sourceType === 'image') {
-    const directUrl = getImageUrl(page.file_path);
-    resolvedUrlCache.set(cacheKey, directUrl);
-    return directUrl;
-  }
-
-  if (sourceType === 'pdf') {
-    const pageNumber = page.pdf_page_number ?? page.page_number;
-    const sourcePath = page.source_path ?? page.file_path;
-    const rendered = await renderPdfPage(sourcePath, pageNumber);
-    resolvedUrlCache.set(cacheKey, rendered);
-    return rendered;
-  }
-
-  const sourcePath = page.source_path ?? page.file_path;
-  const entryPath = page.archive_entry_path;
-  if (!entryPath) {
-    const fallbackUrl = getImageUrl(page.file_path);
-    resolvedUrlCache.set(cacheKey, fallbackUrl);
-    return fallbackUrl;
-  }
-
-  const bytes = await readArchiveImageEntry(sourcePath, entryPath);
-  const archiveUrl = URL.createObjectURL(new Blob([bytes]));
-
-  resolvedUrlCache.set(cacheKey, archiveUrl);
-  return archiveUrl;
+  const request = getComicPageRequest(page, processing);
+  const url = request
+    ? getComicPageUrl(request.comicPath, request.pageNumber, processing)
+    : getImageUrl(page.file_path);
+  resolvedUrlCache.set(cacheKey, url);
+  return url;
 };
 
-export const resolvePagePreviewUrl = async (page: ComicPage): Promise<string> => {
+/**
+ * Thumbnail of a page, in the tier that covers `displaySize` (longest edge in
+ * CSS pixels) when given. Pages without a thumbnail are shown in full.
+ */
+export const resolvePagePreviewUrl = async (page: ComicPage, displaySize?: number): Promise<string> => {
+  if (page.thumbnail_path) {
+    return getThumbnailUrl(page.thumbnail_path, displaySize);
+  }
   return await resolvePageImageUrl(page, true);
 };
 
@@ -243,8 +89,7 @@ export const decodeImageBytesForThumbnail = async (page: ComicPage): Promise<Uin
   if (sourceType === 'pdf') {
     const pageNumber = page.pdf_page_number ?? page.page_number;
     const sourcePath = page.source_path ?? page.file_path;
-    const blob = await renderPdfPageToBlob(sourcePath, pageNumber, 1.0, 'image/png');
-    return new Uint8Array(await blob.arrayBuffer());
+    return await readPdfPage(sourcePath, pageNumber);
   }
 
   const sourcePath = page.source_path ?? page.file_path;
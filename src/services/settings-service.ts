@@ -25,6 +25,10 @@ export type AppSettings = {
   showViewCount: boolean;
   appZoom: number;               // percentage, e.g. 100
   saveOpenedTabs: boolean;       // save and restore tabs on app restart
+  cropBorders: boolean;          // crop uniform scanner borders off pages
+  cropTolerance: number;         // brightness difference, 0-255, still counted as border
+  cropMinContent: number;        // percentage of a page that is never cropped
+  coverOnlyThumbnails: boolean;  // index only cover thumbnails, pages get theirs on demand
 };
 
 export const DEFAULT_SETTINGS: AppSettings = {
@@ -52,6 +56,10 @@ export const DEFAULT_SETTINGS: AppSettings = {
   showViewCount: false,
   appZoom: 100,
   saveOpenedTabs: true,
+  cropBorders: false,
+  cropTolerance: 24,
+  cropMinContent: 50,
+  coverOnlyThumbnails: false,
 };
 
 const STORE_PATH = 'settings.json';
@@ -1,5 +1,6 @@
-import { invoke } from '@tauri-apps/api/core';
+import { Channel, invoke } from '@tauri-apps/api/core';
 import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+import type { BorderCropOptions } from '../utils/image-utils';
 
 export type ComicCandidate = {
   path: string;
@@ -23,6 +24,7 @@ export type IndexedPagePayload = {
   pdfPageNumber: number | null;
   thumbnailPath: string | null;
   thumbnailExists: boolean;
+  pageType: string | null;
 };
 
 export type IndexedComicPayload = {
@@ -32,24 +34,96 @@ export type IndexedComicPayload = {
   artist: string | null;
   series: string | null;
   issue: string | null;
+  volume: number | null;
+  writer: string | null;
+  penciller: string | null;
+  publisher: string | null;
+  year: number | null;
+  summary: string | null;
+  genre: string | null;
+  manga: boolean | null;
   coverImagePath: string | null;
   pageCount: number;
   pages: IndexedPagePayload[];
 };
 
 export type BuildIndexPayloadResult = {
-  comics: IndexedComicPayload[];
+  added: IndexedComicPayload[];
+  changed: IndexedComicPayload[];
+  removedComicPaths: string[];
+  unchangedComicPaths: string[];
   activeComicPaths: string[];
   errors: { path: string; message: string }[];
 };
 
+export type RustIndexingPhase = 'scanning' | 'indexing' | 'finalizing' | 'completed';
+
+export type StorageKind = 'solidState' | 'rotational' | 'network';
+
 export type RustIndexingProgressEvent = {
   basePath: string;
-  totalComics: number;
-  currentComic: number;
-  currentPath: string;
+  phase: RustIndexingPhase;
+  currentPath: string | null;
+  comicsDone: number;
+  comicsTotal: number;
+  pagesDone: number;
+  pagesTotal: number;
+  bytesProcessed: number;
   percentage: number;
-  currentTask: string;
+  elapsedMs: number;
+  etaMs: number | null;
+  pagesPerSecond: number;
+  bytesPerSecond: number;
+  /** Storage of the index path and the comics read at the same time on it. */
+  storage: StorageKind | null;
+  workers: number;
+  comicsActive: number;
+};
+
+export type MetadataPrecedence = 'comicInfo' | 'pattern';
+
+export type IndexedComicEvent = {
+  change: 'added' | 'changed';
+  comic: IndexedComicPayload;
+};
+
+export type IndexingJobStatus = 'completed' | 'cancelled' | 'failed';
+
+export type IndexingJobFinishedEvent = {
+  jobId: number;
+  basePath: string;
+  status: IndexingJobStatus;
+  error: string | null;
+  result: BuildIndexPayloadResult | null;
+};
+
+export type WatchedIndexPath = {
+  path: string;
+  pattern: string;
+  metadataPrecedence?: MetadataPrecedence;
+};
+
+export type WatchStatus = {
+  path: string;
+  polling: boolean;
+  error: string | null;
+};
+
+export type LibraryChangedEvent = BuildIndexPayloadResult & {
+  basePath: string;
+};
+
+export type ComicInfoUpdate = {
+  series: string | null;
+  number: string | null;
+  volume: number | null;
+  writer: string | null;
+  penciller: string | null;
+  publisher: string | null;
+  year: number | null;
+  summary: string | null;
+  genre: string | null;
+  manga: boolean | null;
 };
 
 export type ScanResult = {
@@ -61,31 +135,38 @@ export const scanComicCandidates = async (basePath: string): Promise<ScanResult>
   return await invoke<ScanResult>('scan_comic_candidates', { basePath });
 };
 
-export const buildIndexPayloadForPath = async (
-  basePath: string,
-  pattern: string
-): Promise<BuildIndexPayloadResult> => {
-  return await invoke<BuildIndexPayloadResult>('build_index_payload_for_path', { basePath, pattern });
-};
-
-export const getComicPages = async (
+export const startIndexingJob = async (
   basePath: string,
-  totalComics: number,
-  currentComic: number,
-  comicPath: string,
-  sourceType: string,
-  fullReindex: boolean
-): Promise<IndexedPagePayload[]> => {
-  return await invoke<IndexedPagePayload[]>('get_comic_pages', {
+  pattern: string,
+  onComic: (event: IndexedComicEvent) => void,
+  metadataPrecedence?: MetadataPrecedence,
+  fullReindex?: boolean,
+  coverOnly?: boolean
+): Promise<number> => {
+  const channel = new Channel<IndexedComicEvent>();
+  channel.onmessage = onComic;
+  return await invoke<number>('start_indexing_job', {
     basePath,
-    totalComics,
-    currentComic,
-    comicPath,
-    sourceType,
+    pattern,
+    metadataPrecedence,
     fullReindex,
+    coverOnly,
+    onComic: channel,
   });
 };
 
+export const cancelIndexingJob = async (jobId: number): Promise<void> => {
+  await invoke('cancel_indexing_job', { jobId });
+};
+
+export const pauseIndexingJob = async (jobId: number): Promise<void> => {
+  await invoke('pause_indexing_job', { jobId });
+};
+
+export const resumeIndexingJob = async (jobId: number): Promise<void> => {
+  await invoke('resume_indexing_job', { jobId });
+};
+
 export const listImagePages = async (comicDirPath: string): Promise<ImagePageEntry[]> => {
   return await invoke<ImagePageEntry[]>('list_image_pages', { comicDirPath });
 };
@@ -99,6 +180,19 @@ export const countPdfPages = async (path: string): Promise<number> => {
   return await invoke<number>('count_pdf_pages', { path });
 };
 
+export const readPdfPage = async (path: string, pageNumber: number): Promise<Uint8Array> => {
+  const bytes = await invoke<number[]>('read_pdf_page', { path, pageNumber });
+  return new Uint8Array(bytes);
+};
+
+export const writeComicInfo = async (
+  path: string,
+  sourceType: string,
+  metadata: ComicInfoUpdate
+): Promise<void> => {
+  await invoke('write_comic_info', { path, sourceType, metadata });
+};
+
 export const listArchiveImageEntries = async (path: string): Promise<string[]> => {
   return await invoke<string[]>('list_archive_image_entries', { path });
 };
@@ -113,6 +207,31 @@ export const readArchiveImageEntriesBatch = async (path: string, entryPaths: str
   return entries.map((bytes) => new Uint8Array(bytes));
 };
 
+export type ReaderViewMode = 'overview' | 'single' | 'scroll';
+
+export type PrefetchOptions = {
+  ahead?: number;
+  behind?: number;
+  /** Processing of the pages, as passed to the `comic://` URLs. */
+  width?: number;
+  height?: number;
+  crop?: BorderCropOptions;
+};
+
+/**
+ * Reads the pages around `pageNumber` into the backend page cache, so the
+ * `comic://` protocol serves them from memory. `ahead` and `behind` default
+ * to a window suited to `viewMode`.
+ */
+export const prefetchPages = async (
+  comicPath: string,
+  pageNumber: number,
+  viewMode: ReaderViewMode,
+  options: PrefetchOptions = {}
+): Promise<void> => {
+  await invoke('prefetch_pages', { comicPath, pageNumber, viewMode, ...options });
+};
+
 export const cleanupIndexedThumbnails = async (activeComicPaths: string[]): Promise<void> => {
   await invoke('cleanup_indexed_thumbnails', { activeComicPaths });
 };
@@ -124,3 +243,27 @@ export const listenToRustIndexingProgress = async (
     onEvent(event.payload);
   });
 };
+
+export const watchIndexPaths = async (indexPaths: WatchedIndexPath[]): Promise<WatchStatus[]> => {
+  return await invoke<WatchStatus[]>('watch_index_paths', { indexPaths });
+};
+
+export const unwatchIndexPaths = async (): Promise<void> => {
+  await invoke('unwatch_index_paths');
+};
+
+export const listenToLibraryChanges = async (
+  onEvent: (event: LibraryChangedEvent) => void
+): Promise<UnlistenFn> => {
+  return await listen<LibraryChangedEvent>('library-changed', (event) => {
+    onEvent(event.payload);
+  });
+};
+
+export const listenToIndexingJobFinished = async (
+  onEvent: (event: IndexingJobFinishedEvent) => void
+): Promise<UnlistenFn> => {
+  return await listen<IndexingJobFinishedEvent>('indexing-job-finished', (event) => {
+    onEvent(event.payload);
+  });
+};
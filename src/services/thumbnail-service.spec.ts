@@ -37,6 +37,28 @@ describe('thumbnail-service', () => {
     });
   });
 
+  it('setThumbnailSettings should invoke settings command', async () => {
+    const settings = {
+      maxSize: 400,
+      quality: 90,
+      format: 'png' as const,
+      cacheBudgetMb: 512,
+    };
+
+    await thumbnailService.setThumbnailSettings(settings);
+
+    expect(invoke).toHaveBeenCalledWith('set_thumbnail_settings', { settings });
+  });
+
+  it('ensurePageThumbnails should invoke on-demand thumbnail command', async () => {
+    vi.mocked(invoke).mockResolvedValue(12);
+
+    const count = await thumbnailService.ensurePageThumbnails('7');
+
+    expect(count).toBe(12);
+    expect(invoke).toHaveBeenCalledWith('ensure_page_thumbnails', { comicId: 7 });
+  });
+
   it('cleanupOrphans should invoke orphan cleanup command', async () => {
     await thumbnailService.cleanupOrphans([1, '2']);
 
@@ -33,3 +33,46 @@ export const cleanupOrphans = async (activeComicIds: (string | number)[]): Promi
     activeComicIds: activeComicIds.map((id) => Number(id)),
   });
 };
+
+export type ThumbnailFormat = 'jpeg' | 'png';
+
+export type ThumbnailSettings = {
+  maxSize: number;
+  quality: number;
+  format: ThumbnailFormat;
+  /** Size the thumbnail cache is kept under, 0 for no limit. */
+  cacheBudgetMb: number;
+};
+
+export type ThumbnailCacheStats = {
+  sizeBytes: number;
+  fileCount: number;
+  pinnedBytes: number;
+  budgetBytes: number | null;
+  hits: number;
+  misses: number;
+  hitRate: number | null;
+  evictedFiles: number;
+  evictedBytes: number;
+};
+
+export const getThumbnailSettings = async (): Promise<ThumbnailSettings> => {
+  return await invoke<ThumbnailSettings>('get_thumbnail_settings');
+};
+
+export const setThumbnailSettings = async (settings: ThumbnailSettings): Promise<void> => {
+  await invoke('set_thumbnail_settings', { settings });
+};
+
+export const getThumbnailCacheStats = async (): Promise<ThumbnailCacheStats> => {
+  return await invoke<ThumbnailCacheStats>('get_thumbnail_cache_stats');
+};
+
+export const ensurePageThumbnails = async (comicId: string | number): Promise<number> => {
+  return await invoke<number>('ensure_page_thumbnails', { comicId: Number(comicId) });
+};
+
+/** Generates all deferred page thumbnails below an index path. Returns the job ID. */
+export const startThumbnailFillJob = async (basePath: string): Promise<number> => {
+  return await invoke<number>('start_thumbnail_fill_job', { basePath });
+};
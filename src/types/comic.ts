@@ -46,5 +46,7 @@ export type IndexPath = {
   id: number;
   path: string;
   pattern: string;
+  /** Whether ComicInfo.xml or the path pattern wins when both name a field. */
+  metadata_precedence: 'comicInfo' | 'pattern';
   created_at: string;
 };
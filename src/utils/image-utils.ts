@@ -1,10 +1,116 @@
 import { convertFileSrc } from '@tauri-apps/api/core';
+import type { AppSettings } from '../services/settings-service';
 
 /**
  * Converts a platform-native file path to a URL that can be used in the webview.
  */
 export const getImageUrl = (filePath: string): string => convertFileSrc(filePath);
 
+/**
+ * Bounds in physical pixels a page is downscaled to fit before it is sent to
+ * the webview.
+ */
+export type PageTargetSize = { width?: number; height?: number };
+
+/**
+ * Parameters of the automatic border crop: the brightness difference (0-255)
+ * that still counts as border, and the share of the page in percent that is
+ * always kept.
+ */
+export type BorderCropOptions = { tolerance: number; minContentPercent: number };
+
+/**
+ * Processing the `comic://` protocol applies to a page before sending it.
+ */
+export type PageProcessing = PageTargetSize & { crop?: BorderCropOptions };
+
+/**
+ * Whether a page is processed at all, rather than served as stored.
+ */
+export const isPageProcessed = (processing?: PageProcessing): boolean =>
+  Boolean(processing?.width || processing?.height || processing?.crop);
+
+/**
+ * URL of a page image served by the `comic://` protocol, for archive and PDF
+ * pages that have no file of their own, and for pages that are processed.
+ */
+export const getComicPageUrl = (comicPath: string, pageNumber: number, processing?: PageProcessing): string => {
+  const params = new URLSearchParams({ page: String(pageNumber) });
+  if (processing?.width) params.set('width', String(processing.width));
+  if (processing?.height) params.set('height', String(processing.height));
+  if (processing?.crop) {
+    params.set('crop', '1');
+    params.set('cropTolerance', String(processing.crop.tolerance));
+    params.set('cropMinContent', String(processing.crop.minContentPercent));
+  }
+  return `${convertFileSrc(comicPath, 'comic')}?${params}`;
+};
+
+/**
+ * Longest edge in CSS pixels thumbnails are shown at: library grid cards,
+ * the small cards of the library sidebar and the viewer's "Up Next" strip,
+ * and an overview grid at its default size.
+ */
+export const THUMBNAIL_DISPLAY_SIZES = {
+  card: 400,
+  sidebar: 120,
+  overview: 300,
+} as const;
+
+/**
+ * Display sizes are rounded up to this step, so that one thumbnail keeps one
+ * URL across small layout changes.
+ */
+const THUMBNAIL_SIZE_STEP = 64;
+
+/**
+ * URL of a generated thumbnail served by the `comic://` protocol, which
+ * records each load so that thumbnails in use are evicted last. With a
+ * display size (longest edge in CSS pixels) the smallest tier that covers it
+ * is served instead of the stored one.
+ */
+export const getThumbnailUrl = (thumbnailPath: string, displaySize?: number): string => {
+  const params = new URLSearchParams({ thumbnail: '1' });
+  if (displaySize) {
+    const physical = displaySize * (window.devicePixelRatio || 1);
+    params.set('size', String(Math.ceil(physical / THUMBNAIL_SIZE_STEP) * THUMBNAIL_SIZE_STEP));
+  }
+  return `${convertFileSrc(thumbnailPath, 'comic')}?${params}`;
+};
+
+/**
+ * Target sizes are rounded up to this step, so resizing the window does not
+ * request a new variant of every page.
+ */
+const TARGET_SIZE_STEP = 256;
+
+/**
+ * Size a page fills when fitted to the window, in physical pixels. Pages shown
+ * at their natural size are not downscaled.
+ */
+export const getPageTargetSize = (fitMode: 'width' | 'both' | 'none'): PageTargetSize | undefined => {
+  if (fitMode === 'none') return undefined;
+
+  const ratio = window.devicePixelRatio || 1;
+  const roundUp = (cssPixels: number) => Math.ceil((cssPixels * ratio) / TARGET_SIZE_STEP) * TARGET_SIZE_STEP;
+  const width = roundUp(window.innerWidth);
+  return fitMode === 'both' ? { width, height: roundUp(window.innerHeight) } : { width };
+};
+
+/**
+ * Processing for pages shown with the given fit mode and reader settings.
+ */
+export const getPageProcessing = (
+  fitMode: 'width' | 'both' | 'none',
+  settings: Pick<AppSettings, 'cropBorders' | 'cropTolerance' | 'cropMinContent'>
+): PageProcessing | undefined => {
+  const crop = settings.cropBorders
+    ? { tolerance: settings.cropTolerance, minContentPercent: settings.cropMinContent }
+    : undefined;
+  const processing = { ...getPageTargetSize(fitMode), crop };
+  return isPageProcessed(processing) ? processing : undefined;
+};
+
 /**
  * Natural sort comparator for strings.
  * Properly handles numeric parts so that 'page2.jpg' comes before 'page10.jpg'.